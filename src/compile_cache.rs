@@ -0,0 +1,201 @@
+//! Caches the result of compiling a book to HVM, so that compiling the same program with the
+//! same options again doesn't run the compiler.
+//!
+//! Each result is saved in its own file of the cache directory, named after a hash of the book,
+//! the compile and diagnostics options and the version of the compiler, so a different version
+//! of the compiler never reuses a result. Only the compilations without errors are cached.
+//!
+//! The nets are saved node by node, with the exact bits of each number, so a cached result is
+//! always the same as the one of [`compile_book`].
+
+use crate::{
+  compile_book_with_hook,
+  diagnostics::{Diagnostic, DiagnosticOrigin, Diagnostics, DiagnosticsConfig, Severity},
+  fun::{term_to_net::Labels, Book, Name},
+  incremental::Fnv,
+  maybe_grow, CompileOpts, CompileResult, PhaseInfo,
+};
+use ::hvm::ast::{Net, Numb, Tree};
+use serde::{Deserialize, Serialize};
+use std::{
+  collections::{BTreeMap, HashMap},
+  path::{Path, PathBuf},
+  time::Instant,
+};
+
+#[derive(Serialize, Deserialize)]
+struct CachedCompile {
+  diagnostics: Vec<(DiagnosticOrigin, Vec<Diagnostic>)>,
+  nets: BTreeMap<String, CachedNet>,
+  labels: Labels,
+  name_map: HashMap<String, Name>,
+}
+
+/// A net with its trees flattened with [`flatten_tree`].
+#[derive(Serialize, Deserialize)]
+struct CachedNet {
+  root: Vec<CachedNode>,
+  rbag: Vec<(bool, Vec<CachedNode>, Vec<CachedNode>)>,
+}
+
+/// A node of a tree. The nodes with two children are followed by the nodes of their first child
+/// and then of their second one, so that deep trees don't need deeply nested data to be saved.
+#[derive(Serialize, Deserialize)]
+enum CachedNode {
+  Var(String),
+  Ref(String),
+  Era,
+  Num(u32),
+  Con,
+  Dup,
+  Opr,
+  Swi,
+}
+
+/// Like [`compile_book_with_hook`], but reuses the result of a previous compilation of the same book
+/// with the same options if there's one in `cache_dir`, saving the result there otherwise.
+///
+/// When the result comes from the cache, the book is left as it was given and the hook is only
+/// called once, for a `cache-hit` phase with the time it took to read the result.
+/// Failing to save the result is only a warning, since the result is still valid.
+pub fn compile_book_cached(
+  book: &mut Book,
+  opts: CompileOpts,
+  diagnostics_cfg: DiagnosticsConfig,
+  cache_dir: &Path,
+  hook: Option<&mut dyn FnMut(PhaseInfo)>,
+) -> Result<CompileResult, Diagnostics> {
+  let start = Instant::now();
+  let path = cache_path(cache_dir, book, &opts, &diagnostics_cfg);
+  if let Some(mut result) = read_cache(&path, diagnostics_cfg) {
+    result.diagnostics.def_spans = book.def_spans.clone();
+    if let Some(hook) = hook {
+      hook(PhaseInfo { name: "cache-hit", elapsed: start.elapsed() });
+    }
+    return Ok(result);
+  }
+
+  let mut result = compile_book_with_hook(book, opts, diagnostics_cfg, None, hook)?;
+  if let Err(e) = write_cache(cache_dir, &path, &result) {
+    result.diagnostics.add_diagnostic(e, Severity::Warning, DiagnosticOrigin::Book);
+  }
+  Ok(result)
+}
+
+/// The file where the result of compiling the book with the given options is cached.
+fn cache_path(
+  cache_dir: &Path,
+  book: &Book,
+  opts: &CompileOpts,
+  diagnostics_cfg: &DiagnosticsConfig,
+) -> PathBuf {
+  let mut hasher = Fnv::new();
+  hasher.write(env!("CARGO_PKG_VERSION").as_bytes());
+  hasher.write(format!("{opts:?}").as_bytes());
+  hasher.write(format!("{diagnostics_cfg:?}").as_bytes());
+  hasher.write(&serde_json::to_vec(book).unwrap());
+  hasher.write(format!("{:?}", book.host_defs).as_bytes());
+  hasher.write(format!("{:?}", book.allowed_warnings).as_bytes());
+  cache_dir.join(format!("{:016x}.json", hasher.finish()))
+}
+
+/// Reads a cached result, or returns `None` if there's none or it can't be read.
+fn read_cache(path: &Path, diagnostics_cfg: DiagnosticsConfig) -> Option<CompileResult> {
+  let file = std::fs::File::open(path).ok()?;
+  let cached: CachedCompile = serde_json::from_reader(std::io::BufReader::new(file)).ok()?;
+
+  let mut defs = BTreeMap::new();
+  for (nam, net) in cached.nets {
+    let root = rebuild_tree(net.root)?;
+    let mut rbag = vec![];
+    for (pri, fst, snd) in net.rbag {
+      rbag.push((pri, rebuild_tree(fst)?, rebuild_tree(snd)?));
+    }
+    defs.insert(nam, Net { root, rbag });
+  }
+
+  let mut diagnostics = Diagnostics::new(diagnostics_cfg);
+  diagnostics.diagnostics.extend(cached.diagnostics);
+  Some(CompileResult {
+    diagnostics,
+    hvm_book: ::hvm::ast::Book { defs },
+    labels: cached.labels,
+    name_map: cached.name_map,
+  })
+}
+
+fn write_cache(cache_dir: &Path, path: &Path, result: &CompileResult) -> Result<(), String> {
+  let err =
+    |e: &dyn std::fmt::Display| format!("Failed to write the compilation cache '{}'.\n{e}", path.display());
+
+  let nets = result
+    .hvm_book
+    .defs
+    .iter()
+    .map(|(nam, net)| {
+      let root = flatten_tree(&net.root);
+      let rbag =
+        net.rbag.iter().map(|(pri, fst, snd)| (*pri, flatten_tree(fst), flatten_tree(snd))).collect();
+      (nam.clone(), CachedNet { root, rbag })
+    })
+    .collect();
+  let cached = CachedCompile {
+    diagnostics: result.diagnostics.diagnostics.iter().map(|(o, d)| (o.clone(), d.clone())).collect(),
+    nets,
+    labels: result.labels.clone(),
+    name_map: result.name_map.clone(),
+  };
+
+  std::fs::create_dir_all(cache_dir).map_err(|e| err(&e))?;
+  let file = std::fs::File::create(path).map_err(|e| err(&e))?;
+  serde_json::to_writer(std::io::BufWriter::new(file), &cached).map_err(|e| err(&e))
+}
+
+fn flatten_tree(tree: &Tree) -> Vec<CachedNode> {
+  fn go(tree: &Tree, nodes: &mut Vec<CachedNode>) {
+    maybe_grow(|| {
+      let (node, children) = match tree {
+        Tree::Var { nam } => (CachedNode::Var(nam.clone()), None),
+        Tree::Ref { nam } => (CachedNode::Ref(nam.clone()), None),
+        Tree::Era => (CachedNode::Era, None),
+        Tree::Num { val } => (CachedNode::Num(val.0), None),
+        Tree::Con { fst, snd } => (CachedNode::Con, Some((fst, snd))),
+        Tree::Dup { fst, snd } => (CachedNode::Dup, Some((fst, snd))),
+        Tree::Opr { fst, snd } => (CachedNode::Opr, Some((fst, snd))),
+        Tree::Swi { fst, snd } => (CachedNode::Swi, Some((fst, snd))),
+      };
+      nodes.push(node);
+      if let Some((fst, snd)) = children {
+        go(fst, nodes);
+        go(snd, nodes);
+      }
+    })
+  }
+
+  let mut nodes = vec![];
+  go(tree, &mut nodes);
+  nodes
+}
+
+/// Rebuilds a tree flattened with [`flatten_tree`], returning `None` if the nodes don't form a tree.
+fn rebuild_tree(nodes: Vec<CachedNode>) -> Option<Tree> {
+  fn go(nodes: &mut std::vec::IntoIter<CachedNode>) -> Option<Tree> {
+    maybe_grow(|| {
+      let tree = match nodes.next()? {
+        CachedNode::Var(nam) => Tree::Var { nam },
+        CachedNode::Ref(nam) => Tree::Ref { nam },
+        CachedNode::Era => Tree::Era,
+        CachedNode::Num(val) => Tree::Num { val: Numb(val) },
+        CachedNode::Con => Tree::Con { fst: Box::new(go(nodes)?), snd: Box::new(go(nodes)?) },
+        CachedNode::Dup => Tree::Dup { fst: Box::new(go(nodes)?), snd: Box::new(go(nodes)?) },
+        CachedNode::Opr => Tree::Opr { fst: Box::new(go(nodes)?), snd: Box::new(go(nodes)?) },
+        CachedNode::Swi => Tree::Swi { fst: Box::new(go(nodes)?), snd: Box::new(go(nodes)?) },
+      };
+      Some(tree)
+    })
+  }
+
+  let mut nodes = nodes.into_iter();
+  let tree = go(&mut nodes)?;
+  nodes.next().is_none().then_some(tree)
+}
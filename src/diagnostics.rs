@@ -1,4 +1,5 @@
 use crate::fun::{display::DisplayFn, Name};
+use serde::{Deserialize, Serialize};
 use std::{
   collections::BTreeMap,
   fmt::{Display, Formatter},
@@ -11,26 +12,77 @@ pub struct Diagnostics {
   err_counter: usize,
   pub diagnostics: BTreeMap<DiagnosticOrigin, Vec<Diagnostic>>,
   pub config: DiagnosticsConfig,
+  /// The warnings allowed in each definition with `# allow(...)`, regardless of the config.
+  pub allowed_warnings: BTreeMap<Name, Vec<WarningType>>,
+  /// The region of the source code of each definition, given to the messages about it that have no span.
+  pub def_spans: BTreeMap<Name, TextSpan>,
+  /// Messages about what the passes did, added only with a verbose config.
+  /// They're not problems with the program, so they're shown before the warnings and left out of the JSON.
+  pub notes: Vec<String>,
 }
 
 #[derive(Debug, Clone, Copy)]
 pub struct DiagnosticsConfig {
   pub verbose: bool,
+  /// Hides the warnings, only showing the errors.
+  pub quiet: bool,
   pub irrefutable_match: Severity,
   pub redundant_match: Severity,
   pub unreachable_match: Severity,
   pub unused_definition: Severity,
   pub repeated_bind: Severity,
   pub recursion_cycle: Severity,
+  pub shadowed_builtin: Severity,
+  pub missing_base_case: Severity,
+  pub ctr_arity: Severity,
+  pub shadowed_bind: Severity,
+  pub numeric_overflow: Severity,
+  pub unused_bind: Severity,
+  pub naming_convention: Severity,
+  pub redundant_lambda: Severity,
+  pub potential_exponential: Severity,
+  pub match_arm_order: Severity,
+  /// Reports the user definitions removed by definition pruning.
+  pub report_pruned: bool,
+  pub format: DiagnosticsFormat,
+  /// Whether the text format uses terminal colors and styles.
+  pub color: bool,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Diagnostic {
   message: String,
   severity: Severity,
+  warning_type: Option<WarningType>,
+  span: Option<TextSpan>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+/// How the diagnostics are displayed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DiagnosticsFormat {
+  /// Human readable messages, grouped by severity and origin.
+  #[default]
+  Text,
+  /// A JSON array with one object per message, for tools and editor integrations.
+  Json,
+}
+
+/// A region of a source file, as byte offsets and 1-based line and column numbers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TextSpan {
+  pub ini: TextLocation,
+  pub end: TextLocation,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TextLocation {
+  pub byte: usize,
+  pub line: usize,
+  pub column: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "name", rename_all = "snake_case")]
 pub enum DiagnosticOrigin {
   /// An error from the relationship between multiple top-level definitions.
   Book,
@@ -42,14 +94,16 @@ pub enum DiagnosticOrigin {
   Readback,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum Severity {
   Allow,
   Warning,
   Error,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum WarningType {
   IrrefutableMatch,
   RedundantMatch,
@@ -57,11 +111,61 @@ pub enum WarningType {
   UnusedDefinition,
   RepeatedBind,
   RecursionCycle,
+  ShadowedBuiltin,
+  MissingBaseCase,
+  CtrArity,
+  ShadowedBind,
+  NumericOverflow,
+  UnusedBind,
+  NamingConvention,
+  RedundantLambda,
+  PotentialExponential,
+  MatchArmOrder,
+}
+
+impl WarningType {
+  /// Finds a warning by the name used in the command line options, like `unused-definition`.
+  pub fn from_name(name: &str) -> Option<WarningType> {
+    match name {
+      "irrefutable-match" => Some(WarningType::IrrefutableMatch),
+      "redundant-match" => Some(WarningType::RedundantMatch),
+      "unreachable-match" => Some(WarningType::UnreachableMatch),
+      "unused-definition" => Some(WarningType::UnusedDefinition),
+      "repeated-bind" => Some(WarningType::RepeatedBind),
+      "recursion-cycle" => Some(WarningType::RecursionCycle),
+      "shadowed-builtin" => Some(WarningType::ShadowedBuiltin),
+      "missing-base-case" => Some(WarningType::MissingBaseCase),
+      "ctr-arity" => Some(WarningType::CtrArity),
+      "shadowed-bind" => Some(WarningType::ShadowedBind),
+      "numeric-overflow" => Some(WarningType::NumericOverflow),
+      "unused-bind" => Some(WarningType::UnusedBind),
+      "naming-convention" => Some(WarningType::NamingConvention),
+      "redundant-lambda" => Some(WarningType::RedundantLambda),
+      "potential-exponential" => Some(WarningType::PotentialExponential),
+      "match-arm-order" => Some(WarningType::MatchArmOrder),
+      _ => None,
+    }
+  }
 }
 
 impl Diagnostics {
   pub fn new(config: DiagnosticsConfig) -> Self {
-    Self { err_counter: 0, diagnostics: Default::default(), config }
+    Self {
+      err_counter: 0,
+      diagnostics: Default::default(),
+      config,
+      allowed_warnings: Default::default(),
+      def_spans: Default::default(),
+      notes: Default::default(),
+    }
+  }
+
+  /// Displays the diagnostics with the format, colors and quietness of `config`,
+  /// keeping the rest of their own config.
+  pub fn set_display_config(&mut self, config: DiagnosticsConfig) {
+    self.config.format = config.format;
+    self.config.color = config.color;
+    self.config.quiet = config.quiet;
   }
 
   pub fn add_book_error(&mut self, err: impl std::fmt::Display) {
@@ -80,11 +184,18 @@ impl Diagnostics {
   }
 
   pub fn add_rule_warning(&mut self, warn: impl std::fmt::Display, warn_type: WarningType, def_name: Name) {
-    let severity = self.config.warning_severity(warn_type);
+    let def_name = def_name.def_name_from_generated();
+    let severity = if self.allowed_warnings.get(&def_name).is_some_and(|allowed| allowed.contains(&warn_type))
+    {
+      Severity::Allow
+    } else {
+      self.config.warning_severity(warn_type)
+    };
     if severity == Severity::Error {
       self.err_counter += 1;
     }
-    self.add_diagnostic(warn, severity, DiagnosticOrigin::Rule(def_name.def_name_from_generated()));
+    let orig = DiagnosticOrigin::Rule(def_name);
+    self.push_diagnostic(orig, Diagnostic::new(warn, severity).with_warning_type(warn_type));
   }
 
   pub fn add_book_warning(&mut self, warn: impl std::fmt::Display, warn_type: WarningType) {
@@ -92,11 +203,26 @@ impl Diagnostics {
     if severity == Severity::Error {
       self.err_counter += 1;
     }
-    self.add_diagnostic(warn, severity, DiagnosticOrigin::Book);
+    self
+      .push_diagnostic(DiagnosticOrigin::Book, Diagnostic::new(warn, severity).with_warning_type(warn_type));
+  }
+
+  /// Adds an error that refers to a specific region of the source code.
+  pub fn add_source_error(&mut self, err: impl std::fmt::Display, span: TextSpan) {
+    self.err_counter += 1;
+    self.push_diagnostic(DiagnosticOrigin::Book, Diagnostic::new(err, Severity::Error).with_span(span));
   }
 
   pub fn add_diagnostic(&mut self, msg: impl ToString, severity: Severity, orig: DiagnosticOrigin) {
-    let diag = Diagnostic { message: msg.to_string(), severity };
+    self.push_diagnostic(orig, Diagnostic::new(msg, severity))
+  }
+
+  /// Adds a message about what a pass did, which is not a warning or an error.
+  pub fn add_note(&mut self, note: impl ToString) {
+    self.notes.push(note.to_string())
+  }
+
+  fn push_diagnostic(&mut self, orig: DiagnosticOrigin, diag: Diagnostic) {
     self.diagnostics.entry(orig).or_default().push(diag)
   }
 
@@ -204,13 +330,40 @@ impl Diagnostics {
 
 impl Display for Diagnostics {
   fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-    if self.has_severity(Severity::Warning) {
-      write!(f, "\x1b[4m\x1b[1m\x1b[33mWarnings:\x1b[0m\n{}", self.display_with_severity(Severity::Warning))?;
+    if self.config.format == DiagnosticsFormat::Json {
+      return write!(f, "{}", self.display_json());
     }
-    if self.has_severity(Severity::Error) {
-      write!(f, "\x1b[4m\x1b[1m\x1b[31mErrors:\x1b[0m\n{}", self.display_with_severity(Severity::Error))?;
+    if !self.config.color {
+      return write!(f, "{}", strip_ansi_escapes(&self.display_text().to_string()));
     }
-    Ok(())
+    write!(f, "{}", self.display_text())
+  }
+}
+
+impl Diagnostics {
+  /// Returns a Display that prints the notes and the shown warnings and errors grouped by severity,
+  /// with terminal colors.
+  fn display_text(&self) -> impl std::fmt::Display + '_ {
+    DisplayFn(move |f| {
+      if !self.notes.is_empty() {
+        writeln!(f, "\x1b[4m\x1b[1m\x1b[36mNotes:\x1b[0m")?;
+        for note in &self.notes {
+          writeln!(f, "{note}")?;
+        }
+        writeln!(f)?;
+      }
+      if self.has_severity(Severity::Warning) && !self.config.quiet {
+        write!(
+          f,
+          "\x1b[4m\x1b[1m\x1b[33mWarnings:\x1b[0m\n{}",
+          self.display_with_severity(Severity::Warning)
+        )?;
+      }
+      if self.has_severity(Severity::Error) {
+        write!(f, "\x1b[4m\x1b[1m\x1b[31mErrors:\x1b[0m\n{}", self.display_with_severity(Severity::Error))?;
+      }
+      Ok(())
+    })
   }
 }
 
@@ -219,7 +372,7 @@ impl From<String> for Diagnostics {
     Self {
       diagnostics: BTreeMap::from_iter([(
         DiagnosticOrigin::Book,
-        vec![Diagnostic { message: value, severity: Severity::Error }],
+        vec![Diagnostic::new(value, Severity::Error)],
       )]),
       ..Default::default()
     }
@@ -235,7 +388,55 @@ impl DiagnosticsConfig {
       unused_definition: severity,
       repeated_bind: severity,
       recursion_cycle: severity,
+      shadowed_builtin: severity,
+      // Opt-in, since some programs, like servers, are meant to never stop.
+      missing_base_case: Severity::Allow,
+      ctr_arity: severity,
+      // Opt-in, since shadowing a variable in a pattern is often intended.
+      shadowed_bind: Severity::Allow,
+      numeric_overflow: severity,
+      // Opt-in, since binding a value without using it is common while writing a program.
+      unused_bind: Severity::Allow,
+      // Opt-in, since the naming conventions vary between codebases.
+      naming_convention: Severity::Allow,
+      // Opt-in, since the lambdas are removed by the 'eta' optimization anyway.
+      redundant_lambda: Severity::Allow,
+      // Opt-in, since many recursive functions that pass a variable to several calls are fine.
+      potential_exponential: Severity::Allow,
+      // Opt-in, since the order of the arms only matters for the NumScott encoding.
+      match_arm_order: Severity::Allow,
+      report_pruned: false,
       verbose,
+      quiet: false,
+      format: DiagnosticsFormat::Text,
+      color: true,
+    }
+  }
+
+  /// Turns every warning that is reported as a warning into an error, leaving the allowed ones as they are.
+  pub fn deny_warnings(&mut self) {
+    let severities = [
+      &mut self.irrefutable_match,
+      &mut self.redundant_match,
+      &mut self.unreachable_match,
+      &mut self.unused_definition,
+      &mut self.repeated_bind,
+      &mut self.recursion_cycle,
+      &mut self.shadowed_builtin,
+      &mut self.missing_base_case,
+      &mut self.ctr_arity,
+      &mut self.shadowed_bind,
+      &mut self.numeric_overflow,
+      &mut self.unused_bind,
+      &mut self.naming_convention,
+      &mut self.redundant_lambda,
+      &mut self.potential_exponential,
+      &mut self.match_arm_order,
+    ];
+    for severity in severities {
+      if *severity == Severity::Warning {
+        *severity = Severity::Error;
+      }
     }
   }
 
@@ -244,6 +445,16 @@ impl DiagnosticsConfig {
       WarningType::UnusedDefinition => self.unused_definition,
       WarningType::RepeatedBind => self.repeated_bind,
       WarningType::RecursionCycle => self.recursion_cycle,
+      WarningType::ShadowedBuiltin => self.shadowed_builtin,
+      WarningType::MissingBaseCase => self.missing_base_case,
+      WarningType::CtrArity => self.ctr_arity,
+      WarningType::ShadowedBind => self.shadowed_bind,
+      WarningType::NumericOverflow => self.numeric_overflow,
+      WarningType::UnusedBind => self.unused_bind,
+      WarningType::NamingConvention => self.naming_convention,
+      WarningType::RedundantLambda => self.redundant_lambda,
+      WarningType::PotentialExponential => self.potential_exponential,
+      WarningType::MatchArmOrder => self.match_arm_order,
       WarningType::IrrefutableMatch => self.irrefutable_match,
       WarningType::RedundantMatch => self.redundant_match,
       WarningType::UnreachableMatch => self.unreachable_match,
@@ -259,8 +470,146 @@ impl Default for DiagnosticsConfig {
   }
 }
 
+impl Diagnostic {
+  fn new(msg: impl ToString, severity: Severity) -> Self {
+    Self { message: msg.to_string(), severity, warning_type: None, span: None }
+  }
+
+  fn with_warning_type(self, warning_type: WarningType) -> Self {
+    Self { warning_type: Some(warning_type), ..self }
+  }
+
+  fn with_span(self, span: TextSpan) -> Self {
+    Self { span: Some(span), ..self }
+  }
+
+  /// The warning rule that produced this diagnostic, or `None` for errors that can't be allowed.
+  pub fn warning_type(&self) -> Option<WarningType> {
+    self.warning_type
+  }
+}
+
 impl Display for Diagnostic {
   fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
     write!(f, "{}", self.message)
   }
 }
+
+impl TextSpan {
+  /// Creates the span between two byte offsets of the given source code.
+  pub fn from_byte_span(code: &str, ini: usize, end: usize) -> Self {
+    Self { ini: TextLocation::from_byte(code, ini), end: TextLocation::from_byte(code, end) }
+  }
+}
+
+impl TextLocation {
+  pub fn from_byte(code: &str, byte: usize) -> Self {
+    let byte = byte.min(code.len());
+    let before = code.get(..byte).unwrap_or_default();
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    let column = before[line_start..].chars().count() + 1;
+    Self { byte, line, column }
+  }
+
+  /// Finds the location of a byte offset of the code, only looking at the code after this
+  /// location when the offset comes after it.
+  pub fn advance_to(self, code: &str, byte: usize) -> Self {
+    let byte = byte.min(code.len());
+    let Some(between) = code.get(self.byte..byte) else {
+      return Self::from_byte(code, byte);
+    };
+    match between.rfind('\n') {
+      Some(i) => Self {
+        byte,
+        line: self.line + between.matches('\n').count(),
+        column: between[i + 1..].chars().count() + 1,
+      },
+      None => Self { byte, line: self.line, column: self.column + between.chars().count() },
+    }
+  }
+}
+
+/* Structured output */
+
+/// A shown warning or error, independent of how the diagnostics are displayed.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DiagnosticRecord {
+  pub severity: Severity,
+  /// The warning rule that produced the message, or `None` for errors that can't be allowed.
+  pub rule: Option<WarningType>,
+  /// What the message refers to, like a definition or the whole book.
+  pub origin: DiagnosticOrigin,
+  /// The message, without terminal colors or styles.
+  pub message: String,
+  /// The region of the source code the message refers to, when known.
+  /// For the messages about a definition, it's the region of the whole definition.
+  pub span: Option<TextSpan>,
+}
+
+impl Diagnostics {
+  /// Returns the warnings and errors that are shown, that is, the ones that are not allowed,
+  /// ordered by their origin. Warnings are not shown if the config is quiet.
+  pub fn records(&self) -> Vec<DiagnosticRecord> {
+    self
+      .diagnostics
+      .iter()
+      .flat_map(|(origin, diags)| diags.iter().map(move |diag| (origin, diag)))
+      .filter(|(_, diag)| match diag.severity {
+        Severity::Allow => false,
+        Severity::Warning => !self.config.quiet,
+        Severity::Error => true,
+      })
+      .map(|(origin, diag)| DiagnosticRecord {
+        severity: diag.severity,
+        rule: diag.warning_type,
+        origin: origin.clone(),
+        message: strip_ansi_escapes(&diag.message),
+        span: diag.span.or_else(|| self.origin_span(origin)),
+      })
+      .collect()
+  }
+
+  /// The region of the source code of the definition a message is about, when known.
+  fn origin_span(&self, origin: &DiagnosticOrigin) -> Option<TextSpan> {
+    match origin {
+      DiagnosticOrigin::Rule(nam) => self.def_spans.get(nam).copied(),
+      DiagnosticOrigin::Inet(nam) => {
+        self.def_spans.get(&Name::new(nam.as_str()).def_name_from_generated()).copied()
+      }
+      DiagnosticOrigin::Book | DiagnosticOrigin::Readback => None,
+    }
+  }
+
+  /// Returns a Display that prints the shown warnings and errors as a single-line JSON array.
+  /// Prints nothing if there are no messages to show.
+  pub fn display_json(&self) -> impl std::fmt::Display + '_ {
+    DisplayFn(move |f| {
+      let diags = self.records();
+      if diags.is_empty() {
+        return Ok(());
+      }
+      let json = serde_json::to_string(&diags).map_err(|_| std::fmt::Error)?;
+      writeln!(f, "{json}")
+    })
+  }
+}
+
+/// Removes the terminal color and style codes from a message.
+fn strip_ansi_escapes(msg: &str) -> String {
+  let mut out = String::with_capacity(msg.len());
+  let mut chars = msg.chars();
+  while let Some(c) = chars.next() {
+    if c == '\x1b' {
+      // Skip until the end of the `ESC [ ... m` sequence
+      for c in chars.by_ref() {
+        if c.is_ascii_alphabetic() {
+          break;
+        }
+      }
+    } else {
+      out.push(c);
+    }
+  }
+  out
+}
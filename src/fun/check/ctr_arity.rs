@@ -0,0 +1,68 @@
+use crate::{
+  diagnostics::WarningType,
+  fun::{Book, Ctx, Name, Term},
+  maybe_grow,
+};
+
+impl Ctx<'_> {
+  /// Warns about constructors applied to more arguments than they have fields.
+  ///
+  /// Applying a constructor to fewer arguments is allowed, since that's just currying.
+  /// Must be run after references are resolved, so that variables named like a constructor are not reported.
+  pub fn check_ctr_arity(&mut self) {
+    for (def_name, def) in self.book.defs.iter() {
+      if def.builtin {
+        continue;
+      }
+      let mut errs = vec![];
+      for rule in &def.rules {
+        rule.body.check_ctr_arity(self.book, &mut errs);
+      }
+      for err in errs {
+        self.info.add_rule_warning(err, WarningType::CtrArity, def_name.clone());
+      }
+    }
+  }
+}
+
+impl Term {
+  fn check_ctr_arity(&self, book: &Book, errs: &mut Vec<String>) {
+    maybe_grow(|| {
+      let mut head = self;
+      let mut args = vec![];
+      while let Term::App { fun, arg, .. } = head {
+        args.push(arg.as_ref());
+        head = fun;
+      }
+
+      if let Term::Ref { nam } = head {
+        if let Some(arity) = ctr_arity(book, nam) {
+          if args.len() > arity {
+            errs.push(format!(
+              "Constructor '{nam}' expects {arity} {}, but was given {}: {self}",
+              if arity == 1 { "argument" } else { "arguments" },
+              args.len(),
+            ));
+          }
+        }
+      }
+
+      if args.is_empty() {
+        for child in self.children() {
+          child.check_ctr_arity(book, errs);
+        }
+      } else {
+        head.check_ctr_arity(book, errs);
+        for arg in args.into_iter().rev() {
+          arg.check_ctr_arity(book, errs);
+        }
+      }
+    })
+  }
+}
+
+/// The number of fields of the constructor `nam`, if it is one.
+fn ctr_arity(book: &Book, nam: &Name) -> Option<usize> {
+  let adt = book.ctrs.get(nam)?;
+  Some(book.adts[adt].ctrs[nam].len())
+}
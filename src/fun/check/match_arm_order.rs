@@ -0,0 +1,67 @@
+use crate::{
+  diagnostics::{WarningType, ERR_INDENT_SIZE},
+  fun::{Adts, Constructors, Ctx, MatchRule, Name, Term},
+  maybe_grow,
+};
+use itertools::Itertools;
+
+impl Ctx<'_> {
+  /// Warns about matches whose constructor arms are not in the order in which the
+  /// constructors of the matched type are declared.
+  ///
+  /// Only the arms before the first variable arm are checked, since the variable arm covers the rest.
+  /// Must be run before the match terms are fixed, since that puts the arms in the declared order.
+  pub fn check_match_arm_order(&mut self) {
+    for (def_name, def) in self.book.defs.iter() {
+      if def.builtin {
+        continue;
+      }
+      let mut errs = vec![];
+      for rule in &def.rules {
+        rule.body.check_match_arm_order(&self.book.ctrs, &self.book.adts, &mut errs);
+      }
+      for err in errs {
+        self.info.add_rule_warning(err, WarningType::MatchArmOrder, def_name.clone());
+      }
+    }
+  }
+}
+
+impl Term {
+  fn check_match_arm_order(&self, ctrs: &Constructors, adts: &Adts, errs: &mut Vec<String>) {
+    maybe_grow(|| {
+      if let Term::Mat { bnd, arms, .. } | Term::Fold { bnd, arms, .. } = self {
+        if let Some(err) = arm_order_err(bnd, arms, ctrs, adts) {
+          errs.push(err);
+        }
+      }
+      for child in self.children() {
+        child.check_match_arm_order(ctrs, adts, errs);
+      }
+    })
+  }
+}
+
+fn arm_order_err(bnd: &Option<Name>, arms: &[MatchRule], ctrs: &Constructors, adts: &Adts) -> Option<String> {
+  let adt_nam = ctrs.get(arms.first()?.0.as_ref()?)?;
+  let adt_ctrs = &adts[adt_nam].ctrs;
+  let found = arms
+    .iter()
+    .map_while(|(ctr, _, _)| ctr.as_ref().filter(|ctr| adt_ctrs.contains_key(*ctr)))
+    .collect::<Vec<_>>();
+  let expected = found.iter().copied().sorted_by_key(|ctr| adt_ctrs.get_index_of(*ctr)).collect::<Vec<_>>();
+  if found == expected {
+    return None;
+  }
+  let matched = match bnd {
+    Some(nam) if !nam.contains('%') => format!(" on '{nam}'"),
+    _ => String::new(),
+  };
+  Some(format!(
+    "The arms of the 'match'{matched} are not in the order of the constructors of '{adt_nam}'.\n{:ERR_INDENT_SIZE$}Expected: {}\n{:ERR_INDENT_SIZE$}Found:    {}",
+    "",
+    expected.iter().join(", "),
+    "",
+    found.iter().join(", "),
+  ))
+}
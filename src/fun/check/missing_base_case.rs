@@ -0,0 +1,113 @@
+use crate::{
+  diagnostics::WarningType,
+  fun::{Ctx, Name, Term},
+  maybe_grow,
+};
+use itertools::Itertools;
+
+impl Ctx<'_> {
+  /// Warns about recursive definitions where every branch calls the definition again
+  /// without getting structurally smaller, which means that they never reach a base case.
+  ///
+  /// This is a conservative heuristic, so it misses many non-terminating definitions,
+  /// like the ones that recurse on an argument that is bigger than the input.
+  pub fn check_missing_base_case(&mut self) {
+    for (def_name, def) in self.book.defs.iter() {
+      if def.builtin || !def.rules.iter().all(|rule| rule.body.lam_body().always_calls(def_name, false)) {
+        continue;
+      }
+
+      let mut calls = vec![];
+      for rule in &def.rules {
+        rule.body.find_calls(def_name, &mut calls);
+      }
+      let calls = calls.iter().map(|call| format!("  {call}")).join("\n");
+      self.info.add_rule_warning(
+        format!("Definition calls itself in every branch, without decreasing its arguments, so it never stops. Recursive calls:\n{calls}"),
+        WarningType::MissingBaseCase,
+        def_name.clone(),
+      );
+    }
+  }
+}
+
+impl Term {
+  /// Whether evaluating this term always reaches a match where every arm calls `def_name`
+  /// without decreasing its arguments.
+  ///
+  /// A call inside a match arm is decreasing if it uses one of the fields bound by that arm.
+  /// Only calls in strict positions are considered, since lambda bodies and the arguments
+  /// of an application may never be evaluated.
+  fn always_calls(&self, def_name: &Name, in_match: bool) -> bool {
+    maybe_grow(|| match self {
+      Term::App { .. } => {
+        let head = self.call_head();
+        match head {
+          Term::Ref { nam } => in_match && nam == def_name,
+          _ => head.always_calls(def_name, in_match),
+        }
+      }
+      Term::Ref { .. } | Term::Lam { .. } => false,
+      Term::Mat { arg, arms, .. } => {
+        arg.always_calls(def_name, in_match)
+          || arms.iter().all(|(_, fields, bod)| bod.always_calls_with(def_name, fields.iter().flatten()))
+      }
+      Term::Swt { arg, pred, arms, .. } => {
+        let (succ, nums) = arms.split_last().unwrap();
+        arg.always_calls(def_name, in_match)
+          || (nums.iter().all(|arm| arm.always_calls(def_name, true))
+            && succ.always_calls_with(def_name, pred.iter()))
+      }
+      _ => self.children().any(|child| child.always_calls(def_name, in_match)),
+    })
+  }
+
+  /// Whether this match arm always calls `def_name` without using any of the variables in `fields`.
+  fn always_calls_with<'a>(&self, def_name: &Name, mut fields: impl Iterator<Item = &'a Name>) -> bool {
+    if !self.always_calls(def_name, true) {
+      return false;
+    }
+    let mut calls = vec![];
+    self.find_calls(def_name, &mut calls);
+    let used = calls.iter().flat_map(|call| call.free_vars().into_keys()).collect::<Vec<_>>();
+    !fields.any(|field| used.contains(field))
+  }
+
+  /// The function being applied in a chain of applications.
+  pub(super) fn call_head(&self) -> &Term {
+    let mut head = self;
+    while let Term::App { fun, .. } = head {
+      head = fun;
+    }
+    head
+  }
+
+  /// The body of the lambdas at the start of this term, which bind the arguments of a definition.
+  fn lam_body(&self) -> &Term {
+    let mut term = self;
+    while let Term::Lam { bod, .. } = term {
+      term = bod;
+    }
+    term
+  }
+
+  /// Collects the calls to `def_name` in this term.
+  fn find_calls<'a>(&'a self, def_name: &Name, calls: &mut Vec<&'a Term>) {
+    maybe_grow(|| {
+      if !matches!(self.call_head(), Term::Ref { nam } if nam == def_name) {
+        for child in self.children() {
+          child.find_calls(def_name, calls);
+        }
+        return;
+      }
+      if matches!(self, Term::App { .. }) {
+        calls.push(self);
+      }
+      let mut term = self;
+      while let Term::App { fun, arg, .. } = term {
+        arg.find_calls(def_name, calls);
+        term = fun;
+      }
+    })
+  }
+}
@@ -1,4 +1,14 @@
+pub mod ctr_arity;
+pub mod match_arm_order;
+pub mod missing_base_case;
+pub mod naming_convention;
+pub mod numeric_overflow;
+pub mod potential_exponential;
+pub mod redundant_lambdas;
 pub mod set_entrypoint;
+pub mod shadowed_binds;
+pub mod shadowed_builtins;
 pub mod shared_names;
 pub mod unbound_refs;
 pub mod unbound_vars;
+pub mod unused_binds;
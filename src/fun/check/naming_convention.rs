@@ -0,0 +1,72 @@
+use crate::{
+  diagnostics::WarningType,
+  fun::{Ctx, Name},
+};
+
+/// The ways of writing a name made of several words.
+#[derive(Clone, Copy)]
+enum Case {
+  /// Every word starts with an uppercase letter, like `FooBar`.
+  Pascal,
+  /// Every word but the first starts with an uppercase letter, like `fooBar`.
+  Camel,
+}
+
+impl Ctx<'_> {
+  /// Warns about the types, constructors and functions written by the user whose names don't follow
+  /// the naming convention: PascalCase for types and constructors and camelCase for functions.
+  ///
+  /// Only the part after the last `/` is checked, since the ones before it are the type or the
+  /// module the name belongs to.
+  pub fn check_naming_convention(&mut self) {
+    for (adt_name, adt) in &self.book.adts {
+      if adt.builtin {
+        continue;
+      }
+      if let Some(expected) = expected_name(adt_name, Case::Pascal) {
+        let warn = format!("Type '{adt_name}' should be written in PascalCase, like '{expected}'.");
+        self.info.add_book_warning(warn, WarningType::NamingConvention);
+      }
+      for ctr_name in adt.ctrs.keys() {
+        if let Some(expected) = expected_name(ctr_name, Case::Pascal) {
+          let warn = format!("Constructor '{ctr_name}' should be written in PascalCase, like '{expected}'.");
+          self.info.add_book_warning(warn, WarningType::NamingConvention);
+        }
+      }
+    }
+
+    for (def_name, def) in &self.book.defs {
+      // Constructors are checked with their types, and generated definitions take the name of their parent.
+      if def.builtin
+        || self.book.ctrs.contains_key(def_name)
+        || def_name.def_name_from_generated() != *def_name
+      {
+        continue;
+      }
+      if let Some(expected) = expected_name(def_name, Case::Camel) {
+        let warn = format!("Function '{def_name}' should be written in camelCase, like '{expected}'.");
+        self.info.add_rule_warning(warn, WarningType::NamingConvention, def_name.clone());
+      }
+    }
+  }
+}
+
+/// The name written in the given case, or None if it already is.
+fn expected_name(name: &Name, case: Case) -> Option<String> {
+  let (prefix, last) = match name.rsplit_once('/') {
+    Some((prefix, last)) => (format!("{prefix}/"), last),
+    None => (String::new(), name.as_ref()),
+  };
+  let words = last.split(['_', '-', '.']).filter(|word| !word.is_empty());
+  let mut expected = String::new();
+  for (i, word) in words.enumerate() {
+    let mut chars = word.chars();
+    let first = chars.next().unwrap();
+    match case {
+      Case::Camel if i == 0 => expected.extend(first.to_lowercase()),
+      _ => expected.extend(first.to_uppercase()),
+    }
+    expected.push_str(chars.as_str());
+  }
+  (!expected.is_empty() && expected != last).then(|| format!("{prefix}{expected}"))
+}
@@ -0,0 +1,75 @@
+use crate::{
+  diagnostics::WarningType,
+  fun::{Ctx, Num, Pattern, Term},
+  maybe_grow,
+};
+
+/// The largest value of a `u24` number.
+const U24_MAX: u32 = 0xFFFFFF;
+
+/// The largest integer up to which every integer can be represented exactly by an `f24` number.
+const F24_MAX_EXACT_INT: u32 = 1 << 16;
+
+impl Ctx<'_> {
+  /// Warns about number literals that don't fit in the numeric type they'll have at runtime.
+  ///
+  /// Literals in terms are already checked when parsing, but the numbers in the patterns of
+  /// the rules are only checked here, since they become `u24`s once the rules are desugared.
+  /// Float literals are checked for whole numbers too large to be represented exactly by an `f24`.
+  pub fn check_numeric_overflow(&mut self) {
+    for (def_name, def) in self.book.defs.iter() {
+      if def.builtin {
+        continue;
+      }
+      let mut errs = vec![];
+      for rule in &def.rules {
+        for pat in &rule.pats {
+          pat.check_numeric_overflow(&mut errs);
+        }
+        rule.body.check_numeric_overflow(&mut errs);
+      }
+      for err in errs {
+        self.info.add_rule_warning(err, WarningType::NumericOverflow, def_name.clone());
+      }
+    }
+  }
+}
+
+impl Term {
+  fn check_numeric_overflow(&self, errs: &mut Vec<String>) {
+    maybe_grow(|| {
+      if let Term::Num { val: Num::F24(val) } = self {
+        let Num::F24(rounded) = Num::from_bits(Num::F24(*val).to_bits()) else { unreachable!() };
+        if val.fract() == 0.0 && rounded != *val {
+          errs.push(format!(
+            "Number literal '{}' can't be represented exactly as an F24 and becomes '{}'. \
+             Whole numbers can only be represented exactly up to {F24_MAX_EXACT_INT}.",
+            Term::Num { val: Num::F24(*val) },
+            Term::Num { val: Num::F24(rounded) },
+          ));
+        }
+      }
+      if let Some(pat) = self.pattern() {
+        pat.check_numeric_overflow(errs);
+      }
+      for child in self.children() {
+        child.check_numeric_overflow(errs);
+      }
+    })
+  }
+}
+
+impl Pattern {
+  fn check_numeric_overflow(&self, errs: &mut Vec<String>) {
+    for pat in self.iter() {
+      if let Pattern::Num(num) = pat {
+        if *num > U24_MAX {
+          errs.push(format!(
+            "Number pattern '{num}' is outside of the range of U24 and wraps around. \
+             The maximum value is {U24_MAX}."
+          ));
+        }
+      }
+    }
+  }
+}
@@ -0,0 +1,130 @@
+use crate::{
+  diagnostics::WarningType,
+  fun::{Ctx, Name, Term},
+  maybe_grow,
+};
+use itertools::Itertools;
+use std::collections::BTreeMap;
+
+impl Ctx<'_> {
+  /// Warns about recursive definitions where a variable is used to compute the arguments
+  /// of several recursive calls in the same branch, like `n` in `(+ (fib (- n 1)) (fib (- n 2)))`.
+  ///
+  /// Each of those calls can make as many calls again, so the work can grow exponentially
+  /// with the depth of the recursion.
+  ///
+  /// This is a conservative heuristic. Variables that are passed unchanged to every call,
+  /// like the function given to a `map`, are not reported, and neither are calls to other
+  /// definitions that end up calling this one.
+  pub fn check_potential_exponential(&mut self) {
+    for (def_name, def) in self.book.defs.iter() {
+      if def.builtin {
+        continue;
+      }
+
+      let mut branches = vec![];
+      for rule in &def.rules {
+        let mut calls = vec![];
+        rule.body.branch_calls(def_name, &mut calls, &mut branches);
+        branches.push(calls);
+      }
+
+      let mut reported = vec![];
+      for calls in branches {
+        for (var, calls) in shared_call_vars(&calls) {
+          if reported.contains(&var) {
+            continue;
+          }
+          let listed = calls.iter().map(|call| format!("  {call}")).join("\n");
+          self.info.add_rule_warning(
+            format!(
+              "Variable '{var}' is used to compute the arguments of {} recursive calls in the same branch, so the work can grow exponentially with the depth of the recursion. Recursive calls:\n{listed}",
+              calls.len()
+            ),
+            WarningType::PotentialExponential,
+            def_name.clone(),
+          );
+          reported.push(var);
+        }
+      }
+    }
+  }
+}
+
+/// The variables used by the arguments of more than one of these calls, with the calls that use them.
+///
+/// A variable is only returned if at least one of the calls computes something with it,
+/// instead of just passing it along.
+fn shared_call_vars<'a>(calls: &[&'a Term]) -> BTreeMap<Name, Vec<&'a Term>> {
+  let mut uses = BTreeMap::<Name, (Vec<&Term>, bool)>::new();
+  for call in calls {
+    let mut vars = BTreeMap::new();
+    let mut term = *call;
+    while let Term::App { fun, arg, .. } = term {
+      for var in arg.free_vars().into_keys() {
+        let computed = !matches!(arg.as_ref(), Term::Var { nam } if nam == &var);
+        *vars.entry(var).or_insert(false) |= computed;
+      }
+      term = fun;
+    }
+    for (var, computed) in vars {
+      let (var_calls, var_computed) = uses.entry(var).or_default();
+      var_calls.push(*call);
+      *var_computed |= computed;
+    }
+  }
+  uses
+    .into_iter()
+    .filter(|(_, (calls, computed))| calls.len() > 1 && *computed)
+    .map(|(var, (calls, _))| (var, calls))
+    .collect()
+}
+
+impl Term {
+  /// Collects the calls to `def_name` in this term, grouped by the match arm that they're in.
+  ///
+  /// The calls outside of any match arm are pushed to `calls`,
+  /// while the ones of each arm are pushed as a new group to `branches`.
+  fn branch_calls<'a>(
+    &'a self,
+    def_name: &Name,
+    calls: &mut Vec<&'a Term>,
+    branches: &mut Vec<Vec<&'a Term>>,
+  ) {
+    maybe_grow(|| match self {
+      Term::App { .. } if matches!(self.call_head(), Term::Ref { nam } if nam == def_name) => {
+        calls.push(self);
+        let mut term = self;
+        while let Term::App { fun, arg, .. } = term {
+          arg.branch_calls(def_name, calls, branches);
+          term = fun;
+        }
+      }
+      Term::Mat { arg, with_arg, arms, .. } => {
+        for arg in std::iter::once(arg.as_ref()).chain(with_arg) {
+          arg.branch_calls(def_name, calls, branches);
+        }
+        for (_, _, bod) in arms {
+          let mut arm_calls = vec![];
+          bod.branch_calls(def_name, &mut arm_calls, branches);
+          branches.push(arm_calls);
+        }
+      }
+      Term::Swt { arg, with_arg, arms, .. } => {
+        for arg in std::iter::once(arg.as_ref()).chain(with_arg) {
+          arg.branch_calls(def_name, calls, branches);
+        }
+        for bod in arms {
+          let mut arm_calls = vec![];
+          bod.branch_calls(def_name, &mut arm_calls, branches);
+          branches.push(arm_calls);
+        }
+      }
+      _ => {
+        for child in self.children() {
+          child.branch_calls(def_name, calls, branches);
+        }
+      }
+    })
+  }
+}
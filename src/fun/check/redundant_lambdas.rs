@@ -0,0 +1,50 @@
+use crate::{
+  diagnostics::WarningType,
+  fun::{Ctx, Pattern, Tag, Term},
+  maybe_grow,
+};
+
+impl Ctx<'_> {
+  /// Warns about lambdas that only apply a function to their variable, like `λx (f x)`,
+  /// since they can be replaced with the function itself.
+  ///
+  /// The function can't use the variable, and lambdas and applications with tags are not reported.
+  /// Must be run before the rules and matches are desugared, so that the lambdas are the ones written by the user.
+  pub fn check_redundant_lambdas(&mut self) {
+    for (def_name, def) in self.book.defs.iter() {
+      if def.builtin {
+        continue;
+      }
+      let mut errs = vec![];
+      for rule in &def.rules {
+        rule.body.check_redundant_lambdas(&mut errs);
+      }
+      for err in errs {
+        self.info.add_rule_warning(err, WarningType::RedundantLambda, def_name.clone());
+      }
+    }
+  }
+}
+
+impl Term {
+  fn check_redundant_lambdas(&self, errs: &mut Vec<String>) {
+    maybe_grow(|| {
+      if let Term::Lam { tag: Tag::Static, pat, bod } = self {
+        if let (Pattern::Var(Some(var)), Term::App { tag: Tag::Static, fun, arg }) =
+          (pat.as_ref(), bod.as_ref())
+        {
+          let is_redundant = matches!(arg.as_ref(), Term::Var { nam } if nam == var)
+            // Variables with '%' are generated by desugaring, not written by the user.
+            && !var.contains('%')
+            && !fun.free_vars().contains_key(var);
+          if is_redundant {
+            errs.push(format!("The lambda '{self}' can be replaced with '{fun}'."));
+          }
+        }
+      }
+      for child in self.children() {
+        child.check_redundant_lambdas(errs);
+      }
+    })
+  }
+}
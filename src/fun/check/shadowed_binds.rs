@@ -0,0 +1,100 @@
+use crate::{
+  diagnostics::WarningType,
+  fun::{Book, Ctx, Name, Pattern, Term},
+  maybe_grow,
+};
+use std::collections::HashMap;
+
+impl Ctx<'_> {
+  /// Warns about variables bound by a pattern that shadow a variable bound by a lambda or a `let`.
+  ///
+  /// The patterns are the ones of `let`s and lambdas that destructure a value,
+  /// and the fields and predecessors bound by the arms of `match`, `fold` and `switch`.
+  /// Must be run before the rules and matches are desugared, so that the variables are the ones written by the user.
+  pub fn check_shadowed_binds(&mut self) {
+    for (def_name, def) in self.book.defs.iter() {
+      if def.builtin {
+        continue;
+      }
+      let mut errs = vec![];
+      for rule in &def.rules {
+        // For each variable in scope, how each of its binds shadowing each other can be described,
+        // or None if it was bound by a pattern.
+        let mut scope = HashMap::new();
+        for pat in &rule.pats {
+          let binder = |bind| matches!(pat, Pattern::Var(_)).then(|| format!("the parameter '{bind}'"));
+          for bind in pat.binds().flatten() {
+            scope.entry(bind).or_insert_with(Vec::new).push(binder(bind));
+          }
+        }
+        rule.body.check_shadowed_binds(self.book, &mut scope, &mut errs);
+      }
+      for err in errs {
+        self.info.add_rule_warning(err, WarningType::ShadowedBind, def_name.clone());
+      }
+    }
+  }
+}
+
+impl Term {
+  fn check_shadowed_binds<'a>(
+    &'a self,
+    book: &Book,
+    scope: &mut HashMap<&'a Name, Vec<Option<String>>>,
+    errs: &mut Vec<String>,
+  ) {
+    maybe_grow(|| {
+      for (bind, pattern) in self.pattern_binds(book) {
+        if let Some(Some(shadowed)) = scope.get(&bind).and_then(|binders| binders.last()) {
+          errs
+            .push(format!("Variable '{bind}' bound by {pattern} shadows the variable bound by {shadowed}."));
+        }
+      }
+
+      for (child, binds) in self.children_with_binds() {
+        let binds = binds.flatten().collect::<Vec<_>>();
+        for &bind in &binds {
+          let binder = match self {
+            Term::Lam { pat, .. } if matches!(pat.as_ref(), Pattern::Var(_)) => {
+              Some(format!("the lambda 'λ{bind}'"))
+            }
+            Term::Let { pat, .. } if matches!(pat.as_ref(), Pattern::Var(_)) => Some(format!("'let {bind}'")),
+            Term::Ask { pat, .. } if matches!(pat.as_ref(), Pattern::Var(_)) => Some(format!("'ask {bind}'")),
+            Term::Use { .. } => Some(format!("'use {bind}'")),
+            _ => None,
+          };
+          scope.entry(bind).or_default().push(binder);
+        }
+        child.check_shadowed_binds(book, scope, errs);
+        for bind in binds {
+          scope.get_mut(bind).unwrap().pop();
+        }
+      }
+    })
+  }
+
+  /// The variables bound by the patterns of this term, with a description of the pattern.
+  fn pattern_binds(&self, book: &Book) -> Vec<(Name, String)> {
+    match self {
+      Term::Lam { pat, .. } | Term::Let { pat, .. } | Term::Ask { pat, .. } => match pat.as_ref() {
+        Pattern::Var(_) => vec![],
+        // Patterns with generated variables come from desugaring, like map accesses rebinding the map.
+        pat if pat.binds().flatten().any(|bind| bind.contains('%')) => vec![],
+        pat => pat.binds().flatten().map(|bind| (bind.clone(), format!("the pattern '{pat}'"))).collect(),
+      },
+      // The fields of the arms are only filled in when the matches are fixed, so they're taken from the constructors.
+      Term::Mat { bnd: Some(bnd), arms, .. } | Term::Fold { bnd: Some(bnd), arms, .. } => {
+        let mut binds = vec![];
+        for ctr in arms.iter().filter_map(|(ctr, _, _)| ctr.as_ref()) {
+          let Some(adt) = book.ctrs.get(ctr) else { continue };
+          for field in &book.adts[adt].ctrs[ctr] {
+            binds.push((Name::new(format!("{bnd}.{}", field.nam)), format!("the '{ctr}' arm of a match")));
+          }
+        }
+        binds
+      }
+      Term::Swt { pred: Some(pred), .. } => vec![(pred.clone(), "the '_' arm of a switch".to_string())],
+      _ => vec![],
+    }
+  }
+}
@@ -0,0 +1,29 @@
+use crate::{diagnostics::WarningType, fun::Ctx};
+
+impl Ctx<'_> {
+  /// Warns about user definitions that take the name of a built-in.
+  ///
+  /// Redefining a built-in function, type or constructor is already an error,
+  /// so this only looks for a function named like a built-in type or a type
+  /// named like a built-in function.
+  pub fn check_shadowed_builtins(&mut self) {
+    for (def_name, def) in self.book.defs.iter() {
+      if !def.builtin && self.book.adts.get(def_name).is_some_and(|adt| adt.builtin) {
+        self.info.add_rule_warning(
+          format!("Definition shadows the built-in type '{def_name}'."),
+          WarningType::ShadowedBuiltin,
+          def_name.clone(),
+        );
+      }
+    }
+
+    for (adt_name, adt) in self.book.adts.iter() {
+      if !adt.builtin && self.book.defs.get(adt_name).is_some_and(|def| def.builtin) {
+        self.info.add_book_warning(
+          format!("Type '{adt_name}' shadows the built-in function '{adt_name}'."),
+          WarningType::ShadowedBuiltin,
+        );
+      }
+    }
+  }
+}
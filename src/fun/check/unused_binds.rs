@@ -0,0 +1,108 @@
+use crate::{
+  diagnostics::WarningType,
+  fun::{Ctx, Name, Pattern, Term},
+  maybe_grow,
+};
+use std::collections::HashMap;
+
+/// A variable in scope: whether it was used and, if it has to be reported when it's not,
+/// a description of where it was bound and how to fix it.
+type ScopeEntry = (bool, Option<(String, &'static str)>);
+
+const REPLACE_HINT: &str = "Replace it with '*' if it's not needed.";
+const REMOVE_USE_HINT: &str = "Remove the 'use' if it's not needed.";
+
+impl Ctx<'_> {
+  /// Warns about variables bound by the patterns of a rule or by a `let`, `ask` or `use`
+  /// that are never used in their scope, since they can be replaced with `*`.
+  ///
+  /// Parameters of the rules that aren't destructured, variables bound by lambdas and
+  /// variables whose names start with '_' are not reported.
+  /// Must be run before the rules and matches are desugared, so that the variables are the ones written by the user.
+  pub fn check_unused_binds(&mut self) {
+    for (def_name, def) in self.book.defs.iter() {
+      if def.builtin {
+        continue;
+      }
+      let mut errs = vec![];
+      for rule in &def.rules {
+        let mut scope = HashMap::new();
+        for pat in &rule.pats {
+          let binder = match pat {
+            Pattern::Var(_) => None,
+            pat => Some((format!("the pattern '{pat}'"), REPLACE_HINT)),
+          };
+          for bind in pat.binds().flatten() {
+            push_bind(&mut scope, bind, binder.clone());
+          }
+        }
+        rule.body.check_unused_binds(&mut scope, &mut errs);
+        for pat in &rule.pats {
+          for bind in pat.binds().flatten() {
+            pop_bind(&mut scope, bind, &mut errs);
+          }
+        }
+      }
+      for err in errs {
+        self.info.add_rule_warning(err, WarningType::UnusedBind, def_name.clone());
+      }
+    }
+  }
+}
+
+impl Term {
+  fn check_unused_binds<'a>(&'a self, scope: &mut HashMap<&'a str, Vec<ScopeEntry>>, errs: &mut Vec<String>) {
+    maybe_grow(|| {
+      if let Term::Var { nam } = self {
+        if let Some((used, _)) = scope.get_mut(nam.as_ref()).and_then(|entries| entries.last_mut()) {
+          *used = true;
+        }
+      }
+
+      for (child, binds) in self.children_with_binds() {
+        let binds = binds.flatten().collect::<Vec<_>>();
+        for &bind in &binds {
+          let binder = match self {
+            Term::Let { pat, .. } | Term::Ask { pat, .. }
+              if pat.binds().flatten().any(|b| b.contains('%')) =>
+            {
+              // Patterns with generated variables come from desugaring, like map accesses rebinding the map.
+              None
+            }
+            Term::Let { pat, .. } => match pat.as_ref() {
+              Pattern::Var(_) => Some((format!("'let {bind}'"), REPLACE_HINT)),
+              pat => Some((format!("the pattern '{pat}'"), REPLACE_HINT)),
+            },
+            Term::Ask { pat, .. } => match pat.as_ref() {
+              Pattern::Var(_) => Some((format!("'ask {bind}'"), REPLACE_HINT)),
+              pat => Some((format!("the pattern '{pat}'"), REPLACE_HINT)),
+            },
+            Term::Use { .. } => Some((format!("'use {bind}'"), REMOVE_USE_HINT)),
+            _ => None,
+          };
+          push_bind(scope, bind, binder);
+        }
+        child.check_unused_binds(scope, errs);
+        for bind in binds {
+          pop_bind(scope, bind, errs);
+        }
+      }
+    })
+  }
+}
+
+fn push_bind<'a>(
+  scope: &mut HashMap<&'a str, Vec<ScopeEntry>>,
+  bind: &'a Name,
+  binder: Option<(String, &'static str)>,
+) {
+  scope.entry(bind.as_ref()).or_default().push((false, binder));
+}
+
+fn pop_bind(scope: &mut HashMap<&str, Vec<ScopeEntry>>, bind: &Name, errs: &mut Vec<String>) {
+  let entry = scope.get_mut(bind.as_ref()).and_then(|entries| entries.pop());
+  // Like in Rust, names starting with '_' mark variables that are unused on purpose.
+  if let (Some((false, Some((binder, hint)))), false) = (entry, bind.starts_with('_')) {
+    errs.push(format!("Variable '{bind}' bound by {binder} is never used. {hint}"));
+  }
+}
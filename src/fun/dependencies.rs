@@ -0,0 +1,110 @@
+use crate::{
+  fun::{Book, Name},
+  maybe_grow, ENTRY_POINT, HVM1_ENTRY_POINT,
+};
+use std::collections::{HashMap, HashSet};
+
+/// The definitions and constructors that each user definition references directly.
+///
+/// Like [`Ctx::prune_unreachable`](crate::fun::Ctx::prune_unreachable), this works on books whose
+/// references were not resolved yet, so any variable named like a definition counts as a reference to it.
+pub fn def_dependencies(book: &Book) -> HashMap<Name, HashSet<Name>> {
+  let mut deps = HashMap::new();
+  for (name, def) in book.defs.iter().filter(|(_, def)| !def.builtin) {
+    let mut refs = vec![];
+    for rule in &def.rules {
+      rule.body.find_possible_refs(&mut refs);
+    }
+    let refs = refs.into_iter().filter(|nam| book.defs.contains_key(nam) || book.ctrs.contains_key(nam));
+    deps.insert(name.clone(), refs.collect());
+  }
+  deps
+}
+
+/// Orders the definitions of `deps` so that each one comes after the definitions it depends on.
+///
+/// Definitions that depend on each other can't be ordered, so each group is one of the strongly
+/// connected components of the dependency graph. A group is a cycle if it has more than one definition
+/// or if its definition references itself.
+/// `names` gives the order of the groups that don't depend on each other. Names not in `deps` are ignored.
+pub fn dependency_order<'a>(
+  names: impl IntoIterator<Item = &'a Name>,
+  deps: &HashMap<Name, HashSet<Name>>,
+) -> Vec<Vec<Name>> {
+  // Tarjan's strongly connected components algorithm,
+  // which finds the components in reverse topological order.
+  #[derive(Default)]
+  struct State<'a> {
+    index: HashMap<&'a Name, usize>,
+    low_link: HashMap<&'a Name, usize>,
+    stack: Vec<&'a Name>,
+    on_stack: HashSet<&'a Name>,
+    groups: Vec<Vec<Name>>,
+  }
+
+  fn visit<'a>(name: &'a Name, deps: &'a HashMap<Name, HashSet<Name>>, state: &mut State<'a>) {
+    maybe_grow(|| {
+      let idx = state.index.len();
+      state.index.insert(name, idx);
+      state.low_link.insert(name, idx);
+      state.stack.push(name);
+      state.on_stack.insert(name);
+
+      let mut dep_names = deps[name].iter().filter(|dep| deps.contains_key(*dep)).collect::<Vec<_>>();
+      dep_names.sort();
+      for dep in dep_names {
+        if !state.index.contains_key(dep) {
+          visit(dep, deps, state);
+          let low = state.low_link[name].min(state.low_link[dep]);
+          state.low_link.insert(name, low);
+        } else if state.on_stack.contains(dep) {
+          let low = state.low_link[name].min(state.index[dep]);
+          state.low_link.insert(name, low);
+        }
+      }
+
+      if state.low_link[name] == state.index[name] {
+        let mut group = vec![];
+        while let Some(member) = state.stack.pop() {
+          state.on_stack.remove(member);
+          group.push(member.clone());
+          if member == name {
+            break;
+          }
+        }
+        group.reverse();
+        state.groups.push(group);
+      }
+    })
+  }
+
+  let mut state = State::default();
+  for name in names {
+    if let Some((name, _)) = deps.get_key_value(name) {
+      if !state.index.contains_key(name) {
+        visit(name, deps, &mut state);
+      }
+    }
+  }
+  state.groups
+}
+
+/// The definitions of `deps` that can be reached from the entrypoint of the book, in the order of the book.
+///
+/// The entrypoint is the one set in the book or, if there's none, `main` or `Main`.
+pub fn reachable_defs(book: &Book, deps: &HashMap<Name, HashSet<Name>>) -> Vec<Name> {
+  let entrypoint = book.entrypoint.clone().or_else(|| {
+    [ENTRY_POINT, HVM1_ENTRY_POINT].into_iter().map(Name::new).find(|name| book.defs.contains_key(name))
+  });
+
+  let mut reached = HashSet::new();
+  let mut to_visit = entrypoint.iter().collect::<Vec<_>>();
+  while let Some(name) = to_visit.pop() {
+    if let Some(refs) = deps.get(name) {
+      if reached.insert(name.as_ref()) {
+        to_visit.extend(refs);
+      }
+    }
+  }
+  book.defs.keys().filter(|name| reached.contains(name.as_ref())).cloned().collect()
+}
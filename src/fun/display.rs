@@ -1,4 +1,4 @@
-use super::{Book, Definition, FanKind, Name, Num, Op, Pattern, Rule, Tag, Term};
+use super::{Adt, Book, Definition, FanKind, Name, Num, Op, Pattern, Rule, SourceItem, Tag, Term};
 use crate::maybe_grow;
 use std::{fmt, ops::Deref};
 
@@ -38,121 +38,210 @@ macro_rules! display {
   };
 }
 
+/// How the float numbers of a term are displayed.
+///
+/// Every format but [`FloatFormat::Rounded`] displays the exact value of the number,
+/// in a way that parses back to the same `f24`.
+/// Infinities and NaNs are displayed as `+inf`, `-inf` and `+NaN`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FloatFormat {
+  /// Rounded to 3 decimal places, like `0.100`.
+  #[default]
+  Rounded,
+  /// The shortest decimal number with the same value, like `0.1`.
+  Decimal,
+  /// The shortest decimal number with the same value in scientific notation, like `1.0e-1`.
+  Scientific,
+  /// A hexadecimal number with a binary exponent, like `0x1.99999ap-4`.
+  Hex,
+}
+
+fn display_f24(f: &mut fmt::Formatter, val: f32, format: FloatFormat) -> fmt::Result {
+  // Written with a sign, like the parser expects them.
+  if val.is_nan() {
+    return write!(f, "+NaN");
+  }
+  if val.is_infinite() {
+    return write!(f, "{}inf", if val < 0.0 { "-" } else { "+" });
+  }
+  match format {
+    FloatFormat::Rounded => write!(f, "{val:.3}"),
+    FloatFormat::Decimal => {
+      let str = val.to_string();
+      if str.contains('.') {
+        write!(f, "{str}")
+      } else {
+        write!(f, "{str}.0")
+      }
+    }
+    FloatFormat::Scientific => {
+      let str = format!("{val:e}");
+      match str.split_once('e') {
+        Some((man, exp)) if !man.contains('.') => write!(f, "{man}.0e{exp}"),
+        _ => write!(f, "{str}"),
+      }
+    }
+    FloatFormat::Hex => {
+      let sign = if val.is_sign_negative() { "-" } else { "" };
+      let bits = val.abs().to_bits();
+      let (exp, man) = ((bits >> 23) as i32, bits & 0x7fffff);
+      // Subnormal numbers don't have the implicit leading 1.
+      let (lead, exp) = if exp == 0 { (0, if man == 0 { 0 } else { -126 }) } else { (1, exp - 127) };
+      // The 23 bits of the mantissa, shifted to fill 6 hex digits, without the trailing zeros.
+      let digits = format!("{:06x}", man << 1);
+      let digits = digits.trim_end_matches('0');
+      let digits = if digits.is_empty() { "0" } else { digits };
+      write!(f, "{sign}0x{lead}.{digits}p{exp}")
+    }
+  }
+}
+
 /* The actual display implementations */
 
 impl fmt::Display for Term {
   fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-    maybe_grow(|| match self {
-      Term::Lam { tag, pat, bod } => {
-        write!(f, "{}λ{} {}", tag.display_padded(), pat, bod)
-      }
-      Term::Var { nam } => write!(f, "{nam}"),
-      Term::Link { nam } => write!(f, "${nam}"),
-      Term::Let { pat, val, nxt } => write!(f, "let {} = {}; {}", pat, val, nxt),
-      Term::With { typ, bod } => write!(f, "with {typ} {{ {bod} }}"),
-      Term::Ask { pat, val, nxt } => write!(f, "ask {pat} = {val}; {nxt}"),
-      Term::Use { nam, val, nxt } => {
-        let Some(nam) = nam else { unreachable!() };
-        write!(f, "use {} = {}; {}", nam, val, nxt)
-      }
-      Term::Ref { nam: def_name } => write!(f, "{def_name}"),
-      Term::App { tag, fun, arg } => {
-        write!(f, "{}({} {})", tag.display_padded(), fun.display_app(tag), arg)
-      }
-      Term::Mat { arg, bnd, with_bnd, with_arg, arms } => {
-        write!(f, "match ")?;
-        if let Some(bnd) = bnd {
-          write!(f, "{} = ", bnd)?;
+    write!(f, "{}", self.display_with_floats(FloatFormat::Rounded))
+  }
+}
+
+impl Term {
+  /// Displays the term like its [`fmt::Display`] implementation, but with the floats in the given format.
+  pub fn display_with_floats(&self, floats: FloatFormat) -> impl fmt::Display + '_ {
+    DisplayFn(move |f| {
+      maybe_grow(|| match self {
+        Term::Lam { tag, pat, bod } => {
+          write!(f, "{}λ{} {}", tag.display_padded(), pat, bod.display_with_floats(floats))
         }
-        write!(f, "{} ", arg)?;
-        if !with_bnd.is_empty() {
-          write!(f, "with ")?;
-          for (bnd, arg) in with_bnd.iter().zip(with_arg.iter()) {
-            write!(f, "{} = {}, ", var_as_str(bnd), arg)?;
-          }
+        Term::Var { nam } => write!(f, "{nam}"),
+        Term::Link { nam } => write!(f, "${nam}"),
+        Term::Let { pat, val, nxt } => {
+          write!(f, "let {} = {}; {}", pat, val.display_with_floats(floats), nxt.display_with_floats(floats))
         }
-        write!(f, "{{ ")?;
-        for arm in arms {
-          write!(f, "{}", var_as_str(&arm.0))?;
-          for var in &arm.1 {
-            write!(f, " {}", var_as_str(var))?;
-          }
-          write!(f, ": {}; ", arm.2)?;
+        Term::With { typ, bod } => write!(f, "with {typ} {{ {} }}", bod.display_with_floats(floats)),
+        Term::Ask { pat, val, nxt } => {
+          write!(f, "ask {pat} = {}; {}", val.display_with_floats(floats), nxt.display_with_floats(floats))
         }
-        write!(f, "}}")
-      }
-      Term::Swt { arg, bnd, with_bnd, with_arg, pred, arms } => {
-        write!(f, "switch ")?;
-        if let Some(bnd) = bnd {
-          write!(f, "{bnd} = ")?;
+        Term::Use { nam, val, nxt } => {
+          let Some(nam) = nam else { unreachable!() };
+          write!(f, "use {} = {}; {}", nam, val.display_with_floats(floats), nxt.display_with_floats(floats))
         }
-        write!(f, "{arg} ")?;
-        if !with_bnd.is_empty() {
-          write!(f, "with ")?;
-          for (bnd, arg) in with_bnd.iter().zip(with_arg.iter()) {
-            write!(f, "{} = {}, ", var_as_str(bnd), arg)?;
-          }
+        Term::Ref { nam: def_name } => write!(f, "{def_name}"),
+        Term::App { tag, fun, arg } => {
+          write!(
+            f,
+            "{}({} {})",
+            tag.display_padded(),
+            fun.display_app(tag, floats),
+            arg.display_with_floats(floats)
+          )
         }
-        write!(f, "{{ ")?;
-        for (i, arm) in arms.iter().enumerate() {
-          if i == arms.len() - 1 {
-            write!(f, "_")?;
-            if let Some(pred) = pred {
-              write!(f, " {pred}")?;
+        Term::Mat { arg, bnd, with_bnd, with_arg, arms } => {
+          write!(f, "match ")?;
+          if let Some(bnd) = bnd {
+            write!(f, "{} = ", bnd)?;
+          }
+          write!(f, "{} ", arg.display_with_floats(floats))?;
+          if !with_bnd.is_empty() {
+            write!(f, "with ")?;
+            for (bnd, arg) in with_bnd.iter().zip(with_arg.iter()) {
+              write!(f, "{} = {}, ", var_as_str(bnd), arg.display_with_floats(floats))?;
             }
-          } else {
-            write!(f, "{i}")?;
           }
-          write!(f, ": {arm}; ")?;
-        }
-        write!(f, "}}")
-      }
-      Term::Fold { bnd, arg, with_bnd, with_arg, arms } => {
-        write!(f, "fold ")?;
-        if let Some(bnd) = bnd {
-          write!(f, "{} = ", bnd)?;
+          write!(f, "{{ ")?;
+          for arm in arms {
+            write!(f, "{}", var_as_str(&arm.0))?;
+            for var in &arm.1 {
+              write!(f, " {}", var_as_str(var))?;
+            }
+            write!(f, ": {}; ", arm.2.display_with_floats(floats))?;
+          }
+          write!(f, "}}")
         }
-        write!(f, "{} ", arg)?;
-        if !with_bnd.is_empty() {
-          write!(f, "with ")?;
-          for (bnd, arg) in with_bnd.iter().zip(with_arg.iter()) {
-            write!(f, "{} = {}, ", var_as_str(bnd), arg)?;
+        Term::Swt { arg, bnd, with_bnd, with_arg, pred, arms } => {
+          write!(f, "switch ")?;
+          if let Some(bnd) = bnd {
+            write!(f, "{bnd} = ")?;
+          }
+          write!(f, "{} ", arg.display_with_floats(floats))?;
+          if !with_bnd.is_empty() {
+            write!(f, "with ")?;
+            for (bnd, arg) in with_bnd.iter().zip(with_arg.iter()) {
+              write!(f, "{} = {}, ", var_as_str(bnd), arg.display_with_floats(floats))?;
+            }
           }
+          write!(f, "{{ ")?;
+          for (i, arm) in arms.iter().enumerate() {
+            if i == arms.len() - 1 {
+              write!(f, "_")?;
+              if let Some(pred) = pred {
+                write!(f, " {pred}")?;
+              }
+            } else {
+              write!(f, "{i}")?;
+            }
+            write!(f, ": {}; ", arm.display_with_floats(floats))?;
+          }
+          write!(f, "}}")
         }
-        write!(f, "{{ ")?;
-        for arm in arms {
-          write!(f, "{}", var_as_str(&arm.0))?;
-          for var in &arm.1 {
-            write!(f, " {}", var_as_str(var))?;
+        Term::Fold { bnd, arg, with_bnd, with_arg, arms } => {
+          write!(f, "fold ")?;
+          if let Some(bnd) = bnd {
+            write!(f, "{} = ", bnd)?;
           }
-          write!(f, ": {}; ", arm.2)?;
+          write!(f, "{} ", arg.display_with_floats(floats))?;
+          if !with_bnd.is_empty() {
+            write!(f, "with ")?;
+            for (bnd, arg) in with_bnd.iter().zip(with_arg.iter()) {
+              write!(f, "{} = {}, ", var_as_str(bnd), arg.display_with_floats(floats))?;
+            }
+          }
+          write!(f, "{{ ")?;
+          for arm in arms {
+            write!(f, "{}", var_as_str(&arm.0))?;
+            for var in &arm.1 {
+              write!(f, " {}", var_as_str(var))?;
+            }
+            write!(f, ": {}; ", arm.2.display_with_floats(floats))?;
+          }
+          write!(f, "}}")
         }
-        write!(f, "}}")
-      }
-      Term::Bend { bnd: bind, arg: init, cond, step, base } => {
-        write!(f, "bend ")?;
-        for (bind, init) in bind.iter().zip(init) {
-          if let Some(bind) = bind {
-            write!(f, "{} = ", bind)?;
+        Term::Bend { bnd: bind, arg: init, cond, step, base } => {
+          write!(f, "bend ")?;
+          for (bind, init) in bind.iter().zip(init) {
+            if let Some(bind) = bind {
+              write!(f, "{} = ", bind)?;
+            }
+            write!(f, "{}, ", init.display_with_floats(floats))?;
           }
-          write!(f, "{}, ", init)?;
+          write!(
+            f,
+            "{{ when {}: {}; else: {} }}",
+            cond.display_with_floats(floats),
+            step.display_with_floats(floats),
+            base.display_with_floats(floats)
+          )
         }
-        write!(f, "{{ when {cond}: {step}; else: {base} }}")
-      }
-      Term::Fan { fan: FanKind::Tup, tag, els } => write!(f, "{}({})", tag, DisplayJoin(|| els.iter(), ", ")),
-      Term::Fan { fan: FanKind::Dup, tag, els } => write!(f, "{}{{{}}}", tag, DisplayJoin(|| els, " ")),
-      Term::Era => write!(f, "*"),
-      Term::Num { val: Num::U24(val) } => write!(f, "{val}"),
-      Term::Num { val: Num::I24(val) } => write!(f, "{}{}", if *val < 0 { "-" } else { "+" }, val.abs()),
-      Term::Num { val: Num::F24(val) } => write!(f, "{val:.3}"),
-      Term::Nat { val } => write!(f, "#{val}"),
-      Term::Str { val } => write!(f, "{val:?}"),
-      Term::Oper { opr, fst, snd } => {
-        write!(f, "({} {} {})", opr, fst, snd)
-      }
-      Term::List { els } => write!(f, "[{}]", DisplayJoin(|| els.iter(), ", "),),
-      Term::Open { typ, var, bod } => write!(f, "open {typ} {var}; {bod}"),
-      Term::Err => write!(f, "<Invalid>"),
+        Term::Fan { fan: FanKind::Tup, tag, els } => {
+          write!(f, "{}({})", tag, DisplayJoin(|| els.iter().map(|e| e.display_with_floats(floats)), ", "))
+        }
+        Term::Fan { fan: FanKind::Dup, tag, els } => {
+          write!(f, "{}{{{}}}", tag, DisplayJoin(|| els.iter().map(|e| e.display_with_floats(floats)), " "))
+        }
+        Term::Era => write!(f, "*"),
+        Term::Num { val: Num::U24(val) } => write!(f, "{val}"),
+        Term::Num { val: Num::I24(val) } => write!(f, "{}{}", if *val < 0 { "-" } else { "+" }, val.abs()),
+        Term::Num { val: Num::F24(val) } => display_f24(f, *val, floats),
+        Term::Nat { val } => write!(f, "#{val}"),
+        Term::Str { val } => write!(f, "{val:?}"),
+        Term::Oper { opr, fst, snd } => {
+          write!(f, "({} {} {})", opr, fst.display_with_floats(floats), snd.display_with_floats(floats))
+        }
+        Term::List { els } => {
+          write!(f, "[{}]", DisplayJoin(|| els.iter().map(|e| e.display_with_floats(floats)), ", "))
+        }
+        Term::Open { typ, var, bod } => write!(f, "open {typ} {var}; {}", bod.display_with_floats(floats)),
+        Term::Err => write!(f, "<Invalid>"),
+      })
     })
   }
 }
@@ -181,7 +270,7 @@ impl fmt::Display for Pattern {
       Pattern::Fan(FanKind::Tup, tag, pats) => write!(f, "{}({})", tag, DisplayJoin(|| pats, ", ")),
       Pattern::Fan(FanKind::Dup, tag, pats) => write!(f, "{}{{{}}}", tag, DisplayJoin(|| pats, " ")),
       Pattern::Lst(pats) => write!(f, "[{}]", DisplayJoin(|| pats, ", ")),
-      Pattern::Str(str) => write!(f, "\"{str}\""),
+      Pattern::Str(str) => write!(f, "{str:?}"),
     }
   }
 }
@@ -209,6 +298,31 @@ impl fmt::Display for Book {
   }
 }
 
+impl Adt {
+  pub fn display<'a>(&'a self, nam: &'a Name) -> impl fmt::Display + 'a {
+    let ctrs = DisplayJoin(
+      move || {
+        self.ctrs.iter().map(move |(ctr, fields)| {
+          let ctr = ctr.strip_prefix(nam.as_ref()).and_then(|c| c.strip_prefix('/')).unwrap_or(ctr);
+          DisplayFn(move |f| {
+            if fields.is_empty() {
+              write!(f, "{ctr}")
+            } else {
+              let fields = DisplayJoin(
+                || fields.iter().map(|x| display!(" {}{}", if x.rec { "~" } else { "" }, x.nam)),
+                "",
+              );
+              write!(f, "({ctr}{fields})")
+            }
+          })
+        })
+      },
+      " | ",
+    );
+    display!("type {nam} = {ctrs}")
+  }
+}
+
 impl fmt::Display for Name {
   fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
     self.0.fmt(f)
@@ -216,13 +330,13 @@ impl fmt::Display for Name {
 }
 
 impl Term {
-  fn display_app<'a>(&'a self, tag: &'a Tag) -> impl fmt::Display + 'a {
+  fn display_app<'a>(&'a self, tag: &'a Tag, floats: FloatFormat) -> impl fmt::Display + 'a {
     maybe_grow(|| {
       DisplayFn(move |f| match self {
         Term::App { tag: tag2, fun, arg } if tag2 == tag => {
-          write!(f, "{} {}", fun.display_app(tag), arg)
+          write!(f, "{} {}", fun.display_app(tag, floats), arg.display_with_floats(floats))
         }
-        _ => write!(f, "{}", self),
+        _ => write!(f, "{}", self.display_with_floats(floats)),
       })
     })
   }
@@ -271,55 +385,103 @@ fn var_as_str(nam: &Option<Name>) -> &str {
 
 impl Book {
   pub fn display_pretty(&self) -> impl fmt::Display + '_ {
-    display!("{}", DisplayJoin(|| self.defs.values().map(|def| def.display_pretty()), "\n\n"))
+    display!(
+      "{}",
+      DisplayJoin(|| self.defs.values().map(|def| def.display_pretty(FloatFormat::Rounded)), "\n\n")
+    )
+  }
+}
+
+impl Book {
+  /// Pretty prints only the user-written datatypes and definitions,
+  /// in the order they were declared, as valid Bend source code, with the floats in the given format.
+  ///
+  /// The order is the one recorded in [`Book::source_order`] when the book was parsed.
+  pub fn display_source(&self, floats: FloatFormat) -> impl fmt::Display + '_ {
+    DisplayFn(move |f| {
+      for item in &self.source_order {
+        match item {
+          SourceItem::Adt(nam) => {
+            if let Some(adt) = self.adts.get(nam).filter(|adt| !adt.builtin) {
+              writeln!(f, "{}\n", adt.display(nam))?;
+            }
+          }
+          SourceItem::Def(nam) => {
+            if let Some(def) = self.defs.get(nam).filter(|def| !def.builtin) {
+              writeln!(f, "{}\n", def.display_pretty(floats))?;
+            }
+          }
+        }
+      }
+      Ok(())
+    })
   }
 }
 
 impl Definition {
-  pub fn display_pretty(&self) -> impl fmt::Display + '_ {
-    display!("{}", DisplayJoin(|| self.rules.iter().map(|x| x.display_pretty(&self.name)), "\n"))
+  pub fn display_pretty(&self, floats: FloatFormat) -> impl fmt::Display + '_ {
+    display!("{}", DisplayJoin(|| self.rules.iter().map(|x| x.display_pretty(&self.name, floats)), "\n"))
   }
 }
 
 impl Rule {
-  pub fn display_pretty<'a>(&'a self, def_name: &'a Name) -> impl fmt::Display + 'a {
+  pub fn display_pretty<'a>(&'a self, def_name: &'a Name, floats: FloatFormat) -> impl fmt::Display + 'a {
     display!(
       "({}{}) =\n  {}",
       def_name,
       DisplayJoin(|| self.pats.iter().map(|x| display!(" {x}")), ""),
-      self.body.display_pretty(2)
+      self.body.display_pretty_with_floats(2, floats)
     )
   }
 }
 
 impl Term {
   pub fn display_pretty(&self, tab: usize) -> impl fmt::Display + '_ {
+    self.display_pretty_with_floats(tab, FloatFormat::Rounded)
+  }
+
+  /// Like [`Term::display_pretty`], but with the floats in the given format.
+  pub fn display_pretty_with_floats(&self, tab: usize, floats: FloatFormat) -> impl fmt::Display + '_ {
     maybe_grow(|| {
       DisplayFn(move |f| match self {
         Term::Lam { tag, pat, bod } => {
-          write!(f, "{}λ{} {}", tag.display_padded(), pat, bod.display_pretty(tab))
+          write!(f, "{}λ{} {}", tag.display_padded(), pat, bod.display_pretty_with_floats(tab, floats))
         }
         Term::Var { nam } => write!(f, "{nam}"),
         Term::Link { nam } => write!(f, "${nam}"),
         Term::Let { pat, val, nxt } => {
-          write!(f, "let {} = {};\n{:tab$}{}", pat, val.display_pretty(tab), "", nxt.display_pretty(tab))
+          write!(
+            f,
+            "let {} = {};\n{:tab$}{}",
+            pat,
+            val.display_pretty_with_floats(tab, floats),
+            "",
+            nxt.display_pretty_with_floats(tab, floats)
+          )
         }
         Term::With { typ, bod } => {
           writeln!(f, "with {typ} {{")?;
-          writeln!(f, "{:tab$}{}", "", bod.display_pretty(tab + 2), tab = tab + 2)?;
+          writeln!(f, "{:tab$}{}", "", bod.display_pretty_with_floats(tab + 2, floats), tab = tab + 2)?;
           write!(f, "{:tab$}}}", "")
         }
         Term::Ask { pat, val, nxt } => {
-          write!(f, "ask {} = {};\n{:tab$}{}", pat, val.display_pretty(tab), "", nxt.display_pretty(tab))
+          write!(
+            f,
+            "ask {} = {};\n{:tab$}{}",
+            pat,
+            val.display_pretty_with_floats(tab, floats),
+            "",
+            nxt.display_pretty_with_floats(tab, floats)
+          )
         }
         Term::Use { nam, val, nxt } => {
           write!(
             f,
             "use {} = {};\n{:tab$}{}",
             var_as_str(nam),
-            val.display_pretty(tab),
+            val.display_pretty_with_floats(tab, floats),
             "",
-            nxt.display_pretty(tab)
+            nxt.display_pretty_with_floats(tab, floats)
           )
         }
         Term::App { tag, fun, arg } => {
@@ -327,46 +489,61 @@ impl Term {
             f,
             "{}({} {})",
             tag.display_padded(),
-            fun.display_app_pretty(tag, tab),
-            arg.display_pretty(tab)
+            fun.display_app_pretty(tag, tab, floats),
+            arg.display_pretty_with_floats(tab, floats)
           )
         }
         Term::Fan { fan: FanKind::Tup, tag, els } => {
-          write!(f, "{}({})", tag, DisplayJoin(|| els.iter().map(|e| e.display_pretty(tab)), ", "))
+          write!(
+            f,
+            "{}({})",
+            tag,
+            DisplayJoin(|| els.iter().map(|e| e.display_pretty_with_floats(tab, floats)), ", ")
+          )
         }
         Term::Fan { fan: FanKind::Dup, tag, els } => {
           write!(
             f,
             "{}{{{}}}",
             tag.display_padded(),
-            DisplayJoin(|| els.iter().map(|e| e.display_pretty(tab)), " ")
+            DisplayJoin(|| els.iter().map(|e| e.display_pretty_with_floats(tab, floats)), " ")
           )
         }
         Term::List { els } => {
-          write!(f, "[{}]", DisplayJoin(|| els.iter().map(|e| e.display_pretty(tab)), " "))
+          write!(
+            f,
+            "[{}]",
+            DisplayJoin(|| els.iter().map(|e| e.display_pretty_with_floats(tab, floats)), " ")
+          )
         }
         Term::Oper { opr, fst, snd } => {
-          write!(f, "({} {} {})", opr, fst.display_pretty(tab), snd.display_pretty(tab))
+          write!(
+            f,
+            "({} {} {})",
+            opr,
+            fst.display_pretty_with_floats(tab, floats),
+            snd.display_pretty_with_floats(tab, floats)
+          )
         }
         Term::Mat { bnd, arg, with_bnd, with_arg, arms } => {
           write!(f, "match ")?;
           if let Some(bnd) = bnd {
             write!(f, "{} = ", bnd)?;
           }
-          write!(f, "{} ", arg.display_pretty(tab))?;
+          write!(f, "{} ", arg.display_pretty_with_floats(tab, floats))?;
           if !with_bnd.is_empty() {
             write!(f, "with ")?;
             for (bnd, arg) in with_bnd.iter().zip(with_arg.iter()) {
-              write!(f, "{} = {}, ", var_as_str(bnd), arg)?;
+              write!(f, "{} = {}, ", var_as_str(bnd), arg.display_with_floats(floats))?;
             }
           }
-          write!(f, "{{ ")?;
+          write!(f, "{{")?;
           for arm in arms {
             write!(f, "\n{:tab$}{}", "", var_as_str(&arm.0), tab = tab + 2)?;
             for var in &arm.1 {
               write!(f, " {}", var_as_str(var))?;
             }
-            write!(f, ": {}; ", arm.2.display_pretty(tab + 4))?;
+            write!(f, ": {};", arm.2.display_pretty_with_floats(tab + 4, floats))?;
           }
           write!(f, "\n{:tab$}}}", "")
         }
@@ -375,11 +552,11 @@ impl Term {
           if let Some(bnd) = bnd {
             write!(f, "{bnd} = ")?;
           }
-          write!(f, "{} ", arg.display_pretty(tab))?;
+          write!(f, "{} ", arg.display_pretty_with_floats(tab, floats))?;
           if !with_bnd.is_empty() {
             write!(f, "with ")?;
             for (bnd, arg) in with_bnd.iter().zip(with_arg.iter()) {
-              write!(f, "{} = {}, ", var_as_str(bnd), arg)?;
+              write!(f, "{} = {}, ", var_as_str(bnd), arg.display_with_floats(floats))?;
             }
           }
           writeln!(f, "{{")?;
@@ -392,7 +569,7 @@ impl Term {
             } else {
               write!(f, "{:tab$}{i}", "", tab = tab + 2)?;
             }
-            writeln!(f, ": {};", arm.display_pretty(tab + 4))?;
+            writeln!(f, ": {};", arm.display_pretty_with_floats(tab + 4, floats))?;
           }
           write!(f, "{:tab$}}}", "")
         }
@@ -401,20 +578,20 @@ impl Term {
           if let Some(bnd) = bnd {
             write!(f, "{} = ", bnd)?;
           }
-          write!(f, "{} ", arg.display_pretty(tab))?;
+          write!(f, "{} ", arg.display_pretty_with_floats(tab, floats))?;
           if !with_bnd.is_empty() {
             write!(f, "with ")?;
             for (bnd, arg) in with_bnd.iter().zip(with_arg.iter()) {
-              write!(f, "{} = {}, ", var_as_str(bnd), arg)?;
+              write!(f, "{} = {}, ", var_as_str(bnd), arg.display_with_floats(floats))?;
             }
           }
-          write!(f, "{{ ")?;
+          write!(f, "{{")?;
           for arm in arms {
             write!(f, "\n{:tab$}{}", "", var_as_str(&arm.0), tab = tab + 2)?;
             for var in &arm.1 {
               write!(f, " {}", var_as_str(var))?;
             }
-            write!(f, ": {}; ", arm.2.display_pretty(tab + 4))?;
+            write!(f, ": {};", arm.2.display_pretty_with_floats(tab + 4, floats))?;
           }
           write!(f, "\n{:tab$}}}", "")
         }
@@ -424,22 +601,28 @@ impl Term {
             if let Some(bind) = bind {
               write!(f, "{} = ", bind)?;
             }
-            write!(f, "{}, ", init)?;
+            write!(f, "{}, ", init.display_with_floats(floats))?;
           }
           writeln!(f, "{{")?;
-          writeln!(f, "{:tab$}when {}:", "", cond.display_pretty(tab + 2), tab = tab + 2)?;
-          writeln!(f, "{:tab$}{}", "", step.display_pretty(tab + 4), tab = tab + 4)?;
+          writeln!(
+            f,
+            "{:tab$}when {}:",
+            "",
+            cond.display_pretty_with_floats(tab + 2, floats),
+            tab = tab + 2
+          )?;
+          writeln!(f, "{:tab$}{}", "", step.display_pretty_with_floats(tab + 4, floats), tab = tab + 4)?;
           writeln!(f, "{:tab$}else:", "", tab = tab + 2)?;
-          writeln!(f, "{:tab$}{}", "", base.display_pretty(tab + 4), tab = tab + 4)?;
+          writeln!(f, "{:tab$}{}", "", base.display_pretty_with_floats(tab + 4, floats), tab = tab + 4)?;
           write!(f, "{:tab$}}}", "")
         }
         Term::Open { typ, var, bod } => {
-          write!(f, "open {typ} {var};\n{:tab$}{}", "", bod.display_pretty(tab))
+          write!(f, "open {typ} {var};\n{:tab$}{}", "", bod.display_pretty_with_floats(tab, floats))
         }
         Term::Nat { val } => write!(f, "#{val}"),
         Term::Num { val: Num::U24(val) } => write!(f, "{val}"),
         Term::Num { val: Num::I24(val) } => write!(f, "{}{}", if *val < 0 { "-" } else { "+" }, val.abs()),
-        Term::Num { val: Num::F24(val) } => write!(f, "{val:.3}"),
+        Term::Num { val: Num::F24(val) } => display_f24(f, *val, floats),
         Term::Str { val } => write!(f, "{val:?}"),
         Term::Ref { nam } => write!(f, "{nam}"),
         Term::Era => write!(f, "*"),
@@ -448,13 +631,23 @@ impl Term {
     })
   }
 
-  fn display_app_pretty<'a>(&'a self, tag: &'a Tag, tab: usize) -> impl fmt::Display + 'a {
+  fn display_app_pretty<'a>(
+    &'a self,
+    tag: &'a Tag,
+    tab: usize,
+    floats: FloatFormat,
+  ) -> impl fmt::Display + 'a {
     maybe_grow(|| {
       DisplayFn(move |f| match self {
         Term::App { tag: tag2, fun, arg } if tag2 == tag => {
-          write!(f, "{} {}", fun.display_app_pretty(tag, tab), arg.display_pretty(tab))
+          write!(
+            f,
+            "{} {}",
+            fun.display_app_pretty(tag, tab, floats),
+            arg.display_pretty_with_floats(tab, floats)
+          )
         }
-        _ => write!(f, "{}", self.display_pretty(tab)),
+        _ => write!(f, "{}", self.display_pretty_with_floats(tab, floats)),
       })
     })
   }
@@ -0,0 +1,92 @@
+use crate::{
+  fun::{FanKind, Num, Tag, Term},
+  maybe_grow,
+};
+use serde_json::{Number, Value};
+
+const U24_MAX: u64 = 0xFFFFFF;
+const I24_MIN: i64 = -0x800000;
+
+/// Converts a JSON value to the Bend term with the same data, so that it can be given to a program.
+///
+/// - Strings become `String`s.
+/// - Arrays become `List`s.
+/// - Objects become `List`s of `(key, value)` tuples, sorted by their keys.
+/// - Whole numbers become `u24`, or `i24` if they're negative. Other numbers become `f24`.
+/// - `true` and `false` become `1` and `0`, like the results of the comparison operators.
+/// - `null` becomes an erased value, `*`.
+///
+/// Fails if a whole number doesn't fit in 24 bits.
+pub fn json_to_term(value: &Value) -> Result<Term, String> {
+  maybe_grow(|| {
+    let term = match value {
+      Value::Null => Term::Era,
+      Value::Bool(val) => Term::Num { val: Num::U24(*val as u32) },
+      Value::Number(num) => {
+        let val = if let Some(val) = num.as_u64() {
+          if val > U24_MAX {
+            return Err(format!("The number {val} doesn't fit in a u24."));
+          }
+          Num::U24(val as u32)
+        } else if let Some(val) = num.as_i64() {
+          if val < I24_MIN {
+            return Err(format!("The number {val} doesn't fit in an i24."));
+          }
+          Num::I24(val as i32)
+        } else {
+          Num::F24(num.as_f64().unwrap_or(f64::NAN) as f32)
+        };
+        Term::Num { val }
+      }
+      Value::String(val) => Term::str(val),
+      Value::Array(vals) => Term::List { els: vals.iter().map(json_to_term).collect::<Result<_, _>>()? },
+      Value::Object(fields) => {
+        let els = fields
+          .iter()
+          .map(|(key, val)| {
+            Ok(Term::Fan {
+              fan: FanKind::Tup,
+              tag: Tag::Static,
+              els: vec![Term::str(key), json_to_term(val)?],
+            })
+          })
+          .collect::<Result<_, String>>()?;
+        Term::List { els }
+      }
+    };
+    Ok(term)
+  })
+}
+
+/// Converts a result of a program to JSON, the inverse of [`json_to_term`].
+///
+/// - `String`s become strings.
+/// - `List`s and tuples become arrays.
+/// - Numbers become JSON numbers.
+/// - Erased values become `null`.
+///
+/// Fails if the term has anything else, like a lambda or a constructor of another type,
+/// or a float that is not finite.
+pub fn term_to_json(term: &Term) -> Result<Value, String> {
+  maybe_grow(|| {
+    let value = match term {
+      Term::Era => Value::Null,
+      Term::Num { val: Num::U24(val) } => Value::from(*val),
+      Term::Num { val: Num::I24(val) } => Value::from(*val),
+      Term::Num { val: Num::F24(val) } => match Number::from_f64(*val as f64) {
+        Some(num) => Value::Number(num),
+        None => return Err(format!("The float {val} can't be represented in JSON.")),
+      },
+      Term::Str { val } => Value::String(val.to_string()),
+      Term::List { els } | Term::Fan { fan: FanKind::Tup, els, .. } => {
+        Value::Array(els.iter().map(term_to_json).collect::<Result<_, _>>()?)
+      }
+      _ => {
+        return Err(format!(
+          "The term '{term}' can't be represented in JSON. Only numbers, strings, lists and tuples can."
+        ))
+      }
+    };
+    Ok(value)
+  })
+}
@@ -1,15 +1,180 @@
-use crate::fun::{self, parser::TermParser};
-use std::path::Path;
+use crate::{
+  diagnostics::{Diagnostics, TextSpan},
+  fun::{self, parser::TermParser, Name},
+};
+use std::{
+  collections::HashMap,
+  io::Read,
+  path::{Path, PathBuf},
+};
+use TSPL::Parser;
+
+/// The path that stands for the standard input.
+pub const STDIN_PATH: &str = "-";
 
 // TODO: Refactor so that we don't mix the two syntaxes here.
 
 /// Reads a file and parses to a definition book.
-pub fn load_file_to_book(path: &Path) -> Result<fun::Book, String> {
-  let builtins = fun::Book::builtins();
+///
+/// The path `-` reads the program from stdin instead.
+pub fn load_file_to_book(path: &Path) -> Result<fun::Book, Diagnostics> {
+  load_file_to_book_with_prelude(path, fun::Book::builtins())
+}
+
+/// Like [`load_file_to_book`], but starts from the definitions of `prelude` instead of the builtins.
+pub fn load_file_to_book_with_prelude(path: &Path, prelude: fun::Book) -> Result<fun::Book, Diagnostics> {
+  if path == Path::new(STDIN_PATH) {
+    return read_to_book(std::io::stdin(), Path::new("<stdin>"), prelude);
+  }
   let code = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
-  do_parse_book(&code, path, builtins)
+  do_parse_book(&code, path, prelude)
+}
+
+/// Reads a program from `reader` and parses to a definition book.
+/// `name` is used as the file name in diagnostics.
+pub fn load_reader_to_book(reader: impl Read, name: &Path) -> Result<fun::Book, Diagnostics> {
+  read_to_book(reader, name, fun::Book::builtins())
+}
+
+fn read_to_book(mut reader: impl Read, name: &Path, prelude: fun::Book) -> Result<fun::Book, Diagnostics> {
+  let mut code = String::new();
+  reader.read_to_string(&mut code).map_err(|e| format!("Failed to read {}.\n{e}", name.display()))?;
+  do_parse_book(&code, name, prelude)
+}
+
+/// Parses a program from its source code, starting from the builtins like [`load_file_to_book`].
+/// `filename` is only used as the file name in diagnostics, so it doesn't need to exist.
+///
+/// ```
+/// let book = bend::parse_book("main = (+ 1 2)", "<buffer>").unwrap();
+/// assert!(book.defs.contains_key(&bend::fun::Name::new("main")));
+///
+/// let err = bend::parse_book("main = (+ 1", "<buffer>").unwrap_err();
+/// assert!(err.to_string().contains("<buffer>"));
+/// ```
+pub fn parse_book(src: &str, filename: &str) -> Result<fun::Book, Diagnostics> {
+  parse_book_with_prelude(src, filename, fun::Book::builtins())
+}
+
+/// Like [`parse_book`], but starts from the definitions of `prelude` instead of the builtins.
+pub fn parse_book_with_prelude(
+  src: &str,
+  filename: &str,
+  prelude: fun::Book,
+) -> Result<fun::Book, Diagnostics> {
+  do_parse_book(src, Path::new(filename), prelude)
+}
+
+/// Reads a file to use as the prelude instead of the builtins.
+///
+/// Its definitions are marked as built-in, so they're only compiled when used
+/// and can't be redefined by the programs loaded after it.
+pub fn load_prelude_file(path: &Path) -> Result<fun::Book, Diagnostics> {
+  let code = std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}.\n{e}", path.display()))?;
+  parse_book_with(&code, path, fun::Book::default(), true)
+}
+
+/// Reads several files and merges their definitions into a single book.
+///
+/// Names defined in more than one of the files are reported as errors.
+pub fn load_files_to_book(paths: &[PathBuf]) -> Result<fun::Book, Diagnostics> {
+  load_files_to_book_with_prelude(paths, fun::Book::builtins())
+}
+
+/// Like [`load_files_to_book`], but starts from the definitions of `prelude` instead of the builtins.
+pub fn load_files_to_book_with_prelude(
+  paths: &[PathBuf],
+  prelude: fun::Book,
+) -> Result<fun::Book, Diagnostics> {
+  let mut book = prelude.clone();
+  let mut diags = Diagnostics::default();
+  // Functions and constructors share a namespace, while types have their own.
+  let mut def_origins: HashMap<Name, &Path> = HashMap::new();
+  let mut adt_origins: HashMap<Name, &Path> = HashMap::new();
+
+  fn check_origin<'a>(
+    origins: &mut HashMap<Name, &'a Path>,
+    diags: &mut Diagnostics,
+    kind: &str,
+    name: &Name,
+    path: &'a Path,
+  ) {
+    if let Some(prev) = origins.insert(name.clone(), path) {
+      diags.add_book_error(format!(
+        "Redefinition of {kind} '{name}'. It is defined in both '{}' and '{}'.",
+        prev.display(),
+        path.display()
+      ));
+    }
+  }
+
+  let mut entrypoint_origin: Option<&Path> = None;
+
+  for path in paths {
+    let file_book = load_file_to_book_with_prelude(path, prelude.clone())?;
+
+    if let Some(entrypoint) = file_book.entrypoint {
+      match (&book.entrypoint, entrypoint_origin) {
+        (Some(prev), Some(prev_path)) => diags.add_book_error(format!(
+          "Both '{prev}' in '{}' and '{entrypoint}' in '{}' are marked as the entrypoint.",
+          prev_path.display(),
+          path.display()
+        )),
+        _ => {
+          book.entrypoint = Some(entrypoint);
+          entrypoint_origin = Some(path.as_path());
+        }
+      }
+    }
+
+    for (name, adt) in file_book.adts {
+      if adt.builtin {
+        continue;
+      }
+      check_origin(&mut adt_origins, &mut diags, "type", &name, path.as_path());
+      for ctr in adt.ctrs.keys() {
+        check_origin(&mut def_origins, &mut diags, "constructor", ctr, path.as_path());
+        book.ctrs.insert(ctr.clone(), name.clone());
+      }
+      book.adts.insert(name, adt);
+    }
+
+    for (name, def) in file_book.defs {
+      if def.builtin {
+        continue;
+      }
+      check_origin(&mut def_origins, &mut diags, "function", &name, path.as_path());
+      book.defs.insert(name, def);
+    }
+    book.allowed_warnings.extend(file_book.allowed_warnings);
+    book.def_spans.extend(file_book.def_spans);
+    book.source_order.extend(file_book.source_order);
+  }
+
+  if diags.has_errors() {
+    Err(diags)
+  } else {
+    Ok(book)
+  }
+}
+
+pub fn do_parse_book(code: &str, path: &Path, builtins: fun::Book) -> Result<fun::Book, Diagnostics> {
+  parse_book_with(code, path, builtins, false)
 }
 
-pub fn do_parse_book(code: &str, path: &Path, builtins: fun::Book) -> Result<fun::Book, String> {
-  TermParser::new(code).parse_book(builtins, false).map_err(|e| format!("In {} :\n{}", path.display(), e))
+fn parse_book_with(
+  code: &str,
+  path: &Path,
+  default_book: fun::Book,
+  builtin: bool,
+) -> Result<fun::Book, Diagnostics> {
+  let mut parser = TermParser::new(code);
+  parser.parse_book(default_book, builtin).map_err(|e| {
+    // The parser doesn't keep the span of the error, so we point to where it stopped.
+    let idx = *parser.index();
+    let span = TextSpan::from_byte_span(code, idx, idx + 1);
+    let mut diags = Diagnostics::default();
+    diags.add_source_error(format!("In {} :\n{}", path.display(), e), span);
+    diags
+  })
 }
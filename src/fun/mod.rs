@@ -1,23 +1,34 @@
 use crate::{
-  diagnostics::{Diagnostics, DiagnosticsConfig},
-  maybe_grow, multi_iterator, ENTRY_POINT,
+  diagnostics::{DiagnosticRecord, Diagnostics, DiagnosticsConfig, TextSpan, WarningType},
+  maybe_grow, multi_iterator, AdtEncoding, CompileOpts, ENTRY_POINT,
 };
 use indexmap::{IndexMap, IndexSet};
 use interner::global::{GlobalPool, GlobalString};
 use itertools::Itertools;
-use std::{borrow::Cow, collections::HashMap, hash::Hash, ops::Deref};
+use serde::{Deserialize, Serialize};
+use std::{
+  borrow::Cow,
+  collections::{BTreeMap, HashSet},
+  hash::Hash,
+  ops::Deref,
+};
 
 pub mod builtins;
 pub mod check;
+pub mod dependencies;
 pub mod display;
+pub mod json;
 pub mod load_book;
 pub mod net_to_term;
 pub mod parser;
+pub mod sexpr;
+pub mod structural_eq;
 pub mod term_to_net;
 pub mod transform;
 
 pub use net_to_term::{net_to_term, ReadbackError};
 pub use term_to_net::{book_to_hvm, term_to_hvm};
+pub use transform::instrument_coverage::CoverageArm;
 
 pub static STRINGS: GlobalPool<String> = GlobalPool::new();
 #[derive(Debug)]
@@ -28,12 +39,15 @@ pub struct Ctx<'book> {
 
 impl Ctx<'_> {
   pub fn new(book: &mut Book, diagnostics_cfg: DiagnosticsConfig) -> Ctx {
-    Ctx { book, info: Diagnostics::new(diagnostics_cfg) }
+    let mut info = Diagnostics::new(diagnostics_cfg);
+    info.allowed_warnings = book.allowed_warnings.clone();
+    info.def_spans = book.def_spans.clone();
+    Ctx { book, info }
   }
 }
 
 /// The representation of a program.
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct Book {
   /// The function definitions.
   pub defs: Definitions,
@@ -46,14 +60,49 @@ pub struct Book {
 
   /// A custom or default "main" entrypoint.
   pub entrypoint: Option<Name>,
+
+  /// The definitions provided by the host, already compiled to HVM nets.
+  #[serde(skip)]
+  pub host_defs: IndexMap<Name, hvm::ast::Net>,
+
+  /// The warnings allowed in each definition with a `# allow(...)` attribute.
+  #[serde(skip)]
+  pub allowed_warnings: BTreeMap<Name, Vec<WarningType>>,
+
+  /// The region of the source code of each user definition, in the file it was parsed from.
+  #[serde(skip)]
+  pub def_spans: BTreeMap<Name, TextSpan>,
+
+  /// The match arms of the user's definitions whose coverage is measured, in the order of the program.
+  #[serde(skip)]
+  pub coverage_arms: Vec<CoverageArm>,
+
+  /// The user-written datatypes and definitions, in the order they were declared.
+  #[serde(skip)]
+  pub source_order: Vec<SourceItem>,
 }
 
 pub type Definitions = IndexMap<Name, Definition>;
 pub type Adts = IndexMap<Name, Adt>;
 pub type Constructors = IndexMap<Name, Name>;
 
+/// A top-level datatype or definition of the source code, see [`Book::source_order`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SourceItem {
+  Adt(Name),
+  Def(Name),
+}
+
+impl SourceItem {
+  pub fn name(&self) -> &Name {
+    match self {
+      SourceItem::Adt(nam) | SourceItem::Def(nam) => nam,
+    }
+  }
+}
+
 /// A pattern matching function definition.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct Definition {
   pub name: Name,
   pub rules: Vec<Rule>,
@@ -61,13 +110,13 @@ pub struct Definition {
 }
 
 /// A pattern matching rule of a definition.
-#[derive(Debug, Clone, Default, PartialEq)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
 pub struct Rule {
   pub pats: Vec<Pattern>,
   pub body: Term,
 }
 
-#[derive(Debug, Default, PartialEq, Eq, Hash)]
+#[derive(Debug, Default, PartialEq, Eq, Hash, Serialize)]
 pub enum Term {
   Lam {
     tag: Tag,
@@ -117,6 +166,7 @@ pub enum Term {
     val: u32,
   },
   Str {
+    #[serde(serialize_with = "serialize_global_string")]
     val: GlobalString,
   },
   List {
@@ -174,13 +224,13 @@ pub enum Term {
 
 pub type MatchRule = (Option<Name>, Vec<Option<Name>>, Term);
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
 pub enum FanKind {
   Tup,
   Dup,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
 pub enum Op {
   ADD,
   SUB,
@@ -204,14 +254,14 @@ pub enum Op {
   POW,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize)]
 pub enum Num {
   U24(u32),
   I24(i32),
   F24(f32),
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
 pub enum Pattern {
   Var(Option<Name>),
   Chn(Name),
@@ -220,10 +270,10 @@ pub enum Pattern {
   /// Either a tuple or a duplication
   Fan(FanKind, Tag, Vec<Pattern>),
   Lst(Vec<Pattern>),
-  Str(GlobalString),
+  Str(#[serde(serialize_with = "serialize_global_string")] GlobalString),
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default, Serialize)]
 pub enum Tag {
   Named(Name),
   Numeric(u16),
@@ -233,17 +283,47 @@ pub enum Tag {
 }
 
 /// A user defined datatype
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct Adt {
   pub ctrs: IndexMap<Name, Vec<CtrField>>,
   pub builtin: bool,
 }
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
 pub struct CtrField {
   pub nam: Name,
   pub rec: bool,
 }
+
+/// A datatype of a book, as listed by [`Book::adt_infos`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AdtInfo {
+  pub name: Name,
+  /// The constructors, in the order they were declared.
+  pub ctrs: Vec<CtrInfo>,
+  pub builtin: bool,
+}
+
+/// A constructor of a datatype, as listed by [`Book::adt_infos`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CtrInfo {
+  pub name: Name,
+  /// The fields, in order, marking the ones that have the type of the datatype itself.
+  pub fields: Vec<CtrField>,
+  /// The number that tells this constructor apart in the encoded values,
+  /// or `None` if the encoding doesn't use tags.
+  pub tag: Option<u32>,
+}
+
+impl CtrInfo {
+  pub fn arity(&self) -> usize {
+    self.fields.len()
+  }
+}
+/// A name of a definition, constructor or variable.
+///
+/// Names are interned in the global [`STRINGS`] pool, so equal names share the same storage,
+/// cloning one doesn't allocate and comparing two for equality doesn't look at their characters.
 #[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct Name(GlobalString);
 
@@ -745,9 +825,11 @@ impl Term {
   }
 
   /// Collects all the free variables that a term has
-  /// and the number of times each var is used
-  pub fn free_vars(&self) -> HashMap<Name, u64> {
-    fn go_term(term: &Term, free_vars: &mut HashMap<Name, u64>) {
+  /// and the number of times each var is used.
+  ///
+  /// The variables are sorted by name, so that the definitions generated from them are deterministic.
+  pub fn free_vars(&self) -> BTreeMap<Name, u64> {
+    fn go_term(term: &Term, free_vars: &mut BTreeMap<Name, u64>) {
       maybe_grow(|| {
         if let Term::Var { nam } = term {
           *free_vars.entry(nam.clone()).or_default() += 1;
@@ -771,6 +853,49 @@ impl Term {
     free_vars
   }
 
+  /// Renames the references to the definition `old`, resolved or not, to `new`.
+  /// Stops at the binders of variables named `old`, since they shadow the definition.
+  fn rename_ref(&mut self, old: &Name, new: &Name) {
+    maybe_grow(|| {
+      match self {
+        Term::Ref { nam } | Term::Var { nam } if nam == old => *nam = new.clone(),
+        _ => {}
+      }
+      for (child, mut binds) in self.children_mut_with_binds() {
+        if !binds.any(|bind| bind.as_ref() == Some(old)) {
+          child.rename_ref(old, new);
+        }
+      }
+    })
+  }
+
+  /// Collects the names of all the definitions that a term references.
+  ///
+  /// References are only told apart from variables by [`Ctx::resolve_refs`],
+  /// so before that pass they are returned by [`Term::free_vars`] instead.
+  pub fn references(&self) -> HashSet<Name> {
+    fn go_term(term: &Term, refs: &mut HashSet<Name>) {
+      maybe_grow(|| {
+        if let Term::Ref { nam } = term {
+          refs.insert(nam.clone());
+        }
+
+        for child in term.children() {
+          go_term(child, refs);
+        }
+      })
+    }
+
+    let mut refs = HashSet::new();
+    go_term(self, &mut refs);
+    refs
+  }
+
+  /// The number of nodes of a term, counting the term itself and all its subterms.
+  pub fn node_count(&self) -> usize {
+    maybe_grow(|| 1 + self.children().map(Term::node_count).sum::<usize>())
+  }
+
   /// Returns the set of declared and the set of used unscoped variables
   pub fn unscoped_vars(&self) -> (IndexSet<Name>, IndexSet<Name>) {
     fn go_pat(pat: &Pattern, decls: &mut IndexSet<Name>) {
@@ -974,10 +1099,18 @@ impl Definition {
 }
 
 impl Name {
+  /// Interns a name. Only allocates if no equal name was interned before
+  /// or if an owned `String` is given.
   pub fn new<'a, V: Into<Cow<'a, str>>>(value: V) -> Name {
     Name(STRINGS.get(value))
   }
 
+  /// Interns a name from a borrowed string, sharing the storage of any equal name interned before.
+  /// This is the same as [`Name::new`], but makes clear that an existing name is reused.
+  pub fn intern(value: &str) -> Name {
+    Name::new(value)
+  }
+
   pub fn is_generated(&self) -> bool {
     // Generated def names use $ while var names use %
     self.contains("__") || self.contains('%')
@@ -1024,6 +1157,25 @@ impl AsRef<str> for Name {
   }
 }
 
+impl Serialize for Name {
+  fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(self)
+  }
+}
+
+impl<'de> Deserialize<'de> for Name {
+  fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+    String::deserialize(deserializer).map(Name::new)
+  }
+}
+
+fn serialize_global_string<S: serde::Serializer>(
+  str: &GlobalString,
+  serializer: S,
+) -> Result<S::Ok, S::Error> {
+  serializer.serialize_str(str)
+}
+
 impl Book {
   pub fn hvm_entrypoint(&self) -> &str {
     match self.entrypoint.as_ref().map(|e| e.as_ref()) {
@@ -1031,6 +1183,178 @@ impl Book {
       Some(nam) => nam,
     }
   }
+
+  /// Runs all the static checks of the compiler on a copy of this book and returns the warnings
+  /// and errors found, without printing anything.
+  ///
+  /// Unlike [`crate::check_book`], the records are returned whether or not there were errors.
+  ///
+  /// ```
+  /// use bend::{diagnostics::{DiagnosticsConfig, Severity}, load_reader_to_book, CompileOpts};
+  /// use std::path::Path;
+  ///
+  /// let code = "main = (λx x foo)";
+  /// let book = load_reader_to_book(code.as_bytes(), Path::new("example.bend")).unwrap();
+  /// let records = book.validate(DiagnosticsConfig::default(), CompileOpts::default());
+  ///
+  /// assert!(records.iter().any(|record| record.severity == Severity::Error));
+  /// ```
+  pub fn validate(
+    &self,
+    diagnostics_cfg: DiagnosticsConfig,
+    compile_opts: CompileOpts,
+  ) -> Vec<DiagnosticRecord> {
+    match crate::check_book(&mut self.clone(), diagnostics_cfg, compile_opts) {
+      Ok(diagnostics) | Err(diagnostics) => diagnostics.records(),
+    }
+  }
+
+  /// Lists the datatypes of the book and their constructors, in the order they were declared,
+  /// for tools that work with the values of a program, like generators of bindings in other languages.
+  ///
+  /// The tags are the ones the constructors have in the given encoding: their index in the datatype
+  /// for num-scott, a number unique across the book for tagged-scott and none for scott.
+  ///
+  /// ```
+  /// use bend::{load_reader_to_book, AdtEncoding};
+  /// use std::path::Path;
+  ///
+  /// let code = "type Shape = (Circle r) | (Rect w h) | Empty\nmain = Shape/Empty";
+  /// let book = load_reader_to_book(code.as_bytes(), Path::new("example.bend")).unwrap();
+  /// let adts = book.adt_infos(AdtEncoding::NumScott);
+  /// let shape = adts.iter().find(|adt| adt.name == "Shape").unwrap();
+  ///
+  /// assert_eq!(shape.ctrs.iter().map(|ctr| ctr.arity()).collect::<Vec<_>>(), [1, 2, 0]);
+  /// assert_eq!(shape.ctrs[2].tag, Some(2));
+  /// ```
+  pub fn adt_infos(&self, encoding: AdtEncoding) -> Vec<AdtInfo> {
+    let ctr_tags = self.ctr_tags();
+    self
+      .adts
+      .iter()
+      .map(|(name, adt)| {
+        let ctrs = adt.ctrs.iter().enumerate().map(|(idx, (ctr, fields))| {
+          let tag = match encoding {
+            AdtEncoding::Scott => None,
+            AdtEncoding::NumScott => Some(idx as u32),
+            AdtEncoding::TaggedScott => Some(ctr_tags[ctr]),
+          };
+          CtrInfo { name: ctr.clone(), fields: fields.clone(), tag }
+        });
+        AdtInfo { name: name.clone(), ctrs: ctrs.collect(), builtin: adt.builtin }
+      })
+      .collect()
+  }
+
+  /// Adds the definitions and datatypes of `other` to this book.
+  ///
+  /// Names defined in both books are reported as errors, in which case this book is left unchanged.
+  /// Built-ins present in both books, like when both were loaded with the default builtins, are kept once.
+  /// If only one of the books has an entrypoint set, the merged book keeps it.
+  pub fn merge(&mut self, other: Book) -> Result<(), Diagnostics> {
+    let mut diags = Diagnostics::default();
+
+    let adts = other
+      .adts
+      .into_iter()
+      .filter(|(name, adt)| !(adt.builtin && self.adts.get(name).is_some_and(|adt| adt.builtin)))
+      .collect::<Vec<_>>();
+    let defs = other
+      .defs
+      .into_iter()
+      .filter(|(name, def)| !(def.builtin && self.defs.get(name).is_some_and(|def| def.builtin)))
+      .collect::<Vec<_>>();
+
+    // Functions and constructors share a namespace, while types have their own.
+    let is_defined = |name: &Name| self.defs.contains_key(name) || self.ctrs.contains_key(name);
+    for (name, adt) in &adts {
+      if self.adts.contains_key(name) {
+        diags.add_book_error(format!("Redefinition of type '{name}'."));
+      }
+      for ctr in adt.ctrs.keys().filter(|ctr| is_defined(ctr)) {
+        diags.add_book_error(format!("Redefinition of constructor '{ctr}'."));
+      }
+    }
+    for (name, _) in defs.iter().filter(|(name, _)| is_defined(name)) {
+      diags.add_book_error(format!("Redefinition of function '{name}'."));
+    }
+    match (&self.entrypoint, &other.entrypoint) {
+      (Some(entrypoint), Some(other_entrypoint)) if entrypoint != other_entrypoint => diags.add_book_error(
+        format!("Both '{entrypoint}' and '{other_entrypoint}' are marked as the entrypoint."),
+      ),
+      _ => {}
+    }
+
+    if diags.has_errors() {
+      return Err(diags);
+    }
+
+    for (name, adt) in adts {
+      for ctr in adt.ctrs.keys() {
+        self.ctrs.insert(ctr.clone(), name.clone());
+      }
+      self.adts.insert(name, adt);
+    }
+    self.defs.extend(defs);
+    self.allowed_warnings.extend(other.allowed_warnings);
+    self.def_spans.extend(other.def_spans);
+    self.source_order.extend(other.source_order);
+    if self.entrypoint.is_none() {
+      self.entrypoint = other.entrypoint;
+    }
+    Ok(())
+  }
+
+  /// Renames the definition `old` to `new`, along with every reference to it.
+  ///
+  /// References are renamed whether they were already resolved or are still variables,
+  /// like in a book that was just parsed. Variables named `old` that are bound inside of a
+  /// definition shadow it, so they're left as they are.
+  /// The entrypoint and the `# allow(...)` attributes of the definition follow the new name.
+  ///
+  /// Fails, leaving the book unchanged, if there's no definition `old`
+  /// or if `new` is already the name of a definition or constructor.
+  pub fn rename_def(&mut self, old: &Name, new: &Name) -> Result<(), Diagnostics> {
+    let mut diags = Diagnostics::default();
+    if !self.defs.contains_key(old) {
+      diags.add_book_error(format!("There's no definition named '{old}' to rename."));
+    }
+    if self.defs.contains_key(new) || self.ctrs.contains_key(new) {
+      diags.add_book_error(format!("Can't rename '{old}' to '{new}', since '{new}' is already defined."));
+    }
+    if diags.has_errors() {
+      return Err(diags);
+    }
+
+    // Keeps the definition in the same position, so that the order of the book doesn't change.
+    let (idx, _, mut def) = self.defs.shift_remove_full(old).unwrap();
+    def.name = new.clone();
+    self.defs.shift_insert(idx, new.clone(), def);
+
+    for def in self.defs.values_mut() {
+      for rule in def.rules.iter_mut() {
+        if !rule.pats.iter().flat_map(Pattern::binds).any(|bind| bind.as_ref() == Some(old)) {
+          rule.body.rename_ref(old, new);
+        }
+      }
+    }
+
+    if self.entrypoint.as_ref() == Some(old) {
+      self.entrypoint = Some(new.clone());
+    }
+    if let Some(allowed) = self.allowed_warnings.remove(old) {
+      self.allowed_warnings.insert(new.clone(), allowed);
+    }
+    if let Some(span) = self.def_spans.remove(old) {
+      self.def_spans.insert(new.clone(), span);
+    }
+    for item in self.source_order.iter_mut() {
+      if *item == SourceItem::Def(old.clone()) {
+        *item = SourceItem::Def(new.clone());
+      }
+    }
+    Ok(())
+  }
 }
 
 #[test]
@@ -1064,3 +1388,55 @@ fn num_to_from_bits() {
     assert_eq!(b, Num::from_bits(Num::to_bits(&b)));
   }
 }
+
+#[test]
+fn float_formats_round_trip() {
+  use display::FloatFormat;
+
+  let vals = [
+    0.0,
+    -0.0,
+    0.1,
+    -1.5,
+    0.00123,
+    12345.023,
+    16777215.0,
+    1e-30,
+    -3e30,
+    f32::MIN_POSITIVE,
+    f32::INFINITY,
+    f32::NEG_INFINITY,
+    f32::NAN,
+  ];
+  for val in vals {
+    // Rounds the value to the precision of an F24 first.
+    let num = Num::from_bits(Num::F24(val).to_bits());
+    for format in [FloatFormat::Decimal, FloatFormat::Scientific, FloatFormat::Hex] {
+      let text = Term::Num { val: num }.display_with_floats(format).to_string();
+      let parsed = parser::TermParser::new(&text).parse_term().unwrap();
+      let Term::Num { val: parsed } = parsed else { panic!("'{text}' didn't parse to a number") };
+      assert_eq!(parsed.to_bits(), num.to_bits(), "{format:?}: {text}");
+    }
+  }
+}
+
+#[test]
+fn merge_books() {
+  let parse = |code: &str| parser::TermParser::new(code).parse_book(Book::builtins(), false).unwrap();
+
+  let mut book = parse("type Color = Red | Green\nfoo = 1\nmain = (foo Red)");
+  let overlapping = parse("foo = 2\nbar = 3\ntype Color = Blue");
+  let err = book.merge(overlapping).unwrap_err().to_string();
+  assert!(err.contains("Redefinition of function 'foo'."));
+  assert!(err.contains("Redefinition of type 'Color'."));
+  assert!(!err.contains("'bar'"));
+  assert!(!book.defs.contains_key(&Name::new("bar")));
+
+  let mut disjoint = parse("bar = 3\ntype Shape = (Square side) | (Circle radius)");
+  disjoint.entrypoint = Some(Name::new("bar"));
+  book.merge(disjoint).unwrap();
+  assert!(book.defs.contains_key(&Name::new("foo")) && book.defs.contains_key(&Name::new("bar")));
+  assert_eq!(book.ctrs[&Name::new("Shape/Square")], Name::new("Shape"));
+  assert_eq!(book.entrypoint, Some(Name::new("bar")));
+  assert_eq!(book.defs.values().filter(|def| !def.builtin).count(), 3);
+}
@@ -8,11 +8,14 @@ use hvm::hvm::Numb;
 use std::collections::{BTreeSet, HashMap, HashSet};
 
 /// Converts an Interaction-INet to a Lambda Calculus term
+///
+/// The subterms nested deeper than `depth_limit` are not read, becoming errors.
 pub fn net_to_term(
   net: &INet,
   book: &Book,
   labels: &Labels,
   linear: bool,
+  depth_limit: Option<usize>,
   diagnostics: &mut Diagnostics,
 ) -> Term {
   let mut reader = Reader {
@@ -25,6 +28,8 @@ pub fn net_to_term(
     namegen: Default::default(),
     seen: Default::default(),
     errors: Default::default(),
+    depth: 0,
+    depth_limit: depth_limit.unwrap_or(usize::MAX),
   };
 
   let mut term = reader.read_term(net.enter_port(ROOT));
@@ -72,10 +77,24 @@ pub struct Reader<'a> {
   seen_fans: Scope,
   seen: HashSet<Port>,
   errors: Vec<ReadbackError>,
+  /// How many terms are being read around the current one.
+  depth: usize,
+  depth_limit: usize,
 }
 
 impl Reader<'_> {
   fn read_term(&mut self, next: Port) -> Term {
+    if self.depth >= self.depth_limit {
+      self.error(ReadbackError::TooDeep(self.depth_limit));
+      return Term::Err;
+    }
+    self.depth += 1;
+    let term = self.read_node(next);
+    self.depth -= 1;
+    term
+  }
+
+  fn read_node(&mut self, next: Port) -> Term {
     use CtrKind::*;
 
     maybe_grow(|| {
@@ -558,6 +577,7 @@ pub enum ReadbackError {
   InvalidNumericOp,
   ReachedRoot,
   Cyclic,
+  TooDeep(usize),
 }
 
 impl PartialEq for ReadbackError {
@@ -585,6 +605,12 @@ impl std::fmt::Display for ReadbackError {
       ReadbackError::Cyclic => {
         write!(f, "Unable to interpret the HVM result as a valid Bend term. (Cyclic Term)")
       }
+      ReadbackError::TooDeep(limit) => {
+        write!(
+          f,
+          "The result is nested more than {limit} levels deep, so its deeper parts were not read back."
+        )
+      }
     }
   }
 }
@@ -1,27 +1,34 @@
 use crate::{
+  diagnostics::{Diagnostics, TextLocation, TextSpan, WarningType},
   fun::{
-    display::DisplayFn, Adt, Book, CtrField, Definition, FanKind, MatchRule, Name, Num, Op, Pattern, Rule,
-    Tag, Term, STRINGS,
+    builtins, display::DisplayFn, Adt, Book, CtrField, Definition, FanKind, MatchRule, Name, Num, Op,
+    Pattern, Rule, SourceItem, Tag, Term, STRINGS,
   },
   imp::parser::PyParser,
   maybe_grow,
 };
 use highlight_error::highlight_error;
+use itertools::Itertools;
+use std::collections::HashMap;
 use TSPL::Parser;
 
 // Bend grammar description:
-// <Book>       ::= (<Data> | <Rule>)*
+// <Book>       ::= (<Data> | <Rule> | <InfixDecl>)*
+// <InfixDecl>  ::= ("infixl" | "infixr" | "infix") <Number> <InfixOp> "=" <Name>
+// <Attribute>  ::= ("#[entrypoint]" | "# allow(" <Warning> ("," <Warning>)* ")")
+//                  (on its own line, right before a <Rule>)
 // <ADT>        ::= "type" <Name> "=" ( <Name> | "(" <Name> (<Name>)* ")" )+
 // <Rule>       ::= ("(" <Name> <Pattern>* ")" | <Name> <Pattern>*) "=" <Term>
 // <Pattern>    ::= "(" <Name> <Pattern>* ")" | <NameEra> | <Number> | "(" <Pattern> ("," <Pattern>)+ ")"
 // <Term>       ::=
-//   <Number> | <NumOp> | <Tup> | <App> | <Group> | <Nat> | <Lam> | <UnscopedLam> | <Bend> | <Fold> |
+//   <Number> | <NumOp> | <Tup> | <App> | <Infix> | <Group> | <Nat> | <Lam> | <UnscopedLam> | <Bend> | <Fold> |
 //   <Use> | <Dup> | <LetTup> | <Let> | <With> | <Match> | <Switch> | <Era> | <UnscopedVar> | <Var>
 // <Lam>        ::= <Tag>? ("λ"|"@") <NameEra> <Term>
 // <UnscopedLam>::= <Tag>? ("λ"|"@") "$" <Name> <Term>
 // <NumOp>      ::= "(" <Operator> <Term> <Term> ")"
 // <Tup>        ::= "(" <Term> ("," <Term>)+ ")"
-// <App>        ::= <Tag>? "(" <Term> (<Term>)+ ")"
+// <App>        ::= <Tag>? "(" (<Term> | <InfixOp>) (<Term>)+ ")"
+// <Infix>      ::= <Tag>? "(" <Term>+ (<InfixOp> <Term>+)+ ")"
 // <Group>      ::= "(" <Term> ")"
 // <Use>        ::= "use" <Name> "=" <Term> ";"? <Term>
 // <Let>        ::= "let" <NameEra> "=" <Term> ";"? <Term>
@@ -30,15 +37,16 @@ use TSPL::Parser;
 // <LetTup>     ::= "let" "(" <NameEra> ("," <NameEra>)+ ")" "=" <Term> ";"? <Term>
 // <Dup>        ::= "let" <Tag>? "{" <NameEra> (","? <NameEra>)+ "}" "=" <Term> ";"? <Term>
 // <List>       ::= "[" (<Term> ","?)* "]"
-// <String>     ::= "\"" (escape sequence | [^"])* "\""
+// <String>     ::= "\"" (escape sequence | [^"])* "\"" | "\"\"\"" (any text without "\"\"\"") "\"\"\""
 // <Char>       ::= "'" (escape sequence | [^']) "'"
-// <Match>      ::= "match" <MatchArg> <WithClause>? "{" <MatchArm>+ "}"
+// <Match>      ::= "match" <MatchArg> <WithClause>? "{" (<MatchArm>+ | <StrMatchArms>) "}"
 // <Fold>       ::= "fold" <MatchArg> <WithClause>? "{" <MatchArm>+ "}"
 // <MatchArg>   ::= (<Name> "=" <Term>) | <Term>
 // <WithClause> ::= "with" (<Name> ("=" <Term>)? ","?)+
 // <MatchArm>   ::= "|"? <Pattern> ":" <Term> ";"?
+// <StrMatchArms> ::= ("|"? <String> ":" <Term> ";"?)+ "|"? <NameEra> ":" <Term> ";"?
 // <Switch>     ::= "switch" <MatchArg> <WithClause>? "{" <SwitchArm>+ "}"
-// <SwitchArm>  ::= "|"? (<Num>|"_") ":" <Term> ";"?
+// <SwitchArm>  ::= "|"? (<Num>|"_" <Name>?) ":" <Term> ";"?
 // <Bend>       ::= "bend" (<MatchArg> ","?)+ "{" "when" <Term> ":" <Term> "else" ":" <Term> "}"
 // <Var>        ::= <Name>
 // <UnscopedVar>::= "$" <Name>
@@ -46,19 +54,93 @@ use TSPL::Parser;
 // <Era>        ::= "*"
 // <Tag>        ::= "#" <Name>
 // <Name>       ::= [_\-./a-zA-Z0-9]+
-// <Number>     ::= ([0-9]+ | "0x"[0-9a-fA-F]+ | "0b"[01]+)
+// <Number>     ::= ([0-9_]+ | "0x"[0-9a-fA-F_]+ | "0b"[01_]+)
 // <Operator>   ::= ( "+" | "-" | "*" | "/" | "%" | "==" | "!=" | "<<" | ">>" | "<" | ">" | "&" | "|" | "^" | "**" )
+// <InfixOp>    ::= [!%&*+\-/<=>?^|~:.]+ (declared with <InfixDecl>, and not an <Operator>)
 
 pub type ParseResult<T> = std::result::Result<T, String>;
 
+/// Marks the definition that follows it as the entrypoint of the program.
+pub const ENTRYPOINT_ATTRIBUTE: &str = "#[entrypoint]";
+
+/// Allows the given warnings in the definition that follows it, like `# allow(unused-definition)`.
+pub const ALLOW_ATTRIBUTE: &str = "# allow(";
+
+/// Opens and closes a raw string literal.
+const RAW_STR_DELIM: &str = "\"\"\"";
+
+/// The characters that infix operators declared by the user are made of.
+pub(crate) const INFIX_OPER_CHARS: &str = "!%&*+-/<=>?^|~:.";
+
+/// The operators that can't be declared as infix operators, since they already mean something.
+const RESERVED_OPERS: &[&str] =
+  &["+", "-", "*", "/", "%", "==", "!=", "<<", ">>", "<", ">", "&", "|", "^", "**", "="];
+
+/// An error found while parsing, with the span of the code it's about.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+  /// The error message, followed by the highlighted code.
+  pub message: String,
+  pub span: TextSpan,
+  /// What the parser expected to find instead, if the error is about an unexpected token.
+  pub expected: Option<String>,
+}
+
+impl std::fmt::Display for ParseError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "{}", self.message)
+  }
+}
+
+impl From<ParseError> for Diagnostics {
+  fn from(err: ParseError) -> Self {
+    let mut diags = Diagnostics::default();
+    diags.add_source_error(err.message, err.span);
+    diags
+  }
+}
+
 pub struct TermParser<'i> {
   input: &'i str,
   index: usize,
+  /// The span of the last error, as byte offsets, and what was expected there.
+  err: Option<(usize, usize, Option<String>)>,
+  /// The infix operators declared so far, which can be used by the terms that come after them.
+  infix_opers: HashMap<String, InfixOper>,
+  /// The location of the end of the last definition, from where the location of the next one is found.
+  last_location: TextLocation,
+}
+
+/// An infix operator declared with `infixl`, `infixr` or `infix`, like `infixl 6 <+> = Vec/add`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct InfixOper {
+  /// The function that the operator is applied with, like `(Vec/add a b)` for `(a <+> b)`.
+  fun: Name,
+  /// Operators with a higher precedence are grouped first.
+  prec: u32,
+  assoc: Assoc,
+}
+
+/// How a chain of operators with the same precedence is grouped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Assoc {
+  /// `(a + b + c)` is `((a + b) + c)`.
+  Left,
+  /// `(a + b + c)` is `(a + (b + c))`.
+  Right,
+  /// `(a + b + c)` is an error.
+  NonAssoc,
 }
 
 impl<'a> TermParser<'a> {
   pub fn new(input: &'a str) -> Self {
-    Self { input, index: 0 }
+    Self {
+      input,
+      index: 0,
+      err: None,
+      infix_opers: HashMap::new(),
+      last_location: TextLocation { byte: 0, line: 1, column: 1 },
+    }
   }
 
   /* AST parsing functions */
@@ -68,88 +150,305 @@ impl<'a> TermParser<'a> {
     let mut indent = self.advance_newlines();
     let mut last_rule = None;
     while !self.is_eof() {
-      let ini_idx = *self.index();
+      self.parse_top_level(&mut book, &mut indent, &mut last_rule, builtin)?;
+    }
+    Ok(book)
+  }
 
-      // Record type definition
-      if self.try_parse_keyword("object") {
-        let mut prs = PyParser { input: self.input, index: *self.index() };
-        let (obj, nxt_indent) = prs.parse_object(indent)?;
-        self.index = prs.index;
-        let end_idx = *self.index();
-        prs.add_object(obj, &mut book, ini_idx, end_idx, builtin)?;
-        indent = nxt_indent;
-        last_rule = None;
-        continue;
+  /// Parses the top-level definitions one at a time, yielding each function as soon as it's complete.
+  ///
+  /// Types are still added to `default_book`, since they're needed to parse the functions that follow,
+  /// but the rules of the yielded functions are not kept, so the memory used doesn't grow with the file.
+  /// Stops after the first error.
+  pub fn parse_definitions(&mut self, default_book: Book, builtin: bool) -> ParseDefinitions<'_, 'a> {
+    let indent = self.advance_newlines();
+    ParseDefinitions {
+      parser: self,
+      book: default_book,
+      builtin,
+      indent,
+      last_rule: None,
+      pending: None,
+      error: None,
+      done: false,
+    }
+  }
+
+  /// Parses a single top-level item and adds it to `book`.
+  /// Returns the name of the function it defined or added a rule to.
+  fn parse_top_level(
+    &mut self,
+    book: &mut Book,
+    indent: &mut Indent,
+    last_rule: &mut Option<Name>,
+    builtin: bool,
+  ) -> ParseResult<Option<Name>> {
+    let ini_idx = *self.index();
+    let is_entrypoint = self.has_entrypoint_attribute(ini_idx);
+    let allowed = self.allow_attributes(ini_idx);
+
+    // Record type definition
+    if self.try_parse_keyword("object") {
+      let mut prs = PyParser { input: self.input, index: *self.index() };
+      let (obj, nxt_indent) = prs.parse_object(*indent)?;
+      self.index = prs.index;
+      let end_idx = *self.index();
+      if is_entrypoint {
+        self.set_marked_entrypoint(book, None, ini_idx, end_idx)?;
+      }
+      self.set_allowed_warnings(book, None, allowed, ini_idx, end_idx)?;
+      let name = obj.name.clone();
+      prs.add_object(obj, book, ini_idx, end_idx, builtin)?;
+      if !builtin {
+        book.source_order.push(SourceItem::Adt(name));
+      }
+      *indent = nxt_indent;
+      *last_rule = None;
+      return Ok(None);
+    }
+
+    // Imp function definition
+    if self.try_parse_keyword("def") {
+      let mut prs = PyParser { input: self.input, index: *self.index() };
+      let (def, nxt_indent) = prs.parse_def(*indent)?;
+      self.index = prs.index;
+      let end_idx = *self.index();
+      if is_entrypoint {
+        self.set_marked_entrypoint(book, Some(&def.name), ini_idx, end_idx)?;
       }
+      self.set_allowed_warnings(book, Some(&def.name), allowed, ini_idx, end_idx)?;
+      let name = def.name.clone();
+      prs.add_def(def, book, ini_idx, end_idx, builtin)?;
+      if !builtin {
+        self.set_def_span(book, &name, ini_idx, end_idx);
+        book.source_order.push(SourceItem::Def(name.clone()));
+      }
+      *indent = nxt_indent;
+      *last_rule = None;
+      return Ok(Some(name));
+    }
+
+    // Fun/Imp type definition
+    if self.try_parse_keyword("type") {
+      self.skip_trivia();
+      let rewind_index = self.index;
 
-      // Imp function definition
-      if self.try_parse_keyword("def") {
-        let mut prs = PyParser { input: self.input, index: *self.index() };
-        let (def, nxt_indent) = prs.parse_def(indent)?;
+      let _ = self.labelled(|p| p.parse_top_level_name(), "datatype name")?;
+
+      if self.starts_with(":") {
+        let mut prs = PyParser { input: self.input, index: rewind_index };
+        let (r#enum, nxt_indent) = prs.parse_type(*indent)?;
         self.index = prs.index;
         let end_idx = *self.index();
-        prs.add_def(def, &mut book, ini_idx, end_idx, builtin)?;
-        indent = nxt_indent;
-        last_rule = None;
-        continue;
+        if is_entrypoint {
+          self.set_marked_entrypoint(book, None, ini_idx, end_idx)?;
+        }
+        self.set_allowed_warnings(book, None, allowed, ini_idx, end_idx)?;
+        let name = r#enum.name.clone();
+        prs.add_type(r#enum, book, ini_idx, end_idx, builtin)?;
+        if !builtin {
+          book.source_order.push(SourceItem::Adt(name));
+        }
+        *indent = nxt_indent;
+      } else {
+        self.index = rewind_index;
+        let (nam, adt) = self.parse_datatype(builtin)?;
+        let end_idx = *self.index();
+        if is_entrypoint {
+          self.set_marked_entrypoint(book, None, ini_idx, end_idx)?;
+        }
+        self.set_allowed_warnings(book, None, allowed, ini_idx, end_idx)?;
+        if !builtin {
+          book.source_order.push(SourceItem::Adt(nam.clone()));
+        }
+        self.with_ctx(book.add_adt(nam, adt), ini_idx, end_idx)?;
+        *indent = self.advance_newlines();
       }
+      *last_rule = None;
+      return Ok(None);
+    }
 
-      // Fun/Imp type definition
-      if self.try_parse_keyword("type") {
-        self.skip_trivia();
-        let rewind_index = self.index;
-
-        let _ = self.labelled(|p| p.parse_top_level_name(), "datatype name")?;
-
-        if self.starts_with(":") {
-          let mut prs = PyParser { input: self.input, index: rewind_index };
-          let (r#enum, nxt_indent) = prs.parse_type(indent)?;
-          self.index = prs.index;
-          let end_idx = *self.index();
-          prs.add_type(r#enum, &mut book, ini_idx, end_idx, builtin)?;
-          indent = nxt_indent;
-          last_rule = None;
-          continue;
-        } else {
-          self.index = rewind_index;
-          let (nam, adt) = self.parse_datatype(builtin)?;
-          let end_idx = *self.index();
-          self.with_ctx(book.add_adt(nam, adt), ini_idx, end_idx)?;
-          indent = self.advance_newlines();
-          last_rule = None;
-          continue;
-        }
+    // Infix operator declaration
+    if self.parse_infix_decl()? {
+      *indent = self.advance_newlines();
+      *last_rule = None;
+      return Ok(None);
+    }
+
+    // Fun function definition
+    let ini_idx = *self.index();
+    let (name, rule) = self.parse_rule()?;
+    let end_idx = *self.index();
+    if is_entrypoint {
+      self.set_marked_entrypoint(book, Some(&name), ini_idx, end_idx)?;
+    }
+    self.set_allowed_warnings(book, Some(&name), allowed, ini_idx, end_idx)?;
+    // Add to book
+    if let Some(def) = book.defs.get_mut(&name) {
+      if last_rule.as_ref() == Some(&name) {
+        // Continuing with a new rule to the current definition
+        def.rules.push(rule);
+      } else {
+        // Trying to add a new rule to a previous definition,
+        // coming from a different rule or another kind of top-level.
+        let msg = format!("Redefinition of function '{name}'");
+        return self.with_ctx(Err(msg), ini_idx, end_idx);
+      }
+    } else {
+      // Adding the first rule of a new definition
+      book.defs.insert(name.clone(), Definition { name: name.clone(), rules: vec![rule], builtin });
+      if !builtin {
+        book.source_order.push(SourceItem::Def(name.clone()));
       }
+    }
+    if !builtin {
+      self.set_def_span(book, &name, ini_idx, end_idx);
+    }
+    *indent = self.advance_newlines();
+    *last_rule = Some(name.clone());
+    Ok(Some(name))
+  }
 
-      // Fun function definition
-      let ini_idx = *self.index();
-      let (name, rule) = self.parse_rule()?;
-      let end_idx = *self.index();
-      // Add to book
-      if let Some(def) = book.defs.get_mut(&name) {
-        if let Some(last_rule) = last_rule {
-          if last_rule == name {
-            // Continuing with a new rule to the current definition
-            def.rules.push(rule);
-          } else {
-            // Trying to add a new rule to a previous definition, coming from a different rule.
-            let msg = format!("Redefinition of function '{name}'");
-            return self.with_ctx(Err(msg), ini_idx, end_idx);
+  /// Parses a declaration of an infix operator, like `infixl 6 <+> = Vec/add`, if there's one.
+  ///
+  /// A rule of a function named `infixl`, `infixr` or `infix`, like `infix 0 = 1`, is not a declaration.
+  fn parse_infix_decl(&mut self) -> ParseResult<bool> {
+    let ini_idx = *self.index();
+    let assoc = if self.try_parse_keyword("infixl") {
+      Assoc::Left
+    } else if self.try_parse_keyword("infixr") {
+      Assoc::Right
+    } else if self.try_parse_keyword("infix") {
+      Assoc::NonAssoc
+    } else {
+      return Ok(false);
+    };
+    self.skip_trivia();
+    if !self.peek_one().map_or(false, |c| c.is_ascii_digit()) {
+      self.index = ini_idx;
+      return Ok(false);
+    }
+    let prec = self.parse_u32()?;
+    self.skip_trivia();
+    let oper = self.peek_infix_oper();
+    if oper.is_empty() || oper == "=" {
+      self.index = ini_idx;
+      return Ok(false);
+    }
+    let oper_idx = *self.index();
+    self.consume_exactly(oper)?;
+    let oper_end_idx = *self.index();
+    self.consume("=")?;
+    self.skip_trivia();
+    let fun = self.parse_bend_name()?;
+    let end_idx = *self.index();
+
+    if RESERVED_OPERS.contains(&oper) {
+      let msg = format!("Can't declare '{oper}' as an infix operator, since it's a built-in operator.");
+      return self.with_ctx(Err(msg), oper_idx, oper_end_idx);
+    }
+    let decl = InfixOper { fun, prec, assoc };
+    match self.infix_opers.get(oper) {
+      Some(prev) if *prev != decl => {
+        let msg = format!("Conflicting declarations of the infix operator '{oper}'.");
+        self.with_ctx(Err(msg), ini_idx, end_idx)
+      }
+      _ => {
+        self.infix_opers.insert(oper.to_string(), decl);
+        Ok(true)
+      }
+    }
+  }
+
+  /// Checks if the comments right before `idx` have an `#[entrypoint]` attribute.
+  fn has_entrypoint_attribute(&self, idx: usize) -> bool {
+    for line in self.input[..idx].lines().rev() {
+      let line = line.trim();
+      if line == ENTRYPOINT_ATTRIBUTE {
+        return true;
+      }
+      if !line.is_empty() && !line.starts_with('#') {
+        break;
+      }
+    }
+    false
+  }
+
+  /// Collects the warnings allowed by the `# allow(...)` attributes in the comments right before `idx`.
+  fn allow_attributes(&self, idx: usize) -> Result<Vec<WarningType>, String> {
+    let mut allowed = vec![];
+    for line in self.input[..idx].lines().rev() {
+      let line = line.trim();
+      if let Some(warnings) = line.strip_prefix(ALLOW_ATTRIBUTE).and_then(|line| line.strip_suffix(')')) {
+        for warning in warnings.split(',').map(str::trim) {
+          match WarningType::from_name(warning) {
+            Some(warning) => allowed.push(warning),
+            None => return Err(format!("Unknown warning '{warning}' in '{line}'.")),
           }
-        } else {
-          // Trying to add a new rule to a previous definition, coming from another kind of top-level.
-          let msg = format!("Redefinition of function '{name}'");
-          return self.with_ctx(Err(msg), ini_idx, end_idx);
         }
-      } else {
-        // Adding the first rule of a new definition
-        book.defs.insert(name.clone(), Definition { name: name.clone(), rules: vec![rule], builtin });
+      } else if !line.is_empty() && !line.starts_with('#') {
+        break;
       }
-      indent = self.advance_newlines();
-      last_rule = Some(name);
     }
+    Ok(allowed)
+  }
 
-    Ok(book)
+  /// Allows the warnings of the `# allow(...)` attributes in the definition that follows them.
+  /// `name` is `None` if the attributes were put on something that isn't a function.
+  fn set_allowed_warnings(
+    &mut self,
+    book: &mut Book,
+    name: Option<&Name>,
+    allowed: Result<Vec<WarningType>, String>,
+    ini_idx: usize,
+    end_idx: usize,
+  ) -> ParseResult<()> {
+    let res = match (name, allowed) {
+      (_, Ok(allowed)) if allowed.is_empty() => Ok(()),
+      (_, Err(err)) => Err(err),
+      (None, Ok(_)) => Err(format!("Only function definitions can be marked with '{ALLOW_ATTRIBUTE}...)'.")),
+      (Some(name), Ok(allowed)) => {
+        book.allowed_warnings.entry(name.clone()).or_default().extend(allowed);
+        Ok(())
+      }
+    };
+    self.with_ctx(res, ini_idx, end_idx)
+  }
+
+  /// Records the region of the source code of a definition, without its trailing whitespace.
+  /// The rules of a definition after its first one extend its region.
+  fn set_def_span(&mut self, book: &mut Book, name: &Name, ini_idx: usize, end_idx: usize) {
+    let end_idx = ini_idx + self.input[ini_idx..end_idx].trim_end().len();
+    // The definitions are parsed in order, so finding each location from the last one
+    // only goes through the source code once.
+    let ini = match book.def_spans.get(name) {
+      Some(span) => span.ini,
+      None => self.last_location.advance_to(self.input, ini_idx),
+    };
+    let from = if ini.byte > self.last_location.byte { ini } else { self.last_location };
+    let end = from.advance_to(self.input, end_idx);
+    self.last_location = end;
+    book.def_spans.insert(name.clone(), TextSpan { ini, end });
+  }
+
+  /// Makes the definition marked with `#[entrypoint]` the entrypoint of the book.
+  /// `name` is `None` if the attribute was put on something that isn't a function.
+  fn set_marked_entrypoint(
+    &mut self,
+    book: &mut Book,
+    name: Option<&Name>,
+    ini_idx: usize,
+    end_idx: usize,
+  ) -> ParseResult<()> {
+    let res = match (name, &book.entrypoint) {
+      (None, _) => Err(format!("Only function definitions can be marked with '{ENTRYPOINT_ATTRIBUTE}'.")),
+      (Some(name), Some(prev)) => Err(format!("Both '{prev}' and '{name}' are marked as the entrypoint.")),
+      (Some(name), None) => {
+        book.entrypoint = Some(name.clone());
+        Ok(())
+      }
+    };
+    self.with_ctx(res, ini_idx, end_idx)
   }
 
   fn parse_datatype(&mut self, builtin: bool) -> ParseResult<(Name, Adt)> {
@@ -210,7 +509,7 @@ impl<'a> TermParser<'a> {
       (name, pats)
     };
 
-    let body = self.parse_term()?;
+    let body = self.parse_term_inner()?;
 
     let rule = Rule { pats, body };
     Ok((name, rule))
@@ -262,7 +561,7 @@ impl<'a> TermParser<'a> {
       // String
       if self.starts_with("\"") && !simple {
         unexpected_tag(self)?;
-        let str = self.parse_quoted_string()?;
+        let str = self.parse_string()?;
         return Ok(Pattern::Str(STRINGS.get(str)));
       }
 
@@ -296,7 +595,25 @@ impl<'a> TermParser<'a> {
     })
   }
 
-  pub fn parse_term(&mut self) -> ParseResult<Term> {
+  /// Parses a single term, starting at the current position.
+  ///
+  /// ```
+  /// use bend::fun::parser::TermParser;
+  ///
+  /// let err = TermParser::new("(f x").parse_term().unwrap_err();
+  /// assert_eq!(err.span.ini.column, 5);
+  /// assert_eq!(err.expected.as_deref(), Some("term"));
+  /// ```
+  pub fn parse_term(&mut self) -> Result<Term, ParseError> {
+    self.err = None;
+    self.parse_term_inner().map_err(|message| {
+      // Errors that weren't spanned point to where the parser stopped.
+      let (ini_idx, end_idx, expected) = self.err.take().unwrap_or((self.index, self.index + 1, None));
+      ParseError { message, span: TextSpan::from_byte_span(self.input, ini_idx, end_idx), expected }
+    })
+  }
+
+  fn parse_term_inner(&mut self) -> ParseResult<Term> {
     maybe_grow(|| {
       let (tag, unexpected_tag) = self.parse_tag()?;
       self.skip_trivia();
@@ -306,16 +623,28 @@ impl<'a> TermParser<'a> {
         self.advance_one();
         let tag = tag.unwrap_or(Tag::Static);
         let pat = self.parse_pattern(true)?;
-        let bod = self.parse_term()?;
+        let bod = self.parse_term_inner()?;
         return Ok(Term::Lam { tag, pat: Box::new(pat), bod: Box::new(bod) });
       }
 
-      // App, Tup, Num Op
+      // App, Tup, Num Op, Infix Op
       if self.starts_with("(") {
         self.advance_one();
 
-        // Opr but maybe a tup
+        // Declared infix operator used as a function, like `(<+> a b)`
         self.skip_trivia();
+        if let Some(oper) = self.infix_opers.get(self.peek_infix_oper()).cloned() {
+          self.consume_exactly(self.peek_infix_oper())?;
+          let args = self.list_like(|p| p.parse_term_inner(), "", ")", "", false, 1)?;
+          let tag = tag.unwrap_or(Tag::Static);
+          return Ok(args.into_iter().fold(Term::Var { nam: oper.fun }, |fun, arg| Term::App {
+            tag: tag.clone(),
+            fun: Box::new(fun),
+            arg: Box::new(arg),
+          }));
+        }
+
+        // Opr but maybe a tup
         let starts_with_oper = self.peek_one().map_or(false, |c| "+-*/%&|<>^=!".contains(c));
         if starts_with_oper {
           let opr = self.parse_oper()?;
@@ -325,7 +654,7 @@ impl<'a> TermParser<'a> {
           if self.starts_with(",") && opr == Op::MUL {
             let mut els = vec![Term::Era];
             while self.try_consume(",") {
-              els.push(self.parse_term()?);
+              els.push(self.parse_term_inner()?);
             }
             self.consume(")")?;
             return Ok(Term::Fan { fan: FanKind::Tup, tag: tag.unwrap_or(Tag::Static), els });
@@ -333,46 +662,65 @@ impl<'a> TermParser<'a> {
 
           // Opr
           unexpected_tag(self)?;
-          let fst = self.parse_term()?;
-          let snd = self.parse_term()?;
+          let fst = self.parse_term_inner()?;
+          let snd = self.parse_term_inner()?;
           self.consume(")")?;
           return Ok(Term::Oper { opr, fst: Box::new(fst), snd: Box::new(snd) });
         }
 
         // Tup or App
-        let head = self.parse_term()?;
+        let head = self.parse_term_inner()?;
 
         // Tup
         self.skip_trivia();
         if self.starts_with(",") {
           let mut els = vec![head];
           while self.try_consume(",") {
-            els.push(self.parse_term()?);
+            els.push(self.parse_term_inner()?);
           }
           self.consume(")")?;
           return Ok(Term::Fan { fan: FanKind::Tup, tag: tag.unwrap_or(Tag::Static), els });
         }
 
-        // App
-        let els = self.list_like(|p| p.parse_term(), "", ")", "", false, 0)?;
-        let term = els.into_iter().fold(head, |fun, arg| Term::App {
-          tag: tag.clone().unwrap_or(Tag::Static),
-          fun: Box::new(fun),
-          arg: Box::new(arg),
+        // App, with infix operators between the applications
+        let tag = tag.unwrap_or(Tag::Static);
+        let mut operands = vec![vec![head]];
+        let mut opers = vec![];
+        while !self.try_consume(")") {
+          let oper = self.peek_infix_oper();
+          if let Some(decl) = self.infix_opers.get(oper).cloned() {
+            let ini_idx = *self.index();
+            self.consume_exactly(oper)?;
+            let end_idx = *self.index();
+            self.skip_trivia();
+            if self.starts_with(")") || self.infix_opers.contains_key(self.peek_infix_oper()) {
+              let msg = format!("Expected a term after the infix operator '{oper}'.");
+              return self.with_ctx(Err(msg), ini_idx, end_idx);
+            }
+            opers.push((oper, decl, ini_idx, end_idx));
+            operands.push(vec![]);
+          } else {
+            operands.last_mut().unwrap().push(self.parse_term_inner()?);
+          }
+        }
+        let operands = operands.into_iter().map(|els| {
+          let mut els = els.into_iter();
+          let head = els.next().unwrap();
+          els.fold(head, |fun, arg| Term::App { tag: tag.clone(), fun: Box::new(fun), arg: Box::new(arg) })
         });
-        return Ok(term);
+        return self.resolve_infix_opers(operands.collect(), opers, tag);
       }
 
       // List
       if self.starts_with("[") {
         unexpected_tag(self)?;
-        let els = self.list_like(|p| p.parse_term(), "[", "]", ",", false, 0)?;
+        let els = self.list_like(|p| p.parse_term_inner(), "[", "]", ",", false, 0)?;
         return Ok(Term::List { els });
       }
 
       // Sup
       if self.starts_with("{") {
-        let els = self.list_like(|p| p.parse_term(), "{", "}", ",", false, 2)?;
+        let els = self.list_like(|p| p.parse_term_inner(), "{", "}", ",", false, 2)?;
         return Ok(Term::Fan { fan: FanKind::Dup, tag: tag.unwrap_or(Tag::Auto), els });
       }
 
@@ -403,7 +751,7 @@ impl<'a> TermParser<'a> {
       // String
       if self.starts_with("\"") {
         unexpected_tag(self)?;
-        let str = self.parse_quoted_string()?;
+        let str = self.parse_string()?;
         return Ok(Term::Str { val: STRINGS.get(str) });
       }
 
@@ -434,9 +782,9 @@ impl<'a> TermParser<'a> {
         self.skip_trivia();
         let nam = self.parse_bend_name()?;
         self.consume("=")?;
-        let val = self.parse_term()?;
+        let val = self.parse_term_inner()?;
         self.try_consume(";");
-        let nxt = self.parse_term()?;
+        let nxt = self.parse_term_inner()?;
         return Ok(Term::Use { nam: Some(nam), val: Box::new(val), nxt: Box::new(nxt) });
       }
 
@@ -445,9 +793,9 @@ impl<'a> TermParser<'a> {
         unexpected_tag(self)?;
         let pat = self.parse_pattern(true)?;
         self.consume("=")?;
-        let val = self.parse_term()?;
+        let val = self.parse_term_inner()?;
         self.try_consume(";");
-        let nxt = self.parse_term()?;
+        let nxt = self.parse_term_inner()?;
         return Ok(Term::Let { pat: Box::new(pat), val: Box::new(val), nxt: Box::new(nxt) });
       }
 
@@ -456,21 +804,21 @@ impl<'a> TermParser<'a> {
         unexpected_tag(self)?;
         let pat = self.parse_pattern(true)?;
         self.consume("=")?;
-        let val = self.parse_term()?;
+        let val = self.parse_term_inner()?;
         self.try_consume(";");
-        let nxt = self.parse_term()?;
+        let nxt = self.parse_term_inner()?;
         return Ok(Term::Ask { pat: Box::new(pat), val: Box::new(val), nxt: Box::new(nxt) });
       }
 
       // If
       if self.try_parse_keyword("if") {
-        let cnd = self.parse_term()?;
+        let cnd = self.parse_term_inner()?;
         self.consume("{")?;
-        let thn = self.parse_term()?;
+        let thn = self.parse_term_inner()?;
         self.consume("}")?;
         self.consume("else")?;
         self.consume("{")?;
-        let els = self.parse_term()?;
+        let els = self.parse_term_inner()?;
         self.consume("}")?;
         return Ok(Term::Swt {
           arg: Box::new(cnd),
@@ -487,6 +835,18 @@ impl<'a> TermParser<'a> {
         unexpected_tag(self)?;
         let (bnd, arg) = self.parse_match_arg()?;
         let (with_bnd, with_arg) = self.parse_with_clause()?;
+        self.try_consume("|");
+        self.skip_trivia();
+        if self.starts_with("\"") {
+          let (arms, default) = self.parse_str_match_arms()?;
+          let mut term = desugar_str_match(bnd.unwrap(), &arms, default);
+          if let Term::Mat { arg: mat_arg, with_bnd: mat_with_bnd, with_arg: mat_with_arg, .. } = &mut term {
+            *mat_arg = Box::new(arg);
+            *mat_with_bnd = with_bnd;
+            *mat_with_arg = with_arg;
+          }
+          return Ok(term);
+        }
         let arms = self.list_like(|p| p.parse_match_arm(), "", "}", ";", false, 1)?;
         return Ok(Term::Mat { arg: Box::new(arg), bnd, with_bnd, with_arg, arms });
       }
@@ -500,17 +860,23 @@ impl<'a> TermParser<'a> {
         self.try_consume("|");
         self.consume("0")?;
         self.consume(":")?;
-        let zero = self.parse_term()?;
+        let zero = self.parse_term_inner()?;
         self.try_consume(";");
 
         let mut arms = vec![zero];
+        let mut pred = None;
         let mut expected_num = 1;
         loop {
           self.try_consume("|");
           // case _
           if self.try_consume("_") {
+            // The predecessor can be optionally named, otherwise it's `bnd-n`.
+            self.skip_trivia();
+            if !self.starts_with(":") {
+              pred = Some(self.parse_bend_name()?);
+            }
             self.consume(":")?;
-            arms.push(self.parse_term()?);
+            arms.push(self.parse_term_inner()?);
             self.try_consume(";");
             self.consume("}")?;
             break;
@@ -522,10 +888,10 @@ impl<'a> TermParser<'a> {
           }
           expected_num += 1;
           self.consume(":")?;
-          arms.push(self.parse_term()?);
+          arms.push(self.parse_term_inner()?);
           self.try_consume(";");
         }
-        let pred = Some(Name::new(format!("{}-{}", bnd.as_ref().unwrap(), arms.len() - 1)));
+        let pred = pred.or_else(|| Some(Name::new(format!("{}-{}", bnd.as_ref().unwrap(), arms.len() - 1))));
         return Ok(Term::Swt { arg: Box::new(arg), bnd, with_bnd, with_arg, pred, arms });
       }
 
@@ -534,7 +900,7 @@ impl<'a> TermParser<'a> {
         unexpected_tag(self)?;
         let typ = self.parse_name()?;
         self.consume("{")?;
-        let bod = self.parse_term()?;
+        let bod = self.parse_term_inner()?;
         self.consume("}")?;
         return Ok(Term::With { typ: Name::new(typ), bod: Box::new(bod) });
       }
@@ -554,7 +920,8 @@ impl<'a> TermParser<'a> {
         let args = self.list_like(
           |p| {
             let bind = p.parse_bend_name()?;
-            let init = if p.try_consume("=") { p.parse_term()? } else { Term::Var { nam: bind.clone() } };
+            let init =
+              if p.try_consume("=") { p.parse_term_inner()? } else { Term::Var { nam: bind.clone() } };
             Ok((bind, init))
           },
           "",
@@ -567,13 +934,13 @@ impl<'a> TermParser<'a> {
         let bind = bind.into_iter().map(Some).collect::<Vec<_>>();
         self.skip_trivia();
         self.parse_keyword("when")?;
-        let cond = self.parse_term()?;
+        let cond = self.parse_term_inner()?;
         self.consume(":")?;
-        let step = self.parse_term()?;
+        let step = self.parse_term_inner()?;
         self.skip_trivia();
         self.parse_keyword("else")?;
         self.consume(":")?;
-        let base = self.parse_term()?;
+        let base = self.parse_term_inner()?;
         self.consume("}")?;
         return Ok(Term::Bend {
           bnd: bind,
@@ -592,7 +959,7 @@ impl<'a> TermParser<'a> {
         self.skip_trivia();
         let var = self.parse_bend_name()?;
         self.try_consume(";");
-        let bod = self.parse_term()?;
+        let bod = self.parse_term_inner()?;
         return Ok(Term::Open { typ, var, bod: Box::new(bod) });
       }
 
@@ -603,6 +970,67 @@ impl<'a> TermParser<'a> {
     })
   }
 
+  /// Groups the operands of a chain of infix operators by the precedence and associativity of the operators,
+  /// turning each operator into an application of its function, like `(a <+> b)` into `(Vec/add a b)`.
+  ///
+  /// `opers` has the operator between each pair of operands, with its span.
+  fn resolve_infix_opers(
+    &mut self,
+    operands: Vec<Term>,
+    opers: Vec<(&str, InfixOper, usize, usize)>,
+    tag: Tag,
+  ) -> ParseResult<Term> {
+    fn apply(terms: &mut Vec<Term>, oper: InfixOper, tag: &Tag) {
+      let snd = terms.pop().unwrap();
+      let fst = terms.pop().unwrap();
+      let fun =
+        Term::App { tag: tag.clone(), fun: Box::new(Term::Var { nam: oper.fun }), arg: Box::new(fst) };
+      terms.push(Term::App { tag: tag.clone(), fun: Box::new(fun), arg: Box::new(snd) });
+    }
+
+    // Shunting-yard algorithm, where the operators wait on `pending` until an operator
+    // with a lower precedence comes after them.
+    let mut operands = operands.into_iter();
+    let mut terms = vec![operands.next().unwrap()];
+    let mut pending: Vec<(&str, InfixOper, usize, usize)> = vec![];
+    for ((oper, decl, ini_idx, end_idx), operand) in opers.into_iter().zip(operands) {
+      while let Some((top, top_decl, top_ini_idx, _)) = pending.last() {
+        if top_decl.prec < decl.prec {
+          break;
+        }
+        if top_decl.prec == decl.prec {
+          match (top_decl.assoc, decl.assoc) {
+            (Assoc::Left, Assoc::Left) => {}
+            (Assoc::Right, Assoc::Right) => break,
+            _ => {
+              let msg = format!(
+                "Ambiguous use of the infix operators '{top}' and '{oper}', which have the same precedence but aren't both 'infixl' or both 'infixr'. Add parentheses to group them."
+              );
+              let top_ini_idx = *top_ini_idx;
+              return self.with_ctx(Err(msg), top_ini_idx, end_idx);
+            }
+          }
+        }
+        let (_, top_decl, _, _) = pending.pop().unwrap();
+        apply(&mut terms, top_decl, &tag);
+      }
+      pending.push((oper, decl, ini_idx, end_idx));
+      terms.push(operand);
+    }
+    while let Some((_, decl, _, _)) = pending.pop() {
+      apply(&mut terms, decl, &tag);
+    }
+    Ok(terms.pop().unwrap())
+  }
+
+  /// The longest sequence of the characters of infix operators at the current position,
+  /// which may be empty.
+  fn peek_infix_oper(&self) -> &'a str {
+    let input = &self.input[self.index..];
+    let len = input.find(|c| !INFIX_OPER_CHARS.contains(c)).unwrap_or(input.len());
+    &input[..len]
+  }
+
   fn parse_name_or_era(&mut self) -> ParseResult<Option<Name>> {
     self.labelled(
       |p| {
@@ -646,14 +1074,14 @@ impl<'a> TermParser<'a> {
   // A named arg with optional name.
   fn parse_match_arg(&mut self) -> ParseResult<(Option<Name>, Term)> {
     let ini_idx = *self.index();
-    let mut arg = self.parse_term()?;
+    let mut arg = self.parse_term_inner()?;
     let end_idx = *self.index();
 
     self.skip_trivia();
     match (&mut arg, self.starts_with("=")) {
       (Term::Var { nam }, true) => {
         self.consume("=")?;
-        Ok((Some(std::mem::take(nam)), self.parse_term()?))
+        Ok((Some(std::mem::take(nam)), self.parse_term_inner()?))
       }
       (Term::Var { nam }, false) => Ok((Some(nam.clone()), Term::Var { nam: std::mem::take(nam) })),
       (_, true) => self.expected_spanned("argument name", ini_idx, end_idx),
@@ -667,7 +1095,7 @@ impl<'a> TermParser<'a> {
     self.skip_trivia();
     if self.starts_with("=") {
       self.advance_one();
-      let arg = self.parse_term()?;
+      let arg = self.parse_term_inner()?;
       Ok((Some(nam), arg))
     } else {
       let arg = Term::Var { nam: nam.clone() };
@@ -691,9 +1119,34 @@ impl<'a> TermParser<'a> {
     self.skip_trivia();
     let nam = self.parse_name_or_era()?;
     self.consume(":")?;
-    let bod = self.parse_term()?;
+    let bod = self.parse_term_inner()?;
     Ok((nam, vec![], bod))
   }
+
+  /// Parses the arms of a match on string literals, followed by the required default arm,
+  /// which binds a variable to the whole string.
+  fn parse_str_match_arms(&mut self) -> ParseResult<(Vec<(String, Term)>, (Option<Name>, Term))> {
+    let mut arms = vec![];
+    loop {
+      self.try_consume("|");
+      self.skip_trivia();
+      if self.starts_with("\"") {
+        let str = self.parse_string()?;
+        self.consume(":")?;
+        arms.push((str, self.parse_term_inner()?));
+        self.try_consume(";");
+      } else if self.starts_with("}") {
+        return self.expected("default arm after the string arms, like '_: ...'");
+      } else {
+        let nam = self.parse_name_or_era()?;
+        self.consume(":")?;
+        let bod = self.parse_term_inner()?;
+        self.try_consume(";");
+        self.consume("}")?;
+        return Ok((arms, (nam, bod)));
+      }
+    }
+  }
 }
 
 impl<'a> Parser<'a> for TermParser<'a> {
@@ -749,6 +1202,72 @@ impl<'a> Parser<'a> for TermParser<'a> {
   }
 }
 
+/// Desugars a match on string literals into matches on the characters of the matched string.
+///
+/// Each level matches the next character of the string, comparing it with the characters that
+/// the arms expect at that position, so arms that share a prefix are tested together.
+/// If no arm matches the whole string, the default arm is used, with its variable bound to the string.
+/// The first of the arms with the same string is the one used.
+///
+/// The returned term is a `match` on the variable `bnd`, where the argument of the match can be put.
+fn desugar_str_match(bnd: Name, arms: &[(String, Term)], default: (Option<Name>, Term)) -> Term {
+  let (default_nam, default) = default;
+  let default = match default_nam {
+    // Unused variables are not bound, so that they're not reported as unused binds of a 'use'.
+    Some(nam) if default.free_vars().contains_key(&nam) => {
+      Term::Use { nam: Some(nam), val: Box::new(Term::Var { nam: bnd.clone() }), nxt: Box::new(default) }
+    }
+    _ => default,
+  };
+  let arms = arms.iter().map(|(str, bod)| (str.chars().collect::<Vec<_>>(), bod)).collect::<Vec<_>>();
+  let arms = arms.iter().map(|(chars, bod)| (chars.as_slice(), *bod)).collect::<Vec<_>>();
+  str_match_chars(bnd, &arms, &default)
+}
+
+/// Matches the string in the variable `str` with the remaining characters of each arm.
+fn str_match_chars(str: Name, arms: &[(&[char], &Term)], default: &Term) -> Term {
+  maybe_grow(|| {
+    let nil = arms.iter().find(|(chars, _)| chars.is_empty()).map_or(default, |(_, bod)| *bod).clone();
+
+    let head = Name::new(format!("{str}.head"));
+    let tail = Name::new(format!("{str}.tail"));
+    let mut cons = default.clone();
+    let next_chars = arms.iter().filter_map(|(chars, _)| chars.first()).unique().collect::<Vec<_>>();
+    // Built from the last character, so that the characters are compared in the order of the arms.
+    for chr in next_chars.into_iter().rev() {
+      let rest = arms
+        .iter()
+        .filter(|(chars, _)| chars.first() == Some(chr))
+        .map(|(chars, bod)| (&chars[1..], *bod))
+        .collect::<Vec<_>>();
+      let is_chr = Term::Oper {
+        opr: Op::EQ,
+        fst: Box::new(Term::Var { nam: head.clone() }),
+        snd: Box::new(Term::Num { val: Num::U24(*chr as u32) }),
+      };
+      cons = Term::Swt {
+        arg: Box::new(is_chr),
+        bnd: Some(Name::new("%cond")),
+        with_bnd: Vec::new(),
+        with_arg: Vec::new(),
+        pred: Some(Name::new("%cond-1")),
+        arms: vec![cons, str_match_chars(tail.clone(), &rest, default)],
+      };
+    }
+
+    Term::Mat {
+      bnd: Some(str.clone()),
+      arg: Box::new(Term::Var { nam: str }),
+      with_bnd: Vec::new(),
+      with_arg: Vec::new(),
+      arms: vec![
+        (Some(Name::new(builtins::SNIL)), vec![], nil),
+        (Some(Name::new(builtins::SCONS)), vec![], cons),
+      ],
+    }
+  })
+}
+
 pub fn is_name_char(c: char) -> bool {
   c.is_ascii_alphanumeric() || c == '_' || c == '.' || c == '-' || c == '/'
 }
@@ -808,7 +1327,82 @@ impl Book {
   }
 }
 
-impl<'a> ParserCommons<'a> for TermParser<'a> {}
+/// Iterator over the function definitions of a file, created by [`TermParser::parse_definitions`].
+pub struct ParseDefinitions<'p, 'a> {
+  parser: &'p mut TermParser<'a>,
+  book: Book,
+  builtin: bool,
+  indent: Indent,
+  last_rule: Option<Name>,
+  /// The definition being parsed, which may still get more rules.
+  pending: Option<Name>,
+  /// An error found after the last definition, yielded after it.
+  error: Option<Diagnostics>,
+  done: bool,
+}
+
+impl ParseDefinitions<'_, '_> {
+  /// The types, and the names of the functions, parsed so far.
+  pub fn book(&self) -> &Book {
+    &self.book
+  }
+
+  /// Takes the rules of a finished definition out of the book.
+  /// An empty definition is left in its place to still detect redefinitions.
+  fn take_def(&mut self, name: Name) -> (Name, Definition) {
+    let def = self.book.defs.get_mut(&name).unwrap();
+    let rules = std::mem::take(&mut def.rules);
+    (name.clone(), Definition { name, rules, builtin: def.builtin })
+  }
+}
+
+impl Iterator for ParseDefinitions<'_, '_> {
+  type Item = Result<(Name, Definition), Diagnostics>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    if self.done {
+      return self.error.take().map(Err);
+    }
+    loop {
+      if self.parser.is_eof() {
+        self.done = true;
+        return self.pending.take().map(|name| Ok(self.take_def(name)));
+      }
+      match self.parser.parse_top_level(&mut self.book, &mut self.indent, &mut self.last_rule, self.builtin) {
+        // Another rule of the definition being parsed.
+        Ok(name) if name.is_some() && name == self.pending => {}
+        // A definition is complete once something else comes after it.
+        Ok(name) => {
+          if let Some(finished) = std::mem::replace(&mut self.pending, name) {
+            return Some(Ok(self.take_def(finished)));
+          }
+        }
+        Err(e) => {
+          self.done = true;
+          // The parser doesn't keep the span of the error, so we point to where it stopped.
+          let idx = *self.parser.index();
+          let span = TextSpan::from_byte_span(self.parser.input, idx, idx + 1);
+          let mut diags = Diagnostics::default();
+          diags.add_source_error(e, span);
+          // The definition before the error is already complete, so it's still yielded first.
+          return match self.pending.take() {
+            Some(finished) => {
+              self.error = Some(diags);
+              Some(Ok(self.take_def(finished)))
+            }
+            None => Some(Err(diags)),
+          };
+        }
+      }
+    }
+  }
+}
+
+impl<'a> ParserCommons<'a> for TermParser<'a> {
+  fn record_err(&mut self, ini_idx: usize, end_idx: usize, expected: Option<&str>) {
+    self.err = Some((ini_idx, end_idx, expected.map(str::to_string)));
+  }
+}
 
 pub trait ParserCommons<'a>: Parser<'a> {
   fn labelled<T>(&mut self, parser: impl Fn(&mut Self) -> ParseResult<T>, label: &str) -> ParseResult<T> {
@@ -913,9 +1507,15 @@ pub trait ParserCommons<'a>: Parser<'a> {
     let is_eof = self.is_eof();
     let detected = DisplayFn(|f| if is_eof { write!(f, " end of input") } else { Ok(()) });
     let msg = format!("\x1b[1m- expected:\x1b[0m {}\n\x1b[1m- detected:\x1b[0m{}", exp, detected);
-    self.with_ctx(Err(msg), ini_idx, end_idx)
+    let res = self.with_ctx(Err(msg), ini_idx, end_idx);
+    self.record_err(ini_idx, end_idx, Some(exp));
+    res
   }
 
+  /// Called with the span of each error and what was expected there, if anything,
+  /// so that parsers can report where the error they fail with is.
+  fn record_err(&mut self, _ini_idx: usize, _end_idx: usize, _expected: Option<&str>) {}
+
   fn with_ctx<T>(
     &mut self,
     res: Result<T, impl std::fmt::Display>,
@@ -923,6 +1523,7 @@ pub trait ParserCommons<'a>: Parser<'a> {
     end_idx: usize,
   ) -> ParseResult<T> {
     res.map_err(|msg| {
+      self.record_err(ini_idx, end_idx, None);
       let ctx = highlight_error(ini_idx, end_idx, self.input());
       format!("{msg}\n{ctx}")
     })
@@ -1097,6 +1698,19 @@ pub trait ParserCommons<'a>: Parser<'a> {
   }
 
   fn parse_u32(&mut self) -> ParseResult<u32> {
+    let ini_idx = *self.index();
+    let num = self.parse_digits()?;
+    match u32::try_from(num) {
+      Ok(num) => Ok(num),
+      Err(_) => self.num_range_err(ini_idx, "U32", "0 to 4294967295"),
+    }
+  }
+
+  /// Parses a natural number in decimal, hexadecimal (`0x`) or binary (`0b`), with optional `_` separators.
+  ///
+  /// Numbers that don't fit in 64 bits become `u64::MAX`,
+  /// since they're outside of the range of every numeric type anyway.
+  fn parse_digits(&mut self) -> ParseResult<u64> {
     let radix = match self.peek_many(2) {
       Some("0x") => {
         self.advance_many(2);
@@ -1121,7 +1735,7 @@ pub trait ParserCommons<'a>: Parser<'a> {
       };
       self.expected(format!("valid {base} digit").as_str())
     } else {
-      u32::from_str_radix(&num_str, radix).map_err(|e| e.to_string())
+      Ok(u64::from_str_radix(&num_str, radix).unwrap_or(u64::MAX))
     }
   }
 
@@ -1137,47 +1751,89 @@ pub trait ParserCommons<'a>: Parser<'a> {
       None
     };
 
+    // Infinities and NaNs, which need a sign to not be confused with names
+    if let Some(sgn) = sgn {
+      if self.try_consume_exactly("inf") {
+        return Ok(Num::F24(sgn as f32 * f32::INFINITY));
+      }
+      if self.try_consume_exactly("NaN") {
+        return Ok(Num::F24(f32::NAN));
+      }
+    }
+
     // Parses main value
-    let num = self.parse_u32()?;
+    let radix = match self.peek_many(2) {
+      Some("0x") => 16,
+      Some("0b") => 2,
+      _ => 10,
+    };
+    // The integer part of a decimal float can be too large for an u32, so it's kept as text.
+    let num_idx = *self.index();
+    let mut int = String::new();
+    if radix == 10 {
+      int = self.take_while(|c| c.is_ascii_digit() || c == '_').replace('_', "");
+      if int.is_empty() || self.peek_one() != Some('.') {
+        *self.index() = num_idx;
+        int.clear();
+      }
+    }
+    let num = if int.is_empty() { self.parse_digits()? } else { 0 };
 
-    // Parses frac value (Float type)
-    // TODO: Will lead to some rounding errors
-    // TODO: Doesn't cover very large/small numbers
-    let fra = if let Some('.') = self.peek_one() {
+    // Parses frac value and exponent (Float type)
+    if let Some('.') = self.peek_one() {
       self.advance_one();
-      let ini_idx = *self.index();
-      let fra = self.parse_u32()? as f32;
-      let end_idx = *self.index();
-      let fra = fra / 10f32.powi((end_idx - ini_idx) as i32);
-      Some(fra)
-    } else {
-      None
-    };
+      let fra = self.take_while(|c| c.is_digit(radix) || c == '_').replace('_', "");
+      if fra.is_empty() {
+        return self.expected("fractional digit");
+      }
+      // Decimal numbers have a decimal exponent, like `1.5e3`,
+      // and hexadecimal and binary ones a binary exponent, like `0x1.8p3`.
+      let exp_chars: &[char] = if radix == 10 { &['e', 'E'] } else { &['p', 'P'] };
+      let exp = if self.peek_one().is_some_and(|c| exp_chars.contains(&c)) {
+        self.advance_one();
+        let exp_sgn = if self.try_consume_exactly("-") {
+          -1
+        } else {
+          self.try_consume_exactly("+");
+          1
+        };
+        let exp = self.take_while(|c| c.is_ascii_digit());
+        match exp.parse::<i32>() {
+          Ok(exp) => exp_sgn * exp,
+          Err(_) => return self.expected("exponent digits"),
+        }
+      } else {
+        0
+      };
 
-    // F24
-    if let Some(fra) = fra {
-      let sgn = sgn.unwrap_or(1);
-      return Ok(Num::F24(sgn as f32 * (num as f32 + fra)));
+      let val = if radix == 10 {
+        format!("{int}.{fra}e{exp}").parse::<f32>().unwrap()
+      } else {
+        let digits = fra.chars().map(|c| c.to_digit(radix).unwrap() as f64);
+        let fra = digits.rev().fold(0.0, |acc, digit| (acc + digit) / radix as f64);
+        ((num as f64 + fra) * 2f64.powi(exp)) as f32
+      };
+      return Ok(Num::F24(sgn.unwrap_or(1) as f32 * val));
     }
 
     // I24
     if let Some(sgn) = sgn {
-      let num = sgn * num as i32;
-      if !(-0x00800000..=0x007fffff).contains(&num) {
-        return self.num_range_err(ini_idx, "I24");
+      let max = if sgn < 0 { 0x00800000 } else { 0x007fffff };
+      if num > max {
+        return self.num_range_err(ini_idx, "I24", "-8388608 to +8388607");
       }
-      return Ok(Num::I24(num));
+      return Ok(Num::I24(sgn * num as i32));
     }
 
     // U24
     if num >= 1 << 24 {
-      return self.num_range_err(ini_idx, "U24");
+      return self.num_range_err(ini_idx, "U24", "0 to 16777215");
     }
     Ok(Num::U24(num))
   }
 
-  fn num_range_err<T>(&mut self, ini_idx: usize, typ: &str) -> ParseResult<T> {
-    let msg = format!("\x1b[1mNumber literal outside of range for {}.\x1b[0m", typ);
+  fn num_range_err<T>(&mut self, ini_idx: usize, typ: &str, range: &str) -> ParseResult<T> {
+    let msg = format!("\x1b[1mNumber literal outside of range for {typ}, which goes from {range}.\x1b[0m");
     let end_idx = *self.index();
     self.with_ctx(Err(msg), ini_idx, end_idx)
   }
@@ -1208,4 +1864,25 @@ pub trait ParserCommons<'a>: Parser<'a> {
     self.consume_exactly("`")?;
     Ok(result)
   }
+
+  /// Parses a string literal between double quotes, with escape sequences,
+  /// or a raw string literal between triple double quotes, which can span multiple lines
+  /// and is kept exactly as written, without processing escape sequences.
+  fn parse_string(&mut self) -> ParseResult<String> {
+    self.skip_trivia();
+    if !self.starts_with(RAW_STR_DELIM) {
+      return self.parse_quoted_string();
+    }
+    let ini_idx = *self.index();
+    self.consume_exactly(RAW_STR_DELIM)?;
+    let idx = *self.index();
+    let Some(len) = self.input()[idx..].find(RAW_STR_DELIM) else {
+      let msg =
+        format!("\x1b[1mUnterminated raw string literal, expected a closing '{RAW_STR_DELIM}'.\x1b[0m");
+      return self.with_ctx(Err(msg), ini_idx, idx);
+    };
+    let str = self.input()[idx..idx + len].to_string();
+    *self.index() = idx + len + RAW_STR_DELIM.len();
+    Ok(str)
+  }
 }
@@ -0,0 +1,67 @@
+use crate::{
+  fun::{display::FloatFormat, FanKind, Pattern, Tag, Term},
+  maybe_grow,
+};
+use itertools::Itertools;
+
+/// Converts a result of a program to an S-expression, for tools that read S-expressions instead of Bend.
+///
+/// - Numbers, strings, variables, references and erased values (`*`) are atoms written like in Bend,
+///   with the floats in the given format.
+/// - Applications, like the constructors of a type, become lists with the function as the head,
+///   like `(List/Cons 1 List/Nil)`.
+/// - Numeric operations become lists with the operator as the head, like `(+ a 1)`.
+/// - `List`s, tuples, superpositions and lambdas become lists headed by `:list`, `:tuple`, `:sup` and `:lambda`,
+///   like `(:list 1 2)` and `(:lambda x x)`. Since `:` can't be part of a Bend name, they can't be confused
+///   with applications.
+///
+/// Fails if the term has anything else, like a match or a tagged lambda.
+pub fn term_to_sexpr(term: &Term, float_format: FloatFormat) -> Result<String, String> {
+  maybe_grow(|| {
+    let sexpr = match term {
+      Term::Num { .. }
+      | Term::Nat { .. }
+      | Term::Str { .. }
+      | Term::Var { .. }
+      | Term::Link { .. }
+      | Term::Ref { .. }
+      | Term::Era => term.display_with_floats(float_format).to_string(),
+      Term::App { tag: Tag::Static, .. } => {
+        let mut args = vec![];
+        let mut head = term;
+        while let Term::App { tag: Tag::Static, fun, arg } = head {
+          args.push(arg.as_ref());
+          head = fun;
+        }
+        let els = std::iter::once(head).chain(args.into_iter().rev());
+        list(None, els, float_format)?
+      }
+      Term::Oper { opr, fst, snd } => {
+        list(Some(&opr.to_string()), [fst.as_ref(), snd.as_ref()], float_format)?
+      }
+      Term::List { els } => list(Some(":list"), els, float_format)?,
+      Term::Fan { fan: FanKind::Tup, tag: Tag::Static, els } => list(Some(":tuple"), els, float_format)?,
+      Term::Fan { fan: FanKind::Dup, tag: Tag::Auto | Tag::Static, els } => {
+        list(Some(":sup"), els, float_format)?
+      }
+      Term::Lam { tag: Tag::Static, pat, bod }
+        if matches!(pat.as_ref(), Pattern::Var(_) | Pattern::Chn(_)) =>
+      {
+        format!("(:lambda {pat} {})", term_to_sexpr(bod, float_format)?)
+      }
+      _ => return Err(format!("The term '{term}' can't be represented as an S-expression.")),
+    };
+    Ok(sexpr)
+  })
+}
+
+/// A list with the given head and elements, like `(head el1 el2)`.
+fn list<'a>(
+  head: Option<&str>,
+  els: impl IntoIterator<Item = &'a Term>,
+  float_format: FloatFormat,
+) -> Result<String, String> {
+  let els = els.into_iter().map(|el| term_to_sexpr(el, float_format));
+  let els = head.map(|head| Ok(head.to_string())).into_iter().chain(els).collect::<Result<Vec<_>, _>>()?;
+  Ok(format!("({})", els.iter().join(" ")))
+}
@@ -0,0 +1,214 @@
+use crate::{
+  fun::{Definition, FanKind, Name, Pattern, Term},
+  maybe_grow,
+};
+use std::collections::HashMap;
+
+impl Term {
+  /// Checks if two terms, usually the results read back from a program, have the same meaning.
+  ///
+  /// The terms are compared up to the renaming of their variables, both scoped and unscoped.
+  /// Before comparing, `let`s that only name a value, duplicate it or destructure a tuple literal
+  /// are substituted into their bodies, so the order in which they were read back doesn't matter
+  /// and the duplications of the linear readback (`-l`) compare equal to the repeated variables
+  /// of the normal one.
+  ///
+  /// Expects the variables bound inside of the terms to have distinct names,
+  /// like the ones given by the readback.
+  pub fn structurally_eq(&self, other: &Term) -> bool {
+    let mut lhs = self.clone();
+    let mut rhs = other.clone();
+    lhs.expand_readback_lets();
+    rhs.expand_readback_lets();
+    AlphaEq::default().terms_eq(&lhs, &rhs)
+  }
+
+  /// Substitutes the `let`s that don't change the meaning of the term into their bodies.
+  fn expand_readback_lets(&mut self) {
+    maybe_grow(|| loop {
+      match self {
+        Term::Let { pat, val, nxt } => {
+          val.expand_readback_lets();
+          let vals = match (pat.as_ref(), val.as_ref()) {
+            (Pattern::Var(nam), _) => vec![(nam, val.as_ref())],
+            (Pattern::Fan(FanKind::Dup, _, pats), _) if pats.iter().all(|p| matches!(p, Pattern::Var(_))) => {
+              pats.iter().map(|p| (p.binds().next().unwrap(), val.as_ref())).collect()
+            }
+            (Pattern::Fan(FanKind::Tup, tag, pats), Term::Fan { fan: FanKind::Tup, tag: val_tag, els })
+              if tag == val_tag
+                && pats.len() == els.len()
+                && pats.iter().all(|p| matches!(p, Pattern::Var(_))) =>
+            {
+              pats.iter().map(|p| p.binds().next().unwrap()).zip(els).collect()
+            }
+            _ => break,
+          };
+          for (nam, val) in vals {
+            if let Some(nam) = nam {
+              nxt.subst(nam, val);
+            }
+          }
+          *self = std::mem::take(nxt.as_mut());
+        }
+        Term::Use { nam, val, nxt } => {
+          if let Some(nam) = nam {
+            nxt.subst(nam, val);
+          }
+          *self = std::mem::take(nxt.as_mut());
+        }
+        _ => break,
+      }
+    });
+    for child in self.children_mut() {
+      child.expand_readback_lets();
+    }
+  }
+}
+
+impl Definition {
+  /// Checks if two definitions have the same rules, up to the renaming of their variables.
+  ///
+  /// Unlike [`Term::structurally_eq`], the rules are compared as they're written,
+  /// so a `let` is never the same as the value it binds.
+  pub fn alpha_eq(&self, other: &Definition) -> bool {
+    self.rules.len() == other.rules.len()
+      && self.rules.iter().zip(&other.rules).all(|(lhs, rhs)| {
+        let mut alpha_eq = AlphaEq::default();
+        if !alpha_eq.pattern_lists_eq(&lhs.pats, &rhs.pats) {
+          return false;
+        }
+        let l_binds = lhs.pats.iter().flat_map(|pat| pat.binds().cloned());
+        let r_binds = rhs.pats.iter().flat_map(|pat| pat.binds().cloned());
+        alpha_eq.scope.extend(l_binds.zip(r_binds));
+        alpha_eq.terms_eq(&lhs.body, &rhs.body)
+      })
+  }
+}
+
+/// The correspondence between the variables of the two terms being compared.
+#[derive(Default)]
+struct AlphaEq {
+  /// The pairs of variables bound around the subterms currently being compared, innermost last.
+  scope: Vec<(Option<Name>, Option<Name>)>,
+  /// The unscoped variables of the left term paired with the ones of the right term, in both directions.
+  unscoped: HashMap<String, String>,
+  unscoped_rev: HashMap<String, String>,
+}
+
+impl AlphaEq {
+  fn terms_eq(&mut self, lhs: &Term, rhs: &Term) -> bool {
+    maybe_grow(|| {
+      let same_node = match (lhs, rhs) {
+        (Term::Var { nam: l }, Term::Var { nam: r }) => return self.vars_eq(l, r),
+        (Term::Link { nam: l }, Term::Link { nam: r }) => return self.unscoped_eq(l, r),
+        (Term::Lam { tag: lt, pat: lp, .. }, Term::Lam { tag: rt, pat: rp, .. }) => {
+          lt == rt && self.patterns_eq(lp, rp)
+        }
+        (Term::Let { pat: lp, .. }, Term::Let { pat: rp, .. })
+        | (Term::Ask { pat: lp, .. }, Term::Ask { pat: rp, .. }) => self.patterns_eq(lp, rp),
+        (Term::Use { .. }, Term::Use { .. }) => true,
+        (Term::With { typ: lt, .. }, Term::With { typ: rt, .. }) => lt == rt,
+        (Term::App { tag: lt, .. }, Term::App { tag: rt, .. }) => lt == rt,
+        (Term::Fan { fan: lf, tag: lt, els: le }, Term::Fan { fan: rf, tag: rt, els: re }) => {
+          lf == rf && lt == rt && le.len() == re.len()
+        }
+        (Term::List { els: le }, Term::List { els: re }) => le.len() == re.len(),
+        (Term::Oper { opr: lo, .. }, Term::Oper { opr: ro, .. }) => lo == ro,
+        (
+          Term::Mat { with_bnd: lw, arms: la, .. } | Term::Fold { with_bnd: lw, arms: la, .. },
+          Term::Mat { with_bnd: rw, arms: ra, .. } | Term::Fold { with_bnd: rw, arms: ra, .. },
+        ) => {
+          std::mem::discriminant(lhs) == std::mem::discriminant(rhs)
+            && lw.len() == rw.len()
+            && la.len() == ra.len()
+            && la.iter().zip(ra).all(|(l, r)| l.0 == r.0 && l.1.len() == r.1.len())
+        }
+        (Term::Swt { with_bnd: lw, arms: la, .. }, Term::Swt { with_bnd: rw, arms: ra, .. }) => {
+          lw.len() == rw.len() && la.len() == ra.len()
+        }
+        (Term::Bend { bnd: lb, .. }, Term::Bend { bnd: rb, .. }) => lb.len() == rb.len(),
+        (Term::Num { val: l }, Term::Num { val: r }) => l == r,
+        (Term::Nat { val: l }, Term::Nat { val: r }) => l == r,
+        (Term::Str { val: l }, Term::Str { val: r }) => l == r,
+        (Term::Ref { nam: l }, Term::Ref { nam: r }) => l == r,
+        (Term::Era, Term::Era) | (Term::Err, Term::Err) => true,
+        _ => false,
+      };
+      if !same_node {
+        return false;
+      }
+
+      for ((l_child, l_binds), (r_child, r_binds)) in lhs.children_with_binds().zip(rhs.children_with_binds())
+      {
+        let scope_len = self.scope.len();
+        self.scope.extend(l_binds.cloned().zip(r_binds.cloned()));
+        let eq = self.terms_eq(l_child, r_child);
+        self.scope.truncate(scope_len);
+        if !eq {
+          return false;
+        }
+      }
+      true
+    })
+  }
+
+  /// Whether two variables refer to corresponding binders, or are the same free variable.
+  fn vars_eq(&self, lhs: &Name, rhs: &Name) -> bool {
+    let l_bind = self.scope.iter().rposition(|(l, _)| l.as_ref() == Some(lhs));
+    let r_bind = self.scope.iter().rposition(|(_, r)| r.as_ref() == Some(rhs));
+    match (l_bind, r_bind) {
+      (None, None) => lhs == rhs,
+      (l_bind, r_bind) => l_bind == r_bind,
+    }
+  }
+
+  fn unscoped_eq(&mut self, lhs: &Name, rhs: &Name) -> bool {
+    let r = self.unscoped.entry(lhs.to_string()).or_insert_with(|| rhs.to_string());
+    let l = self.unscoped_rev.entry(rhs.to_string()).or_insert_with(|| lhs.to_string());
+    r.as_str() == rhs.as_ref() && l.as_str() == lhs.as_ref()
+  }
+
+  /// Compares the structure of two patterns, pairing their unscoped variables.
+  /// Their scoped variables are paired when comparing the terms in their scope.
+  fn patterns_eq(&mut self, lhs: &Pattern, rhs: &Pattern) -> bool {
+    maybe_grow(|| match (lhs, rhs) {
+      (Pattern::Var(_), Pattern::Var(_)) => true,
+      (Pattern::Chn(l), Pattern::Chn(r)) => self.unscoped_eq(l, r),
+      (Pattern::Ctr(ln, lp), Pattern::Ctr(rn, rp)) => ln == rn && self.pattern_lists_eq(lp, rp),
+      (Pattern::Num(l), Pattern::Num(r)) => l == r,
+      (Pattern::Fan(lf, lt, lp), Pattern::Fan(rf, rt, rp)) => {
+        lf == rf && lt == rt && self.pattern_lists_eq(lp, rp)
+      }
+      (Pattern::Lst(lp), Pattern::Lst(rp)) => self.pattern_lists_eq(lp, rp),
+      (Pattern::Str(l), Pattern::Str(r)) => l == r,
+      _ => false,
+    })
+  }
+
+  fn pattern_lists_eq(&mut self, lhs: &[Pattern], rhs: &[Pattern]) -> bool {
+    lhs.len() == rhs.len() && lhs.iter().zip(rhs).all(|(l, r)| self.patterns_eq(l, r))
+  }
+}
+
+#[test]
+fn structurally_eq() {
+  use crate::fun::parser::TermParser;
+  let eq = |lhs: &str, rhs: &str| {
+    let lhs = TermParser::new(lhs).parse_term().unwrap();
+    let rhs = TermParser::new(rhs).parse_term().unwrap();
+    lhs.structurally_eq(&rhs)
+  };
+
+  assert!(eq("λa λb (a b)", "λx λy (x y)"));
+  assert!(!eq("λa λb (a b)", "λx λy (y x)"));
+  assert!(!eq("λa (a b)", "λx (x c)"));
+  assert!(!eq("λa λb b", "λb λa b"));
+  assert!(eq("λ$a λb ($a b)", "λ$x λy ($x y)"));
+  assert!(!eq("λ$a λ$b ($a $b)", "λ$x λ$y ($x $x)"));
+  assert!(eq("λa let {b c} = a; (b c)", "λa (a a)"));
+  assert!(eq("λf let x = (f 1); let y = (f 2); (x y)", "λg let b = (g 2); let a = (g 1); (a b)"));
+  assert!(eq("let (a, b) = (1, 2); [a, b]", "[1, 2]"));
+  assert!(!eq("{1 2}", "{2 1}"));
+  assert!(eq("λx switch x { 0: 0; _: x-1 }", "λy switch y { 0: 0; _: y-1 }"));
+  assert!(!eq("λx switch x { 0: 0; _: x-1 }", "λy switch y { 0: 0; _: y }"));
+}
@@ -7,6 +7,7 @@ use crate::{
 };
 use hvm::ast::{Net, Tree};
 use loaned::LoanedMut;
+use serde::{Deserialize, Serialize};
 use std::{
   collections::{hash_map::Entry, HashMap},
   ops::{Index, IndexMut},
@@ -24,6 +25,9 @@ pub fn book_to_hvm(book: &Book, diags: &mut Diagnostics) -> Result<(hvm::ast::Bo
   let main = book.entrypoint.as_ref().unwrap();
 
   for def in book.defs.values() {
+    if book.host_defs.contains_key(&def.name) {
+      continue;
+    }
     for rule in def.rules.iter() {
       let net = term_to_hvm(&rule.body, &mut labels);
 
@@ -38,6 +42,11 @@ pub fn book_to_hvm(book: &Book, diags: &mut Diagnostics) -> Result<(hvm::ast::Bo
     }
   }
 
+  // Host definitions are added even if unused by the program, since they may be used by each other.
+  for (name, net) in &book.host_defs {
+    hvm_book.defs.insert(name.to_string(), net.clone());
+  }
+
   labels.con.finish();
   labels.dup.finish();
 
@@ -351,14 +360,14 @@ impl<'t, 'l> EncodeTermState<'t, 'l> {
   }
 }
 
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct Labels {
   pub con: LabelGenerator,
   pub dup: LabelGenerator,
   pub tup: LabelGenerator,
 }
 
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct LabelGenerator {
   pub next: u16,
   pub name_to_label: HashMap<Name, u16>,
@@ -432,7 +441,7 @@ impl LabelGenerator {
 }
 
 impl Op {
-  fn to_native_tag(self) -> hvm::hvm::Tag {
+  pub(crate) fn to_native_tag(self) -> hvm::hvm::Tag {
     match self {
       Op::ADD => hvm::hvm::OP_ADD,
       Op::SUB => hvm::hvm::OP_SUB,
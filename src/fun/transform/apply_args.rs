@@ -14,6 +14,9 @@ impl Ctx<'_> {
   /// ```hvm
   /// main = (λx1 λx2 λx3 (MainBody x1 x2 x3) arg1 arg2 arg3)
   /// ```
+  ///
+  /// The number of arguments must match the arity of main,
+  /// that is, its patterns plus the lambdas at the start of its body.
   pub fn apply_args(&mut self, args: Option<Vec<Term>>) -> Result<(), Diagnostics> {
     self.info.start_pass();
 
@@ -43,6 +46,17 @@ impl Ctx<'_> {
       }
 
       if let Some(args) = args {
+        let arity = main_body.lambda_count();
+        if args.len() != arity {
+          let plural = if arity == 1 { "" } else { "s" };
+          self.info.add_rule_error(
+            format!(
+              "Expected the entrypoint function to receive {arity} argument{plural}, found {}.",
+              args.len()
+            ),
+            entrypoint.clone(),
+          );
+        }
         main_body = Term::call(main_body, args);
       }
 
@@ -0,0 +1,112 @@
+use crate::{
+  fun::{Ctx, Name, Term},
+  maybe_grow,
+};
+use indexmap::IndexMap;
+use std::collections::BTreeMap;
+
+/// Stands for every reference when comparing the shapes of the definitions.
+const REF_PLACEHOLDER: &str = "%ref";
+
+impl Ctx<'_> {
+  /// Removes the definitions that are the same as an earlier definition, up to the names of their
+  /// variables and of the definitions they reference, and makes the references to them point to
+  /// the earlier one, which keeps its name.
+  ///
+  /// Two definitions are the same if their bodies have the same shape and each of their references
+  /// is to the same definition or to two definitions that are also the same.
+  /// This way, groups of recursive definitions that only differ in their names are deduplicated,
+  /// while a definition that calls another one is never taken as recursive.
+  ///
+  /// Unlike [`crate::fun::Book::merge_definitions`], the definition that is kept keeps its name.
+  /// The entrypoint and the definitions provided by the host are never removed.
+  pub fn dedup_definitions(&mut self) {
+    // The shape of each definition, with the definitions it references in order.
+    let mut shapes = IndexMap::new();
+    let mut refs = IndexMap::new();
+    for (def_name, def) in &self.book.defs {
+      let (shape, def_refs) = def.rule().body.ref_shape();
+      shapes.insert(def_name.clone(), shape);
+      refs.insert(def_name.clone(), def_refs);
+    }
+
+    // Starts with one class per shape, then splits the classes whose definitions reference
+    // definitions of different classes, until no class is split.
+    let mut class_of: IndexMap<Name, usize> = IndexMap::new();
+    let mut classes = IndexMap::new();
+    for (def_name, shape) in shapes {
+      let keep =
+        self.book.entrypoint.as_ref() == Some(&def_name) || self.book.host_defs.contains_key(&def_name);
+      let key = if keep { Err(def_name.clone()) } else { Ok(shape) };
+      let len = classes.len();
+      class_of.insert(def_name, *classes.entry(key).or_insert(len));
+    }
+    let mut class_count = classes.len();
+    loop {
+      let mut new_classes = IndexMap::new();
+      let mut new_class_of = IndexMap::new();
+      for (def_name, class) in &class_of {
+        let ref_classes =
+          refs[def_name].iter().map(|nam| class_of.get(nam).copied().ok_or(nam)).collect::<Vec<_>>();
+        let len = new_classes.len();
+        new_class_of.insert(def_name.clone(), *new_classes.entry((*class, ref_classes)).or_insert(len));
+      }
+      class_of = new_class_of;
+      if new_classes.len() == class_count {
+        break;
+      }
+      class_count = new_classes.len();
+    }
+
+    // Map of each removed definition to the first definition of its class.
+    let mut name_map = BTreeMap::new();
+    let mut canonical_defs: IndexMap<usize, Name> = IndexMap::new();
+    for (def_name, class) in &class_of {
+      match canonical_defs.get(class) {
+        Some(canonical) => {
+          name_map.insert(def_name.clone(), canonical.clone());
+        }
+        None => {
+          canonical_defs.insert(*class, def_name.clone());
+        }
+      }
+    }
+
+    for (name, canonical) in &name_map {
+      let def = self.book.defs.shift_remove(name).unwrap();
+      // The kept definition is only builtin if all the ones it replaces are.
+      if !def.builtin {
+        self.book.defs.get_mut(canonical).unwrap().builtin = false;
+      }
+    }
+    for def in self.book.defs.values_mut() {
+      Term::subst_ref_to_ref(&mut def.rule_mut().body, &name_map);
+    }
+
+    if self.info.config.verbose && !name_map.is_empty() {
+      self.info.add_note(format!("Removed {} definitions identical to other definitions.", name_map.len()));
+    }
+  }
+}
+
+impl Term {
+  /// A copy of the term with its references replaced by [`REF_PLACEHOLDER`] and its variables
+  /// renamed in the order they're bound, together with the replaced references in the same order.
+  fn ref_shape(&self) -> (Term, Vec<Name>) {
+    fn go(term: &mut Term, refs: &mut Vec<Name>) {
+      maybe_grow(|| {
+        if let Term::Ref { nam } = term {
+          refs.push(std::mem::replace(nam, Name::new(REF_PLACEHOLDER)));
+        }
+        for child in term.children_mut() {
+          go(child, refs);
+        }
+      })
+    }
+    let mut shape = self.clone();
+    let mut refs = vec![];
+    go(&mut shape, &mut refs);
+    shape.make_var_names_unique();
+    (shape, refs)
+  }
+}
@@ -16,12 +16,12 @@ impl Book {
   /// Should not be preceded by passes that cares about the origins.
   pub fn merge_definitions(&mut self) {
     let defs: Vec<_> = self.defs.keys().cloned().collect();
-    self.merge(defs.into_iter());
+    self.merge_equal_defs(defs.into_iter());
   }
 
   /// Checks and merges identical definitions given by `defs`.
   /// We never merge the entrypoint function with something else.
-  fn merge(&mut self, defs: impl Iterator<Item = Name>) {
+  fn merge_equal_defs(&mut self, defs: impl Iterator<Item = Name>) {
     let name = self.entrypoint.clone();
     // Sets of definitions that are identical, indexed by the body term.
     let equal_terms =
@@ -80,7 +80,7 @@ impl Book {
     }
 
     if !updated_defs.is_empty() {
-      self.merge(updated_defs.into_iter());
+      self.merge_equal_defs(updated_defs.into_iter());
     }
   }
 }
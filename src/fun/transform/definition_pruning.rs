@@ -1,9 +1,9 @@
 use crate::{
-  diagnostics::WarningType,
+  diagnostics::{DiagnosticOrigin, Diagnostics, Severity, WarningType},
   fun::{Book, Ctx, Name, Term},
   maybe_grow,
 };
-use std::collections::{hash_map::Entry, HashMap};
+use std::collections::{hash_map::Entry, HashMap, HashSet};
 
 #[derive(Clone, Copy, Debug, PartialEq)]
 enum Used {
@@ -18,9 +18,9 @@ enum Used {
 type Definitions = HashMap<Name, Used>;
 
 impl Ctx<'_> {
-  /// If `prune_all`, removes all unused definitions and adts starting from Main.
+  /// If `prune_all`, removes all unused definitions and adts starting from Main and the given `roots`.
   /// Otherwise, prunes only the builtins not accessible from any non-built-in definition
-  pub fn prune(&mut self, prune_all: bool) {
+  pub fn prune(&mut self, prune_all: bool, roots: &[Name]) {
     let mut used = Definitions::new();
 
     // Get the functions that are accessible from the main entry point.
@@ -30,6 +30,16 @@ impl Ctx<'_> {
       self.book.find_used_definitions(&def.rule().body, Used::Main, &mut used);
     }
 
+    // The roots asked to be compiled are kept just like the main entry point.
+    for root in roots {
+      if let Some(def) = self.book.defs.get(root) {
+        used.insert(root.clone(), Used::Main);
+        for rule in &def.rules {
+          self.book.find_used_definitions(&rule.body, Used::Main, &mut used);
+        }
+      }
+    }
+
     // Get the functions that are accessible from non-builtins.
     for def in self.book.defs.values() {
       if !def.builtin && !(used.get(&def.name) == Some(&Used::Main)) {
@@ -43,6 +53,7 @@ impl Ctx<'_> {
     }
 
     // Remove unused definitions.
+    let mut pruned = vec![];
     for def in self.book.defs.keys().cloned().collect::<Vec<_>>() {
       if let Some(use_) = used.get(&def) {
         match use_ {
@@ -54,6 +65,7 @@ impl Ctx<'_> {
             // Prune if `prune_all`, otherwise show a warning.
             if prune_all {
               self.book.defs.shift_remove(&def);
+              pruned.push(def);
             } else {
               self.info.add_rule_warning("Definition is unused.", WarningType::UnusedDefinition, def);
             }
@@ -63,6 +75,7 @@ impl Ctx<'_> {
             // Prune if `prune_all`, otherwise nothing.
             if prune_all {
               self.book.defs.shift_remove(&def);
+              pruned.push(def);
             } else {
               // Don't show warning if it's a user-defined constructor.
             }
@@ -73,6 +86,76 @@ impl Ctx<'_> {
         self.book.defs.shift_remove(&def);
       }
     }
+
+    if self.info.config.report_pruned && !pruned.is_empty() {
+      let pruned = pruned.iter().map(|def| format!("'{def}'")).collect::<Vec<_>>().join(", ");
+      self.info.add_diagnostic(
+        format!("The following unused definitions were pruned: {pruned}."),
+        Severity::Warning,
+        DiagnosticOrigin::Book,
+      );
+    }
+  }
+}
+
+impl Ctx<'_> {
+  /// Removes the user functions that can't be reached from the entrypoint or from the given `roots`,
+  /// so that the rest of the compilation only processes the definitions that are needed.
+  ///
+  /// Since this runs before references are resolved, any variable named like a function counts as a use of it.
+  /// Built-in functions are always kept, since later passes may generate references to them.
+  pub fn prune_unreachable(&mut self, roots: &[Name], args: Option<&[Term]>) -> Result<(), Diagnostics> {
+    self.info.start_pass();
+
+    let mut to_visit = vec![];
+    for root in roots {
+      if self.book.defs.contains_key(root) {
+        to_visit.push(root.clone());
+      } else if !self.book.ctrs.contains_key(root) {
+        self
+          .info
+          .add_book_error(format!("Definition '{root}' was selected for compilation but doesn't exist."));
+      }
+    }
+    to_visit.extend(self.book.entrypoint.clone());
+    for arg in args.into_iter().flatten() {
+      arg.find_possible_refs(&mut to_visit);
+    }
+
+    let mut reached = HashSet::new();
+    while let Some(name) = to_visit.pop() {
+      if let Some(def) = self.book.defs.get(&name) {
+        if reached.insert(name) {
+          for rule in &def.rules {
+            rule.body.find_possible_refs(&mut to_visit);
+          }
+        }
+      }
+    }
+
+    self.book.defs.retain(|name, def| def.builtin || reached.contains(name));
+
+    self.info.fatal(())
+  }
+}
+
+impl Term {
+  /// Collects the names that this term may reference once its references are resolved.
+  pub(crate) fn find_possible_refs(&self, refs: &mut Vec<Name>) {
+    maybe_grow(|| {
+      match self {
+        Term::Var { nam } | Term::Ref { nam } => refs.push(nam.clone()),
+        // 'with' blocks are desugared into calls to the monadic functions of the type.
+        Term::With { typ, .. } => {
+          refs.push(Name::new(format!("{typ}/wrap")));
+          refs.push(Name::new(format!("{typ}/bind")));
+        }
+        _ => {}
+      }
+      for child in self.children() {
+        child.find_possible_refs(refs);
+      }
+    })
   }
 }
 
@@ -1,4 +1,4 @@
-use std::collections::HashSet;
+use std::collections::{BTreeSet, HashSet};
 
 use crate::{
   diagnostics::Diagnostics,
@@ -70,9 +70,10 @@ impl Term {
         let Term::Fold { bnd: _, arg, with_bnd, with_arg, arms } = self else { unreachable!() };
 
         // Gather the free variables
-        let mut free_vars = HashSet::new();
+        // We need consistent iteration order.
+        let mut free_vars = BTreeSet::new();
         for arm in arms.iter() {
-          let mut arm_free_vars = arm.2.free_vars().into_keys().collect::<HashSet<_>>();
+          let mut arm_free_vars = arm.2.free_vars().into_keys().collect::<BTreeSet<_>>();
           for field in arm.1.iter().flatten() {
             arm_free_vars.remove(field);
           }
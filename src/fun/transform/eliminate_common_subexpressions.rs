@@ -0,0 +1,281 @@
+use crate::{
+  fun::{Book, FanKind, Name, Num, Op, Pattern, Tag, Term},
+  maybe_grow,
+};
+use indexmap::IndexMap;
+use std::collections::{BTreeMap, HashMap};
+
+impl Book {
+  /// Shares the structurally identical subterms of each definition, binding them once with a `let`
+  /// that the linearization of variables later turns into a duplication.
+  ///
+  /// Example: `λx (+ (f (g x)) (f (g x)))` becomes `λx let a = (f (g x)); (+ a a)`,
+  /// so that `(f (g x))` is computed only once.
+  ///
+  /// Only numeric operations and calls that receive all the arguments of the called definition are
+  /// shared, since partial applications are cheaper to build again than to duplicate.
+  /// They can only be made of applications, numeric operations, tuples, variables, references,
+  /// numbers and erasers, and are only shared between occurrences in the same scope, that is,
+  /// without a lambda or a match arm between them, so the shared subterm is still only evaluated
+  /// when its scope is.
+  /// The shared subterm is bound at the start of its scope, or right after the innermost `let`
+  /// of the scope that binds one of its variables.
+  /// When a repeated subterm contains another one, only the outer one is shared.
+  ///
+  /// Precondition: Matches have been encoded and variables have unique names within each definition.
+  pub fn eliminate_common_subexpressions(&mut self) {
+    let arities =
+      self.defs.iter().map(|(nam, def)| (nam.to_string(), def.rule().body.lambda_count())).collect();
+    for def in self.defs.values_mut() {
+      let mut name_gen = 0;
+      def.rule_mut().body.eliminate_common_subexpressions(&arities, &mut name_gen);
+    }
+  }
+}
+
+/// A shareable term, with its children identified by the index of their own shapes.
+/// Two terms have the same shape if and only if they are structurally identical.
+#[derive(PartialEq, Eq, Hash)]
+enum Shape {
+  Var(Name),
+  Ref(Name),
+  Num(Num),
+  Era,
+  App(Tag, [usize; 2]),
+  Oper(Op, [usize; 2]),
+  Tup(Tag, Vec<usize>),
+}
+
+struct ShapeInfo {
+  /// How many times the shape appears in the scope, including inside of other shapes.
+  count: usize,
+  /// The innermost binder of the scope whose variables the shape uses, if any.
+  binder: Option<usize>,
+  /// How many more arguments the shape needs to be a complete call of a definition.
+  missing_args: usize,
+}
+
+struct Scope<'a> {
+  /// The number of lambdas at the start of each definition.
+  arities: &'a HashMap<String, usize>,
+  shapes: IndexMap<Shape, ShapeInfo>,
+  /// The binder of each variable bound inside of the scope.
+  /// Binders are numbered in the order they're found, so inner binders have larger numbers.
+  binders: HashMap<Name, usize>,
+  /// The variable that each shared shape is bound to.
+  shared: BTreeMap<usize, Name>,
+  /// The shared shapes bound by each binder, or at the start of the scope.
+  bound_at: HashMap<Option<usize>, Vec<usize>>,
+}
+
+impl Term {
+  /// Shares the repeated subterms of the scope that starts at this term,
+  /// then does the same for the scopes nested in it.
+  fn eliminate_common_subexpressions(&mut self, arities: &HashMap<String, usize>, name_gen: &mut usize) {
+    maybe_grow(|| {
+      let mut scope = Scope {
+        arities,
+        shapes: Default::default(),
+        binders: Default::default(),
+        shared: Default::default(),
+        bound_at: Default::default(),
+      };
+      self.count_shapes(&mut scope);
+      scope.select_shared(name_gen);
+      if !scope.shared.is_empty() {
+        self.share_shapes(&scope);
+        scope.bind_shared(self, None);
+      }
+
+      let mut nested = vec![];
+      self.nested_scopes_mut(&mut nested);
+      for scope in nested {
+        scope.eliminate_common_subexpressions(arities, name_gen);
+      }
+    })
+  }
+
+  /// Counts how many times each shareable term appears in this scope, identifying them by their shape.
+  ///
+  /// Returns the index of the shape of this term if it can be shared,
+  /// that is, if it's made only of the allowed terms.
+  fn count_shapes(&self, scope: &mut Scope) -> Option<usize> {
+    maybe_grow(|| {
+      let mut children = vec![];
+      for (child, binds) in self.children_with_binds().take(self.scoped_children_count()) {
+        let binder = scope.binders.len();
+        scope.binders.extend(binds.flatten().map(|nam| (nam.clone(), binder)));
+        children.push(child.count_shapes(scope));
+      }
+      let children = children.into_iter().collect::<Option<Vec<_>>>()?;
+
+      let shape = self.shape(children)?;
+      let binder = match &shape {
+        Shape::Var(nam) => scope.binders.get(nam).copied(),
+        shape => shape.children().map(|&child| scope.shapes[child].binder).max().flatten(),
+      };
+      let missing_args = match &shape {
+        Shape::Ref(nam) => scope.arities.get(nam.as_ref()).copied().unwrap_or_default(),
+        Shape::App(_, [fun, _]) => scope.shapes[*fun].missing_args.saturating_sub(1),
+        _ => 0,
+      };
+      let entry = scope.shapes.entry(shape);
+      let idx = entry.index();
+      entry.or_insert(ShapeInfo { count: 0, binder, missing_args }).count += 1;
+      Some(idx)
+    })
+  }
+
+  /// Replaces the shared terms of this scope with their variables and binds them.
+  ///
+  /// Returns the index of the shape of this term if it can be shared.
+  fn share_shapes(&mut self, scope: &Scope) -> Option<usize> {
+    maybe_grow(|| {
+      let scoped = self.scoped_children_count();
+      let mut children = vec![];
+      for (child, mut binds) in self.children_mut_with_binds().take(scoped) {
+        let binder = binds.find_map(|nam| nam.as_ref()).map(|nam| scope.binders[nam]);
+        children.push(child.share_shapes(scope));
+        if binder.is_some() {
+          scope.bind_shared(child, binder);
+        }
+      }
+      let children = children.into_iter().collect::<Option<Vec<_>>>()?;
+
+      let idx = scope.shapes.get_index_of(&self.shape(children)?).unwrap();
+      if let Some(nam) = scope.shared.get(&idx) {
+        *self = Term::Var { nam: nam.clone() };
+      }
+      Some(idx)
+    })
+  }
+
+  /// The shape of this term, given the indices of the shapes of its children.
+  fn shape(&self, children: Vec<usize>) -> Option<Shape> {
+    let shape = match self {
+      Term::Var { nam } => Shape::Var(nam.clone()),
+      Term::Ref { nam } => Shape::Ref(nam.clone()),
+      Term::Num { val } => Shape::Num(*val),
+      Term::Era => Shape::Era,
+      Term::App { tag, .. } => Shape::App(tag.clone(), [children[0], children[1]]),
+      Term::Oper { opr, .. } => Shape::Oper(*opr, [children[0], children[1]]),
+      Term::Fan { fan: FanKind::Tup, tag, .. } => Shape::Tup(tag.clone(), children),
+      _ => return None,
+    };
+    Some(shape)
+  }
+
+  /// Collects the terms that start a new scope inside of the scope of this term.
+  fn nested_scopes_mut<'a>(&'a mut self, nested: &mut Vec<&'a mut Term>) {
+    maybe_grow(|| {
+      let scoped = self.scoped_children_count();
+      for (i, child) in self.children_mut().enumerate() {
+        if i < scoped {
+          child.nested_scopes_mut(nested);
+        } else {
+          nested.push(child);
+        }
+      }
+    })
+  }
+
+  /// The number of lambdas at the start of this term.
+  pub(crate) fn lambda_count(&self) -> usize {
+    match self {
+      Term::Lam { bod, .. } => 1 + bod.lambda_count(),
+      _ => 0,
+    }
+  }
+
+  /// How many of the children of this term, in the order of [`Term::children`],
+  /// are in the same scope as the term itself.
+  /// The remaining children, like the body of a lambda or the arms of a match, start a scope of their own.
+  fn scoped_children_count(&self) -> usize {
+    match self {
+      Term::Lam { .. } => 0,
+      Term::Mat { with_arg, .. } | Term::Swt { with_arg, .. } | Term::Fold { with_arg, .. } => {
+        1 + with_arg.len()
+      }
+      Term::Bend { arg, .. } => arg.len(),
+      _ => usize::MAX,
+    }
+  }
+}
+
+impl Scope<'_> {
+  /// Decides which shapes to share, naming their variables.
+  ///
+  /// Shapes are visited from the outermost to the innermost ones, since the index of a shape is
+  /// always larger than the ones of its children.
+  /// A repeated shape is shared only if it's still repeated after sharing the shapes containing it.
+  fn select_shared(&mut self, name_gen: &mut usize) {
+    let mut absorbed = vec![0; self.shapes.len()];
+    for (idx, (shape, info)) in self.shapes.iter().enumerate().rev() {
+      let count = info.count - absorbed[idx];
+      let is_shared = count > 1
+        && match shape {
+          Shape::App(..) => info.missing_args == 0,
+          Shape::Oper(..) => true,
+          _ => false,
+        };
+      if is_shared {
+        self.shared.insert(idx, Name::new(format!("%cse{name_gen}")));
+        self.bound_at.entry(info.binder).or_default().push(idx);
+        *name_gen += 1;
+      }
+      let remaining = if is_shared { 1 } else { count };
+      for &child in shape.children() {
+        absorbed[child] += info.count - remaining;
+      }
+    }
+  }
+
+  /// Binds the shared shapes of the given binder, or of the start of the scope, around `term`.
+  fn bind_shared(&self, term: &mut Term, binder: Option<usize>) {
+    // Shapes were selected from the outermost to the innermost ones,
+    // so the shapes used by other shapes end up bound first.
+    for &idx in self.bound_at.get(&binder).into_iter().flatten() {
+      let nxt = std::mem::take(term);
+      *term = Term::Let {
+        pat: Box::new(Pattern::Var(Some(self.shared[&idx].clone()))),
+        val: Box::new(self.build(idx)),
+        nxt: Box::new(nxt),
+      };
+    }
+  }
+
+  /// Builds the term of the given shape, using the variables of the shared shapes it contains.
+  fn build(&self, idx: usize) -> Term {
+    maybe_grow(|| {
+      let child = |idx: &usize| match self.shared.get(idx) {
+        Some(nam) => Term::Var { nam: nam.clone() },
+        None => self.build(*idx),
+      };
+      match self.shapes.get_index(idx).unwrap().0 {
+        Shape::Var(nam) => Term::Var { nam: nam.clone() },
+        Shape::Ref(nam) => Term::Ref { nam: nam.clone() },
+        Shape::Num(val) => Term::Num { val: *val },
+        Shape::Era => Term::Era,
+        Shape::App(tag, [fun, arg]) => {
+          Term::App { tag: tag.clone(), fun: Box::new(child(fun)), arg: Box::new(child(arg)) }
+        }
+        Shape::Oper(opr, [fst, snd]) => {
+          Term::Oper { opr: *opr, fst: Box::new(child(fst)), snd: Box::new(child(snd)) }
+        }
+        Shape::Tup(tag, els) => {
+          Term::Fan { fan: FanKind::Tup, tag: tag.clone(), els: els.iter().map(child).collect() }
+        }
+      }
+    })
+  }
+}
+
+impl Shape {
+  fn children(&self) -> std::slice::Iter<'_, usize> {
+    match self {
+      Shape::App(_, children) | Shape::Oper(_, children) => children.iter(),
+      Shape::Tup(_, els) => els.iter(),
+      Shape::Var(_) | Shape::Ref(_) | Shape::Num(_) | Shape::Era => [].iter(),
+    }
+  }
+}
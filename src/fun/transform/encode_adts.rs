@@ -1,13 +1,15 @@
 use crate::{
-  fun::{Book, Definition, Name, Num, Pattern, Rule, Term},
+  fun::{Book, CtrField, Definition, Name, Num, Pattern, Rule, Term},
   AdtEncoding,
 };
+use std::collections::HashMap;
 
 impl Book {
   /// Defines a function for each constructor in each ADT in the book.
   pub fn encode_adts(&mut self, adt_encoding: AdtEncoding) {
     let mut defs = vec![];
     let mut tags = vec![];
+    let ctr_tags = self.ctr_tags();
 
     for (adt_name, adt) in self.adts.iter() {
       for (ctr_idx, (ctr_name, fields)) in adt.ctrs.iter().enumerate() {
@@ -19,19 +21,23 @@ impl Book {
             let is_object = adt_name == ctr_name;
             if is_object {
               let tag = Name::new(format!("{ctr_name}/tag"));
-              let body = encode_ctr_num_scott(fields.iter().map(|f| &f.nam), &tag);
+              let body = encode_ctr_num_scott(fields.iter().map(|f| &f.nam), Term::r#ref(&tag));
               let tag_def = make_tag_def(ctr_idx, &tag, adt);
               tags.push((tag, tag_def));
               body
             } else {
               let (typ, ctr) = ctr_name.rsplit_once('/').expect("To split at '/'");
               let tag = Name::new(format!("{typ}/{ctr}/tag"));
-              let body = encode_ctr_num_scott(fields.iter().map(|f| &f.nam), &tag);
+              let body = encode_ctr_num_scott(fields.iter().map(|f| &f.nam), Term::r#ref(&tag));
               let tag_def = make_tag_def(ctr_idx, &tag, adt);
               tags.push((tag, tag_def));
               body
             }
           }
+          AdtEncoding::TaggedScott => {
+            let tag = Term::Num { val: Num::U24(ctr_tags[ctr_name]) };
+            encode_ctr_num_scott(fields.iter().map(|f| &f.nam), tag)
+          }
         };
 
         let rules = vec![Rule { pats: vec![], body }];
@@ -42,6 +48,18 @@ impl Book {
     self.defs.extend(defs);
     self.defs.extend(tags);
   }
+
+  /// The constructors of all the ADTs in the book, in the order of their tagged-scott tags.
+  pub fn ctrs_by_tag(&self) -> impl Iterator<Item = (&Name, &Vec<CtrField>)> {
+    self.adts.values().flat_map(|adt| adt.ctrs.iter())
+  }
+
+  /// The tag of each constructor in the tagged-scott encoding.
+  ///
+  /// Tags are unique across the whole book, so that values can be read back as their constructors.
+  pub fn ctr_tags(&self) -> HashMap<Name, u32> {
+    self.ctrs_by_tag().enumerate().map(|(tag, (ctr, _))| (ctr.clone(), tag as u32)).collect()
+  }
 }
 
 fn encode_ctr_scott<'a>(
@@ -55,11 +73,12 @@ fn encode_ctr_scott<'a>(
   Term::rfold_lams(lam, ctr_args.cloned().map(Some))
 }
 
-fn encode_ctr_num_scott<'a>(ctr_args: impl DoubleEndedIterator<Item = &'a Name> + Clone, tag: &str) -> Term {
+/// Encodes a constructor as `λa1 .. λan λx (x TAG a1 .. an)`.
+/// `tag` is either a reference to the constructor's tag definition or the tag number itself.
+fn encode_ctr_num_scott<'a>(ctr_args: impl DoubleEndedIterator<Item = &'a Name> + Clone, tag: Term) -> Term {
   let nam = Name::new("%x");
   // λa1 .. λan λx (x TAG a1 .. an)
   let term = Term::Var { nam: nam.clone() };
-  let tag = Term::r#ref(tag);
   let term = Term::app(term, tag);
   let term = Term::call(term, ctr_args.clone().cloned().map(|nam| Term::Var { nam }));
   let term = Term::lam(Pattern::Var(Some(nam)), term);
@@ -1,7 +1,8 @@
 use crate::{
-  fun::{Book, MatchRule, Name, Pattern, Term},
+  fun::{Book, MatchRule, Name, Num, Pattern, Term},
   maybe_grow, AdtEncoding,
 };
+use std::collections::HashMap;
 
 impl Book {
   /// Encodes pattern matching expressions in the book into their
@@ -13,19 +14,20 @@ impl Book {
   ///
   /// Var and pair matches become a let expression.
   pub fn encode_matches(&mut self, adt_encoding: AdtEncoding) {
+    let ctr_tags = self.ctr_tags();
     for def in self.defs.values_mut() {
       for rule in &mut def.rules {
-        rule.body.encode_matches(adt_encoding);
+        rule.body.encode_matches(adt_encoding, &ctr_tags);
       }
     }
   }
 }
 
 impl Term {
-  pub fn encode_matches(&mut self, adt_encoding: AdtEncoding) {
+  pub fn encode_matches(&mut self, adt_encoding: AdtEncoding, ctr_tags: &HashMap<Name, u32>) {
     maybe_grow(|| {
       for child in self.children_mut() {
-        child.encode_matches(adt_encoding)
+        child.encode_matches(adt_encoding, ctr_tags)
       }
 
       if let Term::Mat { arg, bnd: _, with_bnd, with_arg, arms } = self {
@@ -33,7 +35,7 @@ impl Term {
         assert!(with_arg.is_empty());
         let arg = std::mem::take(arg.as_mut());
         let rules = std::mem::take(arms);
-        *self = encode_match(arg, rules, adt_encoding);
+        *self = encode_match(arg, rules, adt_encoding, ctr_tags);
       } else if let Term::Swt { arg, bnd: _, with_bnd, with_arg, pred, arms } = self {
         assert!(with_bnd.is_empty());
         assert!(with_arg.is_empty());
@@ -46,51 +48,65 @@ impl Term {
   }
 }
 
-fn encode_match(arg: Term, rules: Vec<MatchRule>, adt_encoding: AdtEncoding) -> Term {
+fn encode_match(
+  arg: Term,
+  rules: Vec<MatchRule>,
+  adt_encoding: AdtEncoding,
+  ctr_tags: &HashMap<Name, u32>,
+) -> Term {
   match adt_encoding {
     AdtEncoding::Scott => {
       let arms = rules.into_iter().map(|rule| Term::rfold_lams(rule.2, rule.1.into_iter()));
       Term::call(arg, arms)
     }
-    AdtEncoding::NumScott => {
-      fn make_switches(arms: &mut [Term]) -> Term {
-        maybe_grow(|| match arms {
-          [] => Term::Err,
-          [arm] => Term::lam(Pattern::Var(None), std::mem::take(arm)),
-          [arm, rest @ ..] => Term::lam(
-            Pattern::Var(Some(Name::new("%tag"))),
-            Term::Swt {
-              arg: Box::new(Term::Var { nam: Name::new("%tag") }),
-              bnd: None,
-              with_bnd: vec![],
-              with_arg: vec![],
-              pred: None,
-              arms: vec![std::mem::take(arm), make_switches(rest)],
-            },
-          ),
-        })
-      }
-      let mut arms =
-        rules.into_iter().map(|rule| Term::rfold_lams(rule.2, rule.1.into_iter())).collect::<Vec<_>>();
-      let term = if arms.len() == 1 {
-        // λx (x λtag switch tag {0: Ctr0; _: * })
-        let arm = arms.pop().unwrap();
-        let term = Term::Swt {
-          arg: Box::new(Term::Var { nam: Name::new("%tag") }),
+    AdtEncoding::NumScott => encode_num_scott_match(arg, rules, 0),
+    AdtEncoding::TaggedScott => {
+      // The tags of a type are contiguous, starting at the tag of its first constructor.
+      let fst_tag = rules.first().and_then(|rule| rule.0.as_ref()).map_or(0, |ctr| ctr_tags[ctr]);
+      encode_num_scott_match(arg, rules, fst_tag)
+    }
+  }
+}
+
+/// Encodes a match on a value of the form `λx (x TAG a1 .. an)` as a sequence of native switches.
+/// `fst_tag` is the tag of the first constructor of the matched type.
+fn encode_num_scott_match(arg: Term, rules: Vec<MatchRule>, fst_tag: u32) -> Term {
+  fn make_switches(arms: &mut [Term], fst_tag: u32) -> Term {
+    maybe_grow(|| match arms {
+      [] => Term::Err,
+      [arm] => Term::lam(Pattern::Var(None), std::mem::take(arm)),
+      [arm, rest @ ..] => Term::lam(
+        Pattern::Var(Some(Name::new("%tag"))),
+        Term::Swt {
+          arg: Box::new(Term::sub_num(Term::Var { nam: Name::new("%tag") }, Num::U24(fst_tag))),
           bnd: None,
           with_bnd: vec![],
           with_arg: vec![],
           pred: None,
-          arms: vec![arm, Term::Era],
-        };
-        Term::lam(Pattern::Var(Some(Name::new("%tag"))), term)
-      } else {
-        // λx (x λtag switch tag {0: Ctr0; _: switch tag-1 { ... } })
-        make_switches(arms.as_mut_slice())
-      };
-      Term::call(arg, [term])
-    }
+          arms: vec![std::mem::take(arm), make_switches(rest, 0)],
+        },
+      ),
+    })
   }
+  let mut arms =
+    rules.into_iter().map(|rule| Term::rfold_lams(rule.2, rule.1.into_iter())).collect::<Vec<_>>();
+  let term = if arms.len() == 1 {
+    // λx (x λtag switch tag {0: Ctr0; _: * })
+    let arm = arms.pop().unwrap();
+    let term = Term::Swt {
+      arg: Box::new(Term::sub_num(Term::Var { nam: Name::new("%tag") }, Num::U24(fst_tag))),
+      bnd: None,
+      with_bnd: vec![],
+      with_arg: vec![],
+      pred: None,
+      arms: vec![arm, Term::Era],
+    };
+    Term::lam(Pattern::Var(Some(Name::new("%tag"))), term)
+  } else {
+    // λx (x λtag switch tag {0: Ctr0; _: switch tag-1 { ... } })
+    make_switches(arms.as_mut_slice(), fst_tag)
+  };
+  Term::call(arg, [term])
 }
 
 /// Convert into a sequence of native switches, decrementing by 1 each switch.
@@ -0,0 +1,71 @@
+use crate::{
+  fun::{Book, Name, Term},
+  maybe_grow,
+};
+use std::collections::HashMap;
+
+impl Book {
+  /// Eta-expands the partial applications of definitions, adding a lambda for each missing argument.
+  ///
+  /// Example: with `Add = λa λb (+ a b)`, `λx (Map (Add x) list)` becomes
+  /// `λx (Map λy (Add x y) list)`.
+  ///
+  /// The arity of a definition is the number of lambdas at the start of its body.
+  /// Constructors are not expanded, since their last lambdas are the ones of the match
+  /// and not missing arguments. References that are not applied to anything are also kept.
+  ///
+  /// This is experimental. The expanded call is evaluated again every time the lambda is called,
+  /// which repeats its work when the lambda is used more than once.
+  ///
+  /// Precondition: Matches have been encoded.
+  pub fn eta_expand(&mut self) {
+    let arities = self
+      .defs
+      .iter()
+      .filter(|(nam, _)| !self.ctrs.contains_key(*nam))
+      .map(|(nam, def)| (nam.to_string(), def.rule().body.lambda_count()))
+      .collect();
+    for def in self.defs.values_mut() {
+      let mut name_gen = 0;
+      def.rule_mut().body.eta_expand(&arities, &mut name_gen);
+    }
+  }
+}
+
+impl Term {
+  fn eta_expand(&mut self, arities: &HashMap<String, usize>, name_gen: &mut usize) {
+    maybe_grow(|| {
+      let missing_args = self.eta_expand_call(arities, name_gen);
+      if missing_args > 0 && matches!(self, Term::App { .. }) {
+        let vars = (0..missing_args)
+          .map(|_| {
+            let nam = Name::new(format!("%eta{name_gen}"));
+            *name_gen += 1;
+            nam
+          })
+          .collect::<Vec<_>>();
+        let call = Term::call(std::mem::take(self), vars.iter().map(|nam| Term::Var { nam: nam.clone() }));
+        *self = Term::rfold_lams(call, vars.into_iter().map(Some));
+      }
+    })
+  }
+
+  /// Eta-expands the partial applications inside of this term, but not the term itself.
+  ///
+  /// Returns how many more arguments this term needs to be a complete call of a definition.
+  fn eta_expand_call(&mut self, arities: &HashMap<String, usize>, name_gen: &mut usize) -> usize {
+    maybe_grow(|| match self {
+      Term::Ref { nam } => arities.get(nam.as_ref()).copied().unwrap_or_default(),
+      Term::App { fun, arg, .. } => {
+        arg.eta_expand(arities, name_gen);
+        fun.eta_expand_call(arities, name_gen).saturating_sub(1)
+      }
+      _ => {
+        for child in self.children_mut() {
+          child.eta_expand(arities, name_gen);
+        }
+        0
+      }
+    })
+  }
+}
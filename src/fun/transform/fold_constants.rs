@@ -0,0 +1,67 @@
+use crate::{
+  diagnostics::Diagnostics,
+  fun::{Ctx, Num, Op, Term},
+  maybe_grow,
+};
+
+impl Ctx<'_> {
+  /// Evaluates at compile time the numeric operations whose operands are both number literals.
+  ///
+  /// Example: `λx (+ x (* 2 (- 5 1)))` becomes `λx (+ x 8)`.
+  ///
+  /// The results are computed in the same way as the runtime does, so folding doesn't change
+  /// what a program returns. Integer divisions by zero are reported as errors, while operations
+  /// that the runtime doesn't support on the type of the numbers are left unchanged.
+  pub fn fold_constants(&mut self) -> Result<(), Diagnostics> {
+    self.info.start_pass();
+
+    for def in self.book.defs.values_mut() {
+      let mut errs = vec![];
+      for rule in def.rules.iter_mut() {
+        rule.body.fold_constants(&mut errs);
+      }
+      for err in errs {
+        self.info.add_rule_error(err, def.name.clone());
+      }
+    }
+
+    self.info.fatal(())
+  }
+}
+
+impl Term {
+  fn fold_constants(&mut self, errs: &mut Vec<String>) {
+    maybe_grow(|| {
+      for child in self.children_mut() {
+        child.fold_constants(errs);
+      }
+
+      if let Term::Oper { opr, fst, snd } = self {
+        if let (Term::Num { val: fst }, Term::Num { val: snd }) = (fst.as_ref(), snd.as_ref()) {
+          match fold_oper(*opr, *fst, *snd) {
+            Ok(Some(val)) => *self = Term::Num { val },
+            Ok(None) => {}
+            Err(()) => errs.push(format!("Division by zero in '{self}'.")),
+          }
+        }
+      }
+    })
+  }
+}
+
+/// The result of an operation on two numbers, as computed by the runtime.
+///
+/// Like in the compiled program, the first number is partially applied to the operation,
+/// so the type of the operation is the type of the second number.
+/// Returns `None` if the runtime doesn't support the operation on that type,
+/// and an error on an integer division by zero.
+fn fold_oper(opr: Op, fst: Num, snd: Num) -> Result<Option<Num>, ()> {
+  match (snd, opr) {
+    (Num::U24(_) | Num::I24(_), Op::DIV | Op::REM) if snd.is_zero() => return Err(()),
+    (Num::I24(_) | Num::F24(_), Op::SHL | Op::SHR) => return Ok(None),
+    _ => {}
+  }
+  let fst = hvm::hvm::Numb((fst.to_bits() & !0x1F) | opr.to_native_tag() as u32);
+  let snd = hvm::hvm::Numb(snd.to_bits());
+  Ok(Some(Num::from_bits(hvm::hvm::Numb::operate(fst, snd).0)))
+}
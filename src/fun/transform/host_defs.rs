@@ -0,0 +1,49 @@
+use crate::{
+  diagnostics::Diagnostics,
+  fun::{Book, Ctx, Definition, Name, Rule, Term},
+};
+
+impl Book {
+  /// Registers a definition provided by the program that embeds the compiler, already compiled to an HVM net.
+  ///
+  /// The program can reference it like any other definition, and its net is added to the compiled
+  /// HVM book unchanged. The net can reference other definitions by their HVM names.
+  ///
+  /// ```
+  /// use bend::{compile_book, diagnostics::DiagnosticsConfig, fun::Name, load_reader_to_book, CompileOpts};
+  /// use std::path::Path;
+  ///
+  /// let code = "main = (Host/fst 1 2)";
+  /// let mut book = load_reader_to_book(code.as_bytes(), Path::new("example.bend")).unwrap();
+  /// let net = hvm::ast::CoreParser::new("(a (* a))").parse_net().unwrap();
+  /// book.add_host_def(Name::new("Host/fst"), net.clone());
+  ///
+  /// let res = compile_book(&mut book, CompileOpts::default(), DiagnosticsConfig::default(), None).unwrap();
+  /// assert_eq!(res.hvm_book.defs["Host/fst"], net);
+  /// ```
+  pub fn add_host_def(&mut self, name: Name, net: hvm::ast::Net) {
+    self.host_defs.insert(name, net);
+  }
+}
+
+impl Ctx<'_> {
+  /// Adds a placeholder definition for each host definition, so that references to them are resolved.
+  /// Their nets are only given to them when compiling to HVM.
+  ///
+  /// Host definitions can't have the same name as a definition or constructor of the program.
+  pub fn add_host_defs(&mut self) -> Result<(), Diagnostics> {
+    self.info.start_pass();
+
+    for name in self.book.host_defs.keys() {
+      if self.book.defs.contains_key(name) || self.book.ctrs.contains_key(name) {
+        self.info.add_rule_error("Definition is also provided by the host.", name.clone());
+        continue;
+      }
+      // Referencing itself keeps it from being merged with other placeholders.
+      let rules = vec![Rule { pats: vec![], body: Term::Ref { nam: name.clone() } }];
+      self.book.defs.insert(name.clone(), Definition { name: name.clone(), rules, builtin: true });
+    }
+
+    self.info.fatal(())
+  }
+}
@@ -0,0 +1,190 @@
+use crate::{
+  fun::{Book, Definition, Name, Pattern, Rule, Tag, Term},
+  maybe_grow,
+};
+use std::collections::{HashMap, HashSet};
+
+/// A match arm whose executions are counted, by moving it to its own definition.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CoverageArm {
+  /// The definition written by the user that has the match.
+  pub def_name: Name,
+  /// The position of the match among the ones of the definition, starting from 1,
+  /// so that identical matches of a definition can be told apart.
+  pub match_idx: usize,
+  /// Describes the arm, like `match list: List/Cons` or `switch n: _`.
+  pub arm: String,
+  /// The definition the arm was moved to, or `None` if it couldn't be moved,
+  /// in which case its executions can't be measured.
+  pub moved_to: Option<Name>,
+}
+
+impl Book {
+  /// Moves each arm of the matches of the user's definitions to a new definition, so that
+  /// the number of times an arm is executed is the number of calls to its definition.
+  ///
+  /// Example: In a definition `Foo`, the arm `List/Cons x xs: body` becomes
+  /// `List/Cons x xs: (Foo__cov0 x xs)`, with `Foo__cov0 = λx λxs body`.
+  /// Once the matches are encoded, [`Book::eta_reduce_coverage_arms`] turns the arm into just
+  /// `Foo__cov0`, which is only called if the arm is taken.
+  ///
+  /// All the arms are recorded in [`Book::coverage_arms`], with the ones that couldn't be moved.
+  /// Only the arms without free variables or unscoped lambdas can be moved, so the matches must
+  /// have been linearized.
+  pub fn instrument_coverage(&mut self) {
+    let mut new_defs = vec![];
+    // The matches of a definition can be spread over the ones generated from it.
+    let mut match_counts = HashMap::new();
+    for (def_name, def) in self.defs.iter_mut() {
+      if def.builtin {
+        continue;
+      }
+      let source = def_name.def_name_from_generated();
+      let mut ctx = CoverageCtx {
+        def_name,
+        match_count: match_counts.entry(source.clone()).or_default(),
+        source,
+        name_gen: 0,
+        arms: &mut self.coverage_arms,
+        new_defs: &mut new_defs,
+      };
+      for rule in &mut def.rules {
+        rule.body.instrument_coverage(&mut ctx);
+      }
+    }
+    self.defs.extend(new_defs.into_iter().map(|def| (def.name.clone(), def)));
+  }
+
+  /// Replaces the encoded arms `λx λxs (Foo__cov0 x xs)` left by [`Book::instrument_coverage`]
+  /// with just `Foo__cov0`, so that the definition of the arm is only called when the arm is taken.
+  ///
+  /// Precondition: Matches have been encoded.
+  pub fn eta_reduce_coverage_arms(&mut self) {
+    let arms = self.coverage_arms.iter().filter_map(|arm| arm.moved_to.as_ref()).collect::<HashSet<_>>();
+    for def in self.defs.values_mut() {
+      for rule in &mut def.rules {
+        rule.body.eta_reduce_coverage_arms(&arms);
+      }
+    }
+  }
+}
+
+struct CoverageCtx<'a> {
+  def_name: &'a Name,
+  source: Name,
+  match_count: &'a mut usize,
+  name_gen: usize,
+  arms: &'a mut Vec<CoverageArm>,
+  new_defs: &'a mut Vec<Definition>,
+}
+
+impl CoverageCtx<'_> {
+  /// Gives the next position to a match of the definition.
+  fn next_match(&mut self) -> usize {
+    *self.match_count += 1;
+    *self.match_count
+  }
+
+  /// Moves the body of an arm that binds `fields` to a new definition, if it has no other free variables.
+  /// The arms that can't be moved are recorded as not measured and left for the caller to go through.
+  fn lift_arm(&mut self, fields: &mut [Option<Name>], body: &mut Term, match_idx: usize, arm: String) {
+    let def_name = self.source.clone();
+    let is_closed = body.free_vars().keys().all(|var| fields.iter().flatten().any(|field| field == var));
+    if !is_closed || body.has_unscoped() {
+      self.arms.push(CoverageArm { def_name, match_idx, arm, moved_to: None });
+      return;
+    }
+
+    let name = Name::new(format!("{}__cov{}", self.def_name, self.name_gen));
+    self.name_gen += 1;
+    self.arms.push(CoverageArm { def_name, match_idx, arm, moved_to: Some(name.clone()) });
+
+    // The arm passes all its fields to the new definition, so the unused ones also need a name.
+    for (i, field) in fields.iter_mut().enumerate() {
+      if field.is_none() {
+        *field = Some(Name::new(format!("%cov{i}")));
+      }
+    }
+    let mut arm_body = std::mem::take(body);
+    arm_body.instrument_coverage(self);
+    let args = fields.iter().flatten().map(|nam| Term::Var { nam: nam.clone() });
+    *body = Term::call(Term::Ref { nam: name.clone() }, args);
+
+    let rules = vec![Rule { pats: vec![], body: Term::rfold_lams(arm_body, fields.iter().cloned()) }];
+    self.new_defs.push(Definition { name, rules, builtin: false });
+  }
+}
+
+impl Term {
+  fn instrument_coverage(&mut self, ctx: &mut CoverageCtx) {
+    maybe_grow(|| {
+      match self {
+        Term::Mat { bnd, arms, .. } => {
+          let matched = matched_name(bnd);
+          let match_idx = ctx.next_match();
+          for (ctr, fields, body) in arms {
+            let ctr = ctr.as_ref().map_or("_".to_string(), ToString::to_string);
+            ctx.lift_arm(fields, body, match_idx, format!("match{matched}: {ctr}"));
+          }
+        }
+        Term::Swt { bnd, pred, arms, .. } => {
+          let matched = matched_name(bnd);
+          let match_idx = ctx.next_match();
+          let (succ, nums) = arms.split_last_mut().unwrap();
+          for (i, body) in nums.iter_mut().enumerate() {
+            ctx.lift_arm(&mut [], body, match_idx, format!("switch{matched}: {i}"));
+          }
+          ctx.lift_arm(std::slice::from_mut(pred), succ, match_idx, format!("switch{matched}: _"));
+        }
+        _ => {}
+      }
+      // The moved arms are just calls now, so this goes through the rest of the term.
+      for child in self.children_mut() {
+        child.instrument_coverage(ctx);
+      }
+    })
+  }
+
+  fn eta_reduce_coverage_arms(&mut self, arms: &HashSet<&Name>) {
+    maybe_grow(|| {
+      if let Some(nam) = self.coverage_arm_ref(arms) {
+        *self = Term::Ref { nam };
+        return;
+      }
+      for child in self.children_mut() {
+        child.eta_reduce_coverage_arms(arms);
+      }
+    })
+  }
+
+  /// If this term is `λx1 .. λxn (arm x1 .. xn)`, with `arm` the definition of an
+  /// instrumented arm, returns the name of that definition.
+  fn coverage_arm_ref(&self, arms: &HashSet<&Name>) -> Option<Name> {
+    let mut vars = vec![];
+    let mut term = self;
+    while let Term::Lam { tag: Tag::Static, pat, bod } = term {
+      let Pattern::Var(Some(var)) = pat.as_ref() else { return None };
+      vars.push(var);
+      term = bod;
+    }
+    let mut args = vec![];
+    while let Term::App { tag: Tag::Static, fun, arg } = term {
+      let Term::Var { nam } = arg.as_ref() else { return None };
+      args.push(nam);
+      term = fun;
+    }
+    args.reverse();
+    match term {
+      Term::Ref { nam } if !vars.is_empty() && vars == args && arms.contains(nam) => Some(nam.clone()),
+      _ => None,
+    }
+  }
+}
+
+/// The name of the matched variable, preceded by a space, or nothing if it was generated by the compiler.
+fn matched_name(bnd: &Option<Name>) -> String {
+  match bnd {
+    Some(nam) if !nam.contains('%') => format!(" {nam}"),
+    _ => String::new(),
+  }
+}
@@ -1,4 +1,5 @@
 pub mod apply_args;
+pub mod definition_dedup;
 pub mod definition_merge;
 pub mod definition_pruning;
 pub mod desugar_bend;
@@ -7,15 +8,22 @@ pub mod desugar_match_defs;
 pub mod desugar_open;
 pub mod desugar_use;
 pub mod desugar_with_blocks;
+pub mod eliminate_common_subexpressions;
 pub mod encode_adts;
 pub mod encode_match_terms;
+pub mod eta_expand;
 pub mod expand_generated;
 pub mod fix_match_defs;
 pub mod fix_match_terms;
 pub mod float_combinators;
+pub mod fold_constants;
+pub mod host_defs;
+pub mod instrument_coverage;
 pub mod linearize_matches;
 pub mod linearize_vars;
 pub mod resolve_refs;
 pub mod resugar_list;
 pub mod resugar_string;
+pub mod resugar_tagged_scott;
+pub mod specialize;
 pub mod unique_names;
@@ -8,7 +8,7 @@ impl Term {
   pub fn resugar_lists(&mut self, adt_encoding: AdtEncoding) {
     match adt_encoding {
       AdtEncoding::Scott => self.resugar_lists_scott(),
-      AdtEncoding::NumScott => self.resugar_lists_num_scott(),
+      AdtEncoding::NumScott | AdtEncoding::TaggedScott => self.resugar_lists_num_scott(),
     }
   }
 
@@ -8,7 +8,7 @@ impl Term {
   pub fn resugar_strings(&mut self, adt_encoding: AdtEncoding) {
     match adt_encoding {
       AdtEncoding::Scott => self.resugar_strings_scott(),
-      AdtEncoding::NumScott => self.resugar_strings_num_scott(),
+      AdtEncoding::NumScott | AdtEncoding::TaggedScott => self.resugar_strings_num_scott(),
     }
   }
 
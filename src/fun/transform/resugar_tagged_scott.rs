@@ -0,0 +1,41 @@
+use crate::{
+  fun::{Book, Name, Num, Pattern, Tag, Term},
+  maybe_grow,
+};
+
+impl Term {
+  /// Converts tagged-scott-encoded ADT values back into constructor applications.
+  ///
+  /// `λx (x TAG a1 .. an)` becomes `(Ctr a1 .. an)` if `TAG` is the tag of a constructor with `n` fields.
+  pub fn resugar_tagged_scott(&mut self, book: &Book) {
+    let ctrs = book.ctrs_by_tag().map(|(ctr, fields)| (ctr, fields.len())).collect::<Vec<_>>();
+    self.resugar_tagged_ctrs(&ctrs);
+  }
+
+  fn resugar_tagged_ctrs(&mut self, ctrs: &[(&Name, usize)]) {
+    maybe_grow(|| {
+      for child in self.children_mut() {
+        child.resugar_tagged_ctrs(ctrs);
+      }
+
+      let Term::Lam { tag: Tag::Static, pat, bod } = self else { return };
+      let Pattern::Var(Some(var)) = pat.as_ref() else { return };
+
+      let mut args = vec![];
+      let mut fun = bod.as_ref();
+      while let Term::App { tag: Tag::Static, fun: app_fun, arg } = fun {
+        args.push(arg.as_ref());
+        fun = app_fun;
+      }
+      let Term::Var { nam } = fun else { return };
+      let Some(Term::Num { val: Num::U24(tag) }) = args.pop() else { return };
+      let Some((ctr, arity)) = ctrs.get(*tag as usize) else { return };
+
+      if nam != var || args.len() != *arity || args.iter().any(|arg| arg.free_vars().contains_key(var)) {
+        return;
+      }
+      let args = args.into_iter().rev().cloned().collect::<Vec<_>>();
+      *self = Term::call(Term::r#ref(ctr), args);
+    })
+  }
+}
@@ -0,0 +1,229 @@
+use crate::{
+  fun::{Ctx, Definition, Name, Num, Pattern, Rule, Tag, Term},
+  maybe_grow,
+};
+use indexmap::IndexMap;
+use itertools::Itertools;
+
+/// The maximum number of specialized versions generated for each definition,
+/// so that calls with many different constants don't make the program grow without limit.
+pub const MAX_SPECIALIZATIONS: usize = 8;
+
+/// The parameters of a recursive definition, with the positions of the ones that are passed
+/// unchanged to every recursive call.
+struct StaticParams {
+  params: Vec<Name>,
+  statics: Vec<usize>,
+}
+
+/// The specializations needed so far, by the definition and the constant arguments they're for.
+struct Specializations<'a> {
+  statics: &'a IndexMap<Name, StaticParams>,
+  specs: IndexMap<(Name, Vec<(usize, Num)>), Name>,
+  counts: IndexMap<Name, usize>,
+}
+
+impl Ctx<'_> {
+  /// Specializes the recursive definitions that are called with constant arguments.
+  ///
+  /// A parameter of a recursive definition is static if the definition passes it unchanged to
+  /// all its recursive calls. A call that gives numbers to some static parameters is replaced with
+  /// a call to a new definition, `Foo__spec0`, where the numbers are substituted in the body and
+  /// the recursive calls don't pass them anymore. Then other passes, like `-Oconst-fold`, can
+  /// optimize the body with the constants.
+  ///
+  /// Example:
+  /// ```hvm
+  /// Pow = λb λe switch e { 0: 1; _: (* b (Pow b e-1)) }
+  /// main = (Pow 2 10)
+  ///
+  /// // Becomes
+  /// Pow__spec0 = λe switch e { 0: 1; _: (* 2 (Pow__spec0 e-1)) }
+  /// main = (Pow__spec0 10)
+  /// ```
+  ///
+  /// Each definition gets at most [`MAX_SPECIALIZATIONS`] specialized versions, the other calls are kept.
+  /// With a verbose diagnostics config, the generated definitions are listed in a note of the diagnostics.
+  /// Must run after the rules of the definitions are desugared into matches.
+  pub fn specialize(&mut self) {
+    let statics = self
+      .book
+      .defs
+      .values()
+      .filter(|def| !def.builtin)
+      .filter_map(|def| Some((def.name.clone(), def.rule().body.static_params(&def.name)?)))
+      .collect::<IndexMap<_, _>>();
+    if statics.is_empty() {
+      return;
+    }
+
+    let mut specs = Specializations { statics: &statics, specs: IndexMap::new(), counts: IndexMap::new() };
+    for def in self.book.defs.values_mut().filter(|def| !def.builtin) {
+      for rule in &mut def.rules {
+        rule.body.specialize_calls(&mut specs);
+      }
+    }
+
+    // Creating a specialized definition can make new constant calls to other definitions.
+    let mut done = 0;
+    while let Some(((def_name, args), spec_name)) = specs.specs.get_index(done) {
+      let (def_name, args, spec_name) = (def_name.clone(), args.clone(), spec_name.clone());
+      let mut body = self.book.defs[&def_name].rule().body.clone();
+      body.specialize_def(&def_name, &spec_name, &statics[&def_name].params, &args);
+      body.specialize_calls(&mut specs);
+      let rules = vec![Rule { pats: vec![], body }];
+      self.book.defs.insert(spec_name.clone(), Definition { name: spec_name, rules, builtin: false });
+      done += 1;
+    }
+
+    if self.info.config.verbose && !specs.specs.is_empty() {
+      let listed = specs
+        .specs
+        .iter()
+        .map(|((def_name, args), spec_name)| {
+          let params = &statics[def_name].params;
+          let args = args.iter().map(|(idx, val)| format!("{} = {}", params[*idx], Term::Num { val: *val }));
+          format!("  {spec_name}: {def_name} with {}", args.format(", "))
+        })
+        .join("\n");
+      self.info.add_note(format!("Generated {} specialized definitions:\n{listed}", specs.specs.len()));
+    }
+  }
+}
+
+impl Specializations<'_> {
+  /// The name of the specialization of `def_name` for the given constant arguments,
+  /// or `None` if the definition already has too many specializations.
+  fn get(&mut self, def_name: &Name, args: Vec<(usize, Num)>) -> Option<Name> {
+    let key = (def_name.clone(), args);
+    if let Some(spec_name) = self.specs.get(&key) {
+      return Some(spec_name.clone());
+    }
+    let count = self.counts.entry(def_name.clone()).or_default();
+    if *count >= MAX_SPECIALIZATIONS {
+      return None;
+    }
+    let spec_name = Name::new(format!("{def_name}__spec{count}"));
+    *count += 1;
+    self.specs.insert(key, spec_name.clone());
+    Some(spec_name)
+  }
+}
+
+impl Term {
+  /// If this is the body of a recursive definition, returns its parameters and which of them are static.
+  fn static_params(&self, def_name: &Name) -> Option<StaticParams> {
+    let mut params = vec![];
+    let mut body = self;
+    while let Term::Lam { tag: Tag::Static, pat, bod } = body {
+      let Pattern::Var(Some(param)) = pat.as_ref() else { break };
+      params.push(param.clone());
+      body = bod;
+    }
+    if params.is_empty() || !params.iter().all_unique() || !body.references().contains(def_name) {
+      return None;
+    }
+    let statics = (0..params.len()).filter(|&idx| body.passes_param(def_name, idx, Some(&params[idx])));
+    let statics = statics.collect::<Vec<_>>();
+    (!statics.is_empty()).then_some(StaticParams { params, statics })
+  }
+
+  /// Whether every reference to `def_name` in this term is a call that passes `param` as its argument `idx`.
+  /// `param` is `None` where it's shadowed by another variable.
+  fn passes_param(&self, def_name: &Name, idx: usize, param: Option<&Name>) -> bool {
+    maybe_grow(|| {
+      let (head, args) = self.call_spine();
+      match head {
+        Term::Ref { nam } if nam == def_name => {
+          let passes =
+            matches!((args.get(idx), param), (Some(Term::Var { nam }), Some(param)) if nam == param);
+          passes && args.iter().all(|arg| arg.passes_param(def_name, idx, param))
+        }
+        _ => self.children_with_binds().all(|(child, mut binds)| {
+          let param = param.filter(|param| !binds.any(|bind| bind.as_ref() == Some(*param)));
+          child.passes_param(def_name, idx, param)
+        }),
+      }
+    })
+  }
+
+  /// The function being applied by this term and its arguments, in order.
+  fn call_spine(&self) -> (&Term, Vec<&Term>) {
+    let mut args = vec![];
+    let mut head = self;
+    while let Term::App { tag: Tag::Static, fun, arg } = head {
+      args.push(arg.as_ref());
+      head = fun;
+    }
+    args.reverse();
+    (head, args)
+  }
+
+  /// Replaces the calls with constants as static arguments with calls to specialized definitions.
+  fn specialize_calls(&mut self, specs: &mut Specializations) {
+    maybe_grow(|| {
+      if let Some(call) = self.specialized_call(specs) {
+        *self = call;
+      }
+      for child in self.children_mut() {
+        child.specialize_calls(specs);
+      }
+    })
+  }
+
+  fn specialized_call(&self, specs: &mut Specializations) -> Option<Term> {
+    let (Term::Ref { nam: def_name }, args) = self.call_spine() else { return None };
+    let statics = specs.statics;
+    let statics = &statics.get(def_name)?.statics;
+    let consts = statics
+      .iter()
+      .filter_map(|&idx| match args.get(idx) {
+        Some(Term::Num { val }) => Some((idx, *val)),
+        _ => None,
+      })
+      .collect::<Vec<_>>();
+    if consts.is_empty() {
+      return None;
+    }
+    let spec_name = specs.get(def_name, consts.clone())?;
+    let args = args.into_iter().enumerate().filter(|(idx, _)| !consts.iter().any(|(i, _)| i == idx));
+    Some(Term::call(Term::Ref { nam: spec_name }, args.map(|(_, arg)| arg.clone())))
+  }
+
+  /// Turns the body of `def_name` into the body of its specialization for the constant `args`.
+  fn specialize_def(&mut self, def_name: &Name, spec_name: &Name, params: &[Name], args: &[(usize, Num)]) {
+    let is_const = |idx: usize| args.iter().any(|(i, _)| *i == idx);
+    self.replace_recursive_calls(def_name, spec_name, &is_const);
+
+    let mut body = std::mem::take(self);
+    for _ in params {
+      let Term::Lam { bod, .. } = body else { unreachable!() };
+      body = *bod;
+    }
+    for (idx, val) in args {
+      body.subst(&params[*idx], &Term::Num { val: *val });
+    }
+    let kept = params.iter().enumerate().filter(|(idx, _)| !is_const(*idx)).map(|(_, nam)| Some(nam.clone()));
+    *self = Term::rfold_lams(body, kept);
+  }
+
+  /// Replaces the recursive calls with calls to the specialization, without the constant arguments.
+  fn replace_recursive_calls(
+    &mut self,
+    def_name: &Name,
+    spec_name: &Name,
+    is_const: &impl Fn(usize) -> bool,
+  ) {
+    maybe_grow(|| {
+      if matches!(self.call_spine().0, Term::Ref { nam } if nam == def_name) {
+        let (_, args) = self.call_spine();
+        let args = args.into_iter().enumerate().filter(|(idx, _)| !is_const(*idx));
+        let args = args.map(|(_, arg)| arg.clone()).collect::<Vec<_>>();
+        *self = Term::call(Term::Ref { nam: spec_name.clone() }, args);
+      }
+      for child in self.children_mut() {
+        child.replace_recursive_calls(def_name, spec_name, is_const);
+      }
+    })
+  }
+}
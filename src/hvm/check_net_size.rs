@@ -4,14 +4,19 @@ use hvm::ast::{Book, Net, Tree};
 
 pub const MAX_NET_SIZE: usize = 64;
 
-pub fn check_net_sizes(book: &Book, diagnostics: &mut Diagnostics) -> Result<(), Diagnostics> {
+/// Reports the definitions whose nets have more than `max_size` nodes.
+pub fn check_net_sizes(
+  book: &Book,
+  max_size: usize,
+  diagnostics: &mut Diagnostics,
+) -> Result<(), Diagnostics> {
   diagnostics.start_pass();
 
   for (name, net) in &book.defs {
     let nodes = count_nodes(net);
-    if nodes > MAX_NET_SIZE {
+    if nodes > max_size {
       diagnostics.add_rule_error(
-        format!("Definition is too large for hvm (size={nodes}, max size={MAX_NET_SIZE}). Please break it into smaller pieces."),
+        format!("Definition is too large for hvm (size={nodes}, max size={max_size}). Please break it into smaller pieces."),
         Name::new(name),
       );
     }
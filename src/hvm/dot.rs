@@ -0,0 +1,122 @@
+use super::display_hvm_numb;
+use crate::fun::display::DisplayFn;
+use hvm::ast::{Book, Net, Tree};
+use std::{collections::HashMap, fmt::Write};
+
+/// Renders each net of the book as a Graphviz DOT graph,
+/// with one subgraph per definition.
+///
+/// Agents become nodes and wires become edges. The auxiliary ports of an agent are
+/// numbered at the end of their edges, while unnumbered ends are principal ports.
+/// Redexes are drawn as bold red edges between two principal ports.
+pub fn display_hvm_book_dot(book: &Book) -> impl std::fmt::Display + '_ {
+  DisplayFn(|f| {
+    writeln!(f, "graph nets {{")?;
+    writeln!(f, "  node [fontname=\"monospace\"];")?;
+    for (idx, (nam, net)) in book.defs.iter().enumerate() {
+      let mut dot = DotNet::new(format!("d{idx}"));
+      dot.net(net);
+      writeln!(f, "  subgraph \"cluster_{}\" {{", escape(nam))?;
+      writeln!(f, "    label = \"@{}\";", escape(nam))?;
+      f.write_str(&dot.out)?;
+      writeln!(f, "  }}")?;
+    }
+    writeln!(f, "}}")
+  })
+}
+
+/// A port of a node, given by the node's id and the number of the auxiliary port,
+/// or `None` for the principal port.
+type Port = (usize, Option<u8>);
+
+enum End<'a> {
+  Port(Port),
+  Var(&'a str),
+}
+
+struct DotNet<'a> {
+  prefix: String,
+  node_count: usize,
+  /// The ends of the wires that were connected to only one of the occurrences of a variable.
+  /// Also keeps the attributes of the wire, so that redexes with a variable are still highlighted.
+  vars: HashMap<&'a str, (End<'a>, &'static str)>,
+  out: String,
+}
+
+impl<'a> DotNet<'a> {
+  fn new(prefix: String) -> Self {
+    Self { prefix, node_count: 0, vars: HashMap::new(), out: String::new() }
+  }
+
+  fn net(&mut self, net: &'a Net) {
+    let root = self.node("root", "plaintext");
+    let end = self.tree(&net.root);
+    self.wire(End::Port((root, None)), end, "");
+    for (pri, a, b) in &net.rbag {
+      let a = self.tree(a);
+      let b = self.tree(b);
+      let attrs = if *pri { "style=bold, color=red, label=\"!\"" } else { "style=bold, color=red" };
+      self.wire(a, b, attrs);
+    }
+  }
+
+  /// Adds the nodes of a tree, returning the end of the wire connected to its principal port.
+  fn tree(&mut self, tree: &'a Tree) -> End<'a> {
+    let (label, fst, snd) = match tree {
+      Tree::Var { nam } => return End::Var(nam),
+      Tree::Ref { nam } => (format!("@{nam}"), None, None),
+      Tree::Era => ("ERA".to_string(), None, None),
+      Tree::Num { val } => (display_hvm_numb(val).to_string(), None, None),
+      Tree::Con { fst, snd } => ("CON".to_string(), Some(fst), Some(snd)),
+      Tree::Dup { fst, snd } => ("DUP".to_string(), Some(fst), Some(snd)),
+      Tree::Opr { fst, snd } => ("OPR".to_string(), Some(fst), Some(snd)),
+      Tree::Swi { fst, snd } => ("SWI".to_string(), Some(fst), Some(snd)),
+    };
+    let shape = if fst.is_some() { "triangle" } else { "box" };
+    let node = self.node(&label, shape);
+    for (port, child) in [(1, fst), (2, snd)] {
+      if let Some(child) = child {
+        let end = self.tree(child);
+        self.wire(End::Port((node, Some(port))), end, "");
+      }
+    }
+    End::Port((node, None))
+  }
+
+  fn node(&mut self, label: &str, shape: &str) -> usize {
+    let id = self.node_count;
+    self.node_count += 1;
+    writeln!(self.out, "    {}n{id} [label=\"{}\", shape={shape}];", self.prefix, escape(label)).unwrap();
+    id
+  }
+
+  /// Connects two ends, waiting for the other occurrence of a variable before drawing its edge.
+  fn wire(&mut self, a: End<'a>, b: End<'a>, attrs: &'static str) {
+    match (a, b) {
+      (End::Var(nam), end) | (end, End::Var(nam)) => match self.vars.remove(nam) {
+        Some((other, other_attrs)) => {
+          self.wire(other, end, if attrs.is_empty() { other_attrs } else { attrs })
+        }
+        None => {
+          self.vars.insert(nam, (end, attrs));
+        }
+      },
+      (End::Port((a, a_port)), End::Port((b, b_port))) => {
+        let mut attrs = attrs.to_string();
+        for (side, port) in [("taillabel", a_port), ("headlabel", b_port)] {
+          if let Some(port) = port {
+            let sep = if attrs.is_empty() { "" } else { ", " };
+            write!(attrs, "{sep}{side}=\"{port}\"").unwrap();
+          }
+        }
+        let prefix = &self.prefix;
+        let attrs = if attrs.is_empty() { attrs } else { format!(" [{attrs}]") };
+        writeln!(self.out, "    {prefix}n{a} -- {prefix}n{b}{attrs};").unwrap();
+      }
+    }
+  }
+}
+
+fn escape(s: &str) -> String {
+  s.replace('\\', "\\\\").replace('"', "\\\"")
+}
@@ -0,0 +1,400 @@
+//! Evaluates an HVM book in this process with the interpreter of the `hvm` crate, so that the
+//! evaluation can be observed: the interactions can be printed as they are performed, and
+//! counted for the Bend definition they are performed on behalf of.
+//!
+//! A call interaction is attributed to the definition being called. Any other interaction is
+//! attributed to the definition that created the nodes taking part in it, or if there are none,
+//! to the definition of the interaction that created the redex. Nodes and redexes created by an
+//! interaction belong to the same definition as the interaction.
+//!
+//! When measuring the coverage, a match arm counts as executed each time the definition it was
+//! moved to is called, except when the call only copies the definition.
+
+use super::{display_hvm_numb, hvm_def_origins};
+use crate::{
+  fun::{CoverageArm, Name},
+  RunStats, ENTRY_POINT,
+};
+use hvm::{
+  ast,
+  hvm::{
+    Book, GNet, Pair, Port, Rule, TMem, ANNI, CALL, COMM, CON, DUP, ERA, ERAS, LINK, NONE, NUM, OPER, OPR,
+    REF, ROOT, SWI, SWIT, VAR, VOID,
+  },
+};
+use indexmap::IndexMap;
+use itertools::Itertools;
+use std::{
+  collections::{HashMap, VecDeque},
+  time::{Duration, Instant},
+};
+
+/// Size of the node and variable buffers, the same as the ones of `hvm run`.
+const NET_SIZE: usize = 1 << 29;
+
+/// How many interactions are performed between checks of the time limit.
+const TIMEOUT_CHECK_INTERVAL: u64 = 1 << 16;
+
+/// How deep the trees of the ports taking part in an interaction are shown when tracing.
+const TRACE_DEPTH: usize = 2;
+
+#[derive(Clone, Debug, Default)]
+pub struct InterpreterOpts {
+  /// Maximum wall-clock time the evaluation is allowed to take.
+  pub timeout: Option<Duration>,
+  /// Counts the interactions performed by each definition.
+  pub profile: bool,
+  /// Prints each interaction before performing it.
+  pub trace: bool,
+  /// Maximum number of interactions printed when tracing, or all of them if `None`.
+  pub trace_limit: Option<u64>,
+  /// The instrumented match arms, whose executions are counted.
+  pub coverage: Option<Vec<CoverageArm>>,
+  /// Performs the oldest redexes first, instead of the newest ones like HVM does.
+  /// Slower, but computes the parts of the result in the order they are built, even for programs
+  /// that never finish, like ones building infinite lists.
+  pub breadth_first: bool,
+}
+
+/// The interactions performed while evaluating a book, attributed to the definitions they come from.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Profile {
+  /// The number of interactions of each definition, from the most to the least interactions.
+  pub definitions: Vec<(Name, u64)>,
+}
+
+impl std::fmt::Display for Profile {
+  /// Displays the profile as a table of definitions, interactions and percentage of the total.
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let rows = &self.definitions;
+    let total = rows.iter().map(|(_, itrs)| itrs).sum::<u64>().max(1);
+    let nam_width = rows.iter().map(|(nam, _)| nam.len()).chain(["Definition".len()]).max().unwrap();
+    let itrs_width = rows.iter().map(|(_, itrs)| itrs.to_string().len()).chain(["Interactions".len()]).max();
+    let itrs_width = itrs_width.unwrap();
+    writeln!(f, "{:<nam_width$}  {:>itrs_width$}  {:>10}", "Definition", "Interactions", "Percentage")?;
+    for &(ref nam, itrs) in rows {
+      let percentage = itrs as f64 * 100.0 / total as f64;
+      writeln!(f, "{:<nam_width$}  {itrs:>itrs_width$}  {percentage:>9.2}%", nam.to_string())?;
+    }
+    Ok(())
+  }
+}
+
+/// The match arms of a program that were executed during an evaluation.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Coverage {
+  /// Each instrumented arm with the number of times it was executed, in the order of the program,
+  /// or `None` for the arms whose executions couldn't be measured.
+  pub arms: Vec<(CoverageArm, Option<u64>)>,
+}
+
+impl Coverage {
+  /// Adds the executions of another evaluation of the same program, like the other tests of a file.
+  pub fn merge(&mut self, other: &Coverage) {
+    for (arm, hits) in &other.arms {
+      match self.arms.iter_mut().find(|(this, _)| this == arm) {
+        Some((_, Some(this_hits))) => *this_hits += hits.unwrap_or(0),
+        Some((_, None)) => {}
+        None => self.arms.push((arm.clone(), *hits)),
+      }
+    }
+  }
+}
+
+impl std::fmt::Display for Coverage {
+  /// Displays how many arms were executed and lists the ones that weren't, grouped by definition,
+  /// and then the ones that couldn't be measured.
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let total = self.arms.iter().filter(|(_, hits)| hits.is_some()).count();
+    let executed = self.arms.iter().filter(|(_, hits)| hits.is_some_and(|hits| hits > 0)).count();
+    let percentage = executed as f64 * 100.0 / total.max(1) as f64;
+    writeln!(f, "Coverage: {executed} of {total} match arms executed ({percentage:.2}%)")?;
+    let missed = self.arms.iter().filter(|(_, hits)| *hits == Some(0)).map(|(arm, _)| arm);
+    display_arms(f, "Arms never executed:", missed)?;
+    let unmeasured = self.arms.iter().filter(|(_, hits)| hits.is_none()).map(|(arm, _)| arm);
+    display_arms(
+      f,
+      "Arms not measured, since they use variables bound outside of them or unscoped lambdas:",
+      unmeasured,
+    )
+  }
+}
+
+/// Lists the given arms under the title, grouped by definition, telling the matches of a
+/// definition apart by their position. Displays nothing if there are no arms.
+fn display_arms<'a>(
+  f: &mut std::fmt::Formatter<'_>,
+  title: &str,
+  arms: impl Iterator<Item = &'a CoverageArm>,
+) -> std::fmt::Result {
+  let mut grouped = IndexMap::<&Name, Vec<&CoverageArm>>::new();
+  for arm in arms {
+    grouped.entry(&arm.def_name).or_default().push(arm);
+  }
+  if !grouped.is_empty() {
+    writeln!(f, "{title}")?;
+  }
+  for (def_name, arms) in grouped {
+    writeln!(f, "  {def_name}:")?;
+    for arm in arms {
+      writeln!(f, "    {} (match {})", arm.arm, arm.match_idx)?;
+    }
+  }
+  Ok(())
+}
+
+/// Evaluates the book in this process and returns the resulting net together with
+/// the stats of the evaluation, which include the interactions performed by each definition
+/// if profiling.
+///
+/// Unlike running the book with HVM, the IO actions of the program are not performed.
+pub fn interpret_hvm_book(book: &ast::Book, opts: &InterpreterOpts) -> Result<(ast::Net, RunStats), String> {
+  let mut interpreter = Interpreter::new(book, opts)?;
+  interpreter.run(None)?;
+  let stats = interpreter.stats();
+  let Some(result) = ast::Net::readback(&interpreter.net, &interpreter.book) else {
+    return Err("Failed to read back the result of the program.".to_string());
+  };
+  Ok((result, stats))
+}
+
+/// An evaluation of a book in this process that can be paused between interactions,
+/// so that the result can be inspected while it's still being computed.
+pub struct Interpreter {
+  pub(crate) book: Book,
+  pub(crate) net: GNet<'static>,
+  tm: TMem,
+  opts: InterpreterOpts,
+  /// The Bend definitions that each HVM definition comes from.
+  origins: Vec<String>,
+  /// The definition that created the node at each location.
+  owners: Vec<u32>,
+  /// The low and high priority redexes, each with the definition that created it.
+  lo_redexes: VecDeque<(Pair, u32)>,
+  hi_redexes: VecDeque<(Pair, u32)>,
+  counts: Vec<u64>,
+  /// The number of times each definition was called, without counting the copies.
+  calls: Vec<u64>,
+  interactions: u64,
+  /// The time spent performing interactions, not counting the pauses.
+  elapsed: Duration,
+}
+
+impl Interpreter {
+  /// Prepares the evaluation of the entrypoint of the book, without performing any interaction.
+  pub fn new(book: &ast::Book, opts: &InterpreterOpts) -> Result<Self, String> {
+    if !book.defs.contains_key(ENTRY_POINT) {
+      return Err(format!("Can't evaluate a book without a '{ENTRY_POINT}' definition in this process."));
+    }
+    let book = book.build();
+    // The entrypoint is always the first definition of a built book.
+    let main_id = 0;
+    let origins = book.defs.iter().map(|def| hvm_def_origins(&def.name).join(", ")).collect::<Vec<_>>();
+
+    let net = GNet::new(NET_SIZE, NET_SIZE);
+    let tm = TMem::new(0, 1);
+    net.vars_create(ROOT.get_val() as usize, NONE);
+
+    Ok(Interpreter {
+      counts: vec![0; book.defs.len()],
+      calls: vec![0; book.defs.len()],
+      book,
+      net,
+      tm,
+      opts: opts.clone(),
+      origins,
+      owners: vec![],
+      lo_redexes: VecDeque::from([(Pair::new(Port::new(REF, main_id), ROOT), main_id)]),
+      hi_redexes: VecDeque::new(),
+      interactions: 0,
+      elapsed: Duration::ZERO,
+    })
+  }
+
+  /// Performs at most `limit` interactions, or all of them if `None`.
+  /// Returns whether the evaluation has finished.
+  pub fn run(&mut self, limit: Option<u64>) -> Result<bool, String> {
+    let start = Instant::now();
+    let res = self.run_interactions(limit);
+    self.elapsed += start.elapsed();
+    res
+  }
+
+  fn run_interactions(&mut self, limit: Option<u64>) -> Result<bool, String> {
+    let Interpreter { book, net, tm, opts, origins, owners, lo_redexes, hi_redexes, counts, calls, .. } =
+      self;
+    let deadline = opts.timeout.map(|timeout| Instant::now() + timeout.saturating_sub(self.elapsed));
+    let end = limit.map(|limit| self.interactions + limit);
+    while end.map_or(true, |end| self.interactions < end) {
+      let next = match hi_redexes.pop_back() {
+        Some(redex) => Some(redex),
+        None if opts.breadth_first => lo_redexes.pop_front(),
+        None => lo_redexes.pop_back(),
+      };
+      let Some((redex, redex_owner)) = next else {
+        return Ok(true);
+      };
+      let mut a = redex.get_fst();
+      let mut b = redex.get_snd();
+      // Same as `TMem::interact`, but keeping track of which definition each interaction belongs to.
+      let mut rule = Port::get_rule(a, b);
+      if a.get_tag() == REF && b == ROOT {
+        rule = CALL;
+      } else if Port::should_swap(a, b) {
+        std::mem::swap(&mut a, &mut b);
+      }
+
+      let owner = if a.get_tag() == REF {
+        a.get_val() & 0xFFFFFFF
+      } else {
+        let node = [a, b].into_iter().find(Port::is_nod);
+        node.and_then(|port| owners.get(port.get_val() as usize).copied()).unwrap_or(redex_owner)
+      };
+      let new_nodes = match rule {
+        CALL if b.get_tag() == DUP && book.defs[a.get_val() as usize & 0xFFFFFFF].safe => 0,
+        CALL => book.defs[a.get_val() as usize & 0xFFFFFFF].node.len(),
+        COMM => 4,
+        OPER => 1,
+        SWIT => 2,
+        _ => 0,
+      };
+
+      if opts.trace && rule != LINK {
+        match opts.trace_limit {
+          Some(limit) if self.interactions == limit => {
+            println!("... (stopped tracing after {limit} interactions)")
+          }
+          Some(limit) if self.interactions > limit => {}
+          _ => {
+            let (a, b) = (display_port(net, book, a, TRACE_DEPTH), display_port(net, book, b, TRACE_DEPTH));
+            let origin = &origins[owner as usize];
+            println!("{:>6} {} {a} ~ {b}  [{origin}]", self.interactions + 1, rule_name(rule));
+          }
+        }
+      }
+
+      let success = match rule {
+        LINK => tm.interact_link(net, a, b),
+        CALL => tm.interact_call(net, a, b, book),
+        VOID => tm.interact_void(net, a, b),
+        ERAS => tm.interact_eras(net, a, b),
+        ANNI => tm.interact_anni(net, a, b),
+        COMM => tm.interact_comm(net, a, b),
+        OPER => tm.interact_oper(net, a, b),
+        SWIT => tm.interact_swit(net, a, b),
+        _ => unreachable!(),
+      };
+      if !success {
+        return Err("The program ran out of memory.".to_string());
+      }
+      lo_redexes.extend(tm.rbag.lo.drain(..).map(|redex| (redex, owner)));
+      hi_redexes.extend(tm.rbag.hi.drain(..).map(|redex| (redex, owner)));
+      if rule == LINK {
+        continue;
+      }
+
+      self.interactions += 1;
+      counts[owner as usize] += 1;
+      if rule == CALL && b.get_tag() != DUP {
+        calls[a.get_val() as usize & 0xFFFFFFF] += 1;
+      }
+      for &loc in &tm.nloc[..new_nodes] {
+        if loc >= owners.len() {
+          owners.resize(loc + 1, 0);
+        }
+        owners[loc] = owner;
+      }
+
+      if self.interactions % TIMEOUT_CHECK_INTERVAL == 0
+        && deadline.is_some_and(|deadline| Instant::now() >= deadline)
+      {
+        let timeout = opts.timeout.unwrap();
+        return Err(format!("The program exceeded the time limit of {timeout:?} and was stopped."));
+      }
+    }
+    Ok(lo_redexes.is_empty() && hi_redexes.is_empty())
+  }
+
+  /// The stats of the interactions performed so far.
+  pub fn stats(&self) -> RunStats {
+    let profile = self.opts.profile.then(|| {
+      let mut definitions = HashMap::<&str, u64>::new();
+      for (origin, &count) in self.origins.iter().zip(&self.counts) {
+        if count > 0 {
+          *definitions.entry(origin).or_default() += count;
+        }
+      }
+      let definitions = definitions
+        .into_iter()
+        .sorted_by(|(nam_a, itrs_a), (nam_b, itrs_b)| itrs_b.cmp(itrs_a).then(nam_a.cmp(nam_b)))
+        .map(|(nam, itrs)| (Name::new(nam), itrs))
+        .collect();
+      Profile { definitions }
+    });
+
+    let coverage = self.opts.coverage.as_ref().map(|arms| {
+      let calls = self.book.defs.iter().zip(&self.calls).map(|(def, &calls)| (def.name.as_str(), calls));
+      let calls = calls.collect::<HashMap<_, _>>();
+      // The arms of definitions that were pruned were never executed.
+      let hits = |nam: &Name| calls.get(nam.as_ref()).copied().unwrap_or(0);
+      let arms = arms.iter().map(|arm| (arm.clone(), arm.moved_to.as_ref().map(hits)));
+      Coverage { arms: arms.collect() }
+    });
+
+    RunStats {
+      interactions: Some(self.interactions),
+      leaks: None,
+      elapsed: Some(self.elapsed),
+      mips: Some(self.interactions as f64 / self.elapsed.as_secs_f64() / 1_000_000.0),
+      profile,
+      coverage,
+    }
+  }
+}
+
+fn rule_name(rule: Rule) -> &'static str {
+  match rule {
+    CALL => "CALL",
+    VOID => "VOID",
+    ERAS => "ERAS",
+    ANNI => "ANNI",
+    COMM => "COMM",
+    OPER => "OPER",
+    SWIT => "SWIT",
+    _ => "LINK",
+  }
+}
+
+/// Displays the tree of a port in the syntax of HVM, without modifying the net.
+/// The nodes deeper than `depth` are shown as `...`.
+fn display_port(net: &GNet, book: &Book, port: Port, depth: usize) -> String {
+  let mut port = port;
+  // Follows the variables that were already linked to something.
+  while port.get_tag() == VAR && port != ROOT {
+    let got = net.vars_load(port.get_val() as usize);
+    if got == NONE || got.0 == 0 {
+      break;
+    }
+    port = got;
+  }
+  match port.get_tag() {
+    _ if port == ROOT => "ROOT".to_string(),
+    VAR => format!("x{:x}", port.get_val()),
+    REF => format!("@{}", book.defs[port.get_val() as usize & 0xFFFFFFF].name),
+    ERA => "*".to_string(),
+    NUM => display_hvm_numb(&ast::Numb(port.get_val())).to_string(),
+    _ if depth == 0 => "...".to_string(),
+    tag => {
+      let node = net.node_load(port.get_val() as usize);
+      let fst = display_port(net, book, node.get_fst(), depth - 1);
+      let snd = display_port(net, book, node.get_snd(), depth - 1);
+      match tag {
+        CON => format!("({fst} {snd})"),
+        DUP => format!("{{{fst} {snd}}}"),
+        OPR => format!("$({fst} {snd})"),
+        SWI => format!("?({fst} {snd})"),
+        _ => unreachable!(),
+      }
+    }
+  }
+}
@@ -1,12 +1,21 @@
-use crate::{fun::display::DisplayFn, multi_iterator};
+use crate::{
+  fun::{display::DisplayFn, transform::definition_merge::MERGE_SEPARATOR, Name},
+  multi_iterator,
+};
 use hvm::ast::{Net, Tree};
+use itertools::Itertools;
 
 pub mod add_recursive_priority;
 pub mod check_net_size;
+pub mod dot;
 pub mod eta_reduce;
 pub mod inline;
+pub mod interpreter;
 pub mod mutual_recursion;
+pub mod optimize_nets;
 pub mod prune;
+pub mod split_c;
+pub mod stream;
 
 pub fn tree_children(tree: &Tree) -> impl DoubleEndedIterator<Item = &Tree> + Clone {
   multi_iterator!(ChildrenIter { Zero, Two });
@@ -36,14 +45,44 @@ pub fn net_trees_mut(net: &mut Net) -> impl DoubleEndedIterator<Item = &mut Tree
   [&mut net.root].into_iter().chain(net.rbag.iter_mut().flat_map(|(_, fst, snd)| [fst, snd]))
 }
 
+/// Displays the definitions of an HVM book sorted by name,
+/// so the same program always generates the same output.
 pub fn display_hvm_book(book: &hvm::ast::Book) -> impl std::fmt::Display + '_ {
   DisplayFn(|f| {
     for (nam, def) in book.defs.iter() {
-      writeln!(f, "@{} = {}", nam, display_hvm_tree(&def.root))?;
-      for (pri, a, b) in def.rbag.iter() {
-        writeln!(f, "  &{}{} ~ {}", if *pri { "!" } else { " " }, display_hvm_tree(a), display_hvm_tree(b))?;
+      writeln!(f, "{}", display_hvm_def(nam, def))?;
+    }
+    Ok(())
+  })
+}
+
+/// Like [display_hvm_book], but precedes each definition with a comment
+/// naming the Bend definitions it was generated from.
+pub fn display_annotated_hvm_book(book: &hvm::ast::Book) -> impl std::fmt::Display + '_ {
+  DisplayFn(|f| {
+    for (nam, def) in book.defs.iter() {
+      let origins = hvm_def_origins(nam).map(|nam| format!("'{nam}'")).join(", ");
+      if origins == format!("'{nam}'") {
+        writeln!(f, "// From {origins}")?;
+      } else {
+        writeln!(f, "// Generated from {origins}")?;
       }
-      writeln!(f)?;
+      writeln!(f, "{}", display_hvm_def(nam, def))?;
+    }
+    Ok(())
+  })
+}
+
+/// The Bend definitions that the HVM definition with the given name was generated from.
+pub fn hvm_def_origins(nam: &str) -> impl Iterator<Item = Name> + '_ {
+  nam.split(MERGE_SEPARATOR).map(|nam| Name::new(nam).def_name_from_generated()).unique()
+}
+
+fn display_hvm_def<'a>(nam: &'a str, def: &'a Net) -> impl std::fmt::Display + 'a {
+  DisplayFn(move |f| {
+    writeln!(f, "@{} = {}", nam, display_hvm_tree(&def.root))?;
+    for (pri, a, b) in def.rbag.iter() {
+      writeln!(f, "  &{}{} ~ {}", if *pri { "!" } else { " " }, display_hvm_tree(a), display_hvm_tree(b))?;
     }
     Ok(())
   })
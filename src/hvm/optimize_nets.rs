@@ -0,0 +1,119 @@
+//! Peephole optimizations on the nets of the definitions, performing at compile time the
+//! interactions that only involve nodes of the same net.
+//!
+//! Interaction nets are strongly confluent, so performing an interaction of a definition's net
+//! before running the program gives the same result as the runtime performing it after expanding
+//! the definition, but saves that interaction on every expansion.
+//!
+//! The interactions performed are the ones between the two trees of a redex that don't create
+//! new nodes:
+//!
+//! ```txt
+//! & (a b) ~ (c d)   ~~~~>   & a ~ c  & b ~ d      (annihilation of lambdas and applications)
+//! & {a b} ~ {c d}   ~~~~>   & a ~ c  & b ~ d      (annihilation of duplications and superpositions)
+//! & * ~ (a b)       ~~~~>   & * ~ a  & * ~ b      (erasure, also of duplications)
+//! & {a b} ~ 123     ~~~~>   & a ~ 123  & b ~ 123  (copy of a number)
+//! & * ~ *           ~~~~>                         (erasure of an eraser or a number)
+//! ```
+//!
+//! A redex with a variable on one side only connects a wire, so it's removed by replacing the
+//! other occurrence of the variable with the tree on the other side:
+//!
+//! ```txt
+//! @f = (a b)  & x ~ (c d)  ...x...   ~~~~>   @f = (a b)  ...(c d)...
+//! ```
+//!
+//! Redexes with references, numeric operations or switches are left for the runtime, since they
+//! expand definitions or depend on the values computed by the program.
+
+use super::{net_trees_mut, tree_children, tree_children_mut};
+use crate::maybe_grow;
+use hvm::ast::{Net, Tree};
+
+/// Performs the interactions between the nodes of the net and removes the redexes that only
+/// connect a wire, until there are none left.
+pub fn optimize_hvm_net(net: &mut Net) {
+  loop {
+    let mut changed = false;
+
+    let mut rbag = vec![];
+    for (pri, a, b) in std::mem::take(&mut net.rbag) {
+      changed |= interact(pri, a, b, &mut rbag);
+    }
+    net.rbag = rbag;
+
+    let mut i = 0;
+    while i < net.rbag.len() {
+      let (_, a, b) = &net.rbag[i];
+      let link = match (a, b) {
+        (Tree::Var { nam }, other) | (other, Tree::Var { nam }) if !occurs(nam, other) => {
+          Some((nam.clone(), other.clone()))
+        }
+        _ => None,
+      };
+      if let Some((nam, tree)) = link {
+        let redex = net.rbag.remove(i);
+        if net_trees_mut(net).any(|t| substitute(t, &nam, &tree)) {
+          changed = true;
+          continue;
+        }
+        net.rbag.insert(i, redex);
+      }
+      i += 1;
+    }
+
+    if !changed {
+      break;
+    }
+  }
+}
+
+/// Performs the interaction of the redex `a ~ b`, if it's one of the ones done at compile time,
+/// adding the resulting redexes to `rbag`. Otherwise, adds the redex itself.
+/// Returns whether any interaction was performed.
+fn interact(pri: bool, a: Tree, b: Tree, rbag: &mut Vec<(bool, Tree, Tree)>) -> bool {
+  maybe_grow(|| match (a, b) {
+    (Tree::Con { fst: a1, snd: a2 }, Tree::Con { fst: b1, snd: b2 })
+    | (Tree::Dup { fst: a1, snd: a2 }, Tree::Dup { fst: b1, snd: b2 }) => {
+      interact(pri, *a1, *b1, rbag);
+      interact(pri, *a2, *b2, rbag);
+      true
+    }
+    (Tree::Era, Tree::Con { fst, snd } | Tree::Dup { fst, snd })
+    | (Tree::Con { fst, snd } | Tree::Dup { fst, snd }, Tree::Era) => {
+      interact(pri, Tree::Era, *fst, rbag);
+      interact(pri, Tree::Era, *snd, rbag);
+      true
+    }
+    (Tree::Dup { fst, snd }, num @ Tree::Num { .. }) | (num @ Tree::Num { .. }, Tree::Dup { fst, snd }) => {
+      interact(pri, *fst, num.clone(), rbag);
+      interact(pri, *snd, num, rbag);
+      true
+    }
+    (Tree::Era, Tree::Era | Tree::Num { .. }) | (Tree::Num { .. }, Tree::Era) => true,
+    (a, b) => {
+      rbag.push((pri, a, b));
+      false
+    }
+  })
+}
+
+/// Whether the variable `nam` appears in the tree.
+fn occurs(nam: &str, tree: &Tree) -> bool {
+  maybe_grow(|| match tree {
+    Tree::Var { nam: var } => var == nam,
+    _ => tree_children(tree).any(|child| occurs(nam, child)),
+  })
+}
+
+/// Replaces the occurrence of the variable `nam` in the tree with `value`.
+/// Returns whether it was found.
+fn substitute(tree: &mut Tree, nam: &str, value: &Tree) -> bool {
+  maybe_grow(|| match tree {
+    Tree::Var { nam: var } if var == nam => {
+      *tree = value.clone();
+      true
+    }
+    _ => tree_children_mut(tree).any(|child| substitute(child, nam, value)),
+  })
+}
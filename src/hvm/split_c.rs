@@ -0,0 +1,445 @@
+//! Splits the C program generated by `hvm gen-c` into several translation units.
+//!
+//! HVM generates a single file with its runtime followed by one `interact_call_<def>`
+//! function for each definition of the book. The runtime stays in the first unit, while
+//! the compiled definitions are distributed among all the units. The units other than the
+//! first include a header that declares what they use from the runtime.
+
+use std::collections::{HashMap, HashSet};
+
+/// The prefix of the names of the functions that HVM generates for the definitions.
+const COMPILED_DEF_PREFIX: &str = "interact_call_";
+
+/// A C program split into several translation units.
+#[derive(Debug)]
+pub struct SplitC {
+  /// The header included by all the units except the first.
+  pub header: String,
+  /// The translation units. The first one has the runtime and the `main` function.
+  pub units: Vec<String>,
+}
+
+/// Splits the C program generated by HVM into `parts` translation units.
+///
+/// `header_name` is the file name used to include the header from the units.
+/// Fails if the program doesn't have the layout of the C generated by HVM.
+pub fn split_c_program(code: &str, parts: usize, header_name: &str) -> Result<SplitC, String> {
+  let items = top_level_items(code);
+
+  let is_compiled_def = |item: &Item| match item.kind {
+    ItemKind::Function { name, is_static, .. } => !is_static && name.starts_with(COMPILED_DEF_PREFIX),
+    _ => false,
+  };
+  let Some(first) = items.iter().position(is_compiled_def) else {
+    return Err("Couldn't find the compiled definitions in the generated C code.".to_string());
+  };
+  let last = items.iter().rposition(is_compiled_def).unwrap();
+  if !items[first..=last].iter().all(is_compiled_def) {
+    return Err("The compiled definitions in the generated C code are not contiguous.".to_string());
+  }
+  let (prefix, rest) = items.split_at(first);
+  let (compiled, suffix) = rest.split_at(last - first + 1);
+  let chunks = split_evenly(compiled, parts.max(1));
+
+  let mut main_unit = String::new();
+  prefix.iter().chain(chunks[0]).for_each(|item| main_unit.push_str(item.text));
+  for item in chunks[1..].iter().copied().flatten() {
+    main_unit.push_str(&item.declaration());
+  }
+  suffix.iter().for_each(|item| main_unit.push_str(item.text));
+
+  let mut units = vec![main_unit];
+  for chunk in &chunks[1..] {
+    let mut unit = format!("#include \"{header_name}\"\n");
+    chunk.iter().for_each(|item| unit.push_str(item.text));
+    units.push(unit);
+  }
+
+  let header = make_header(prefix, compiled, suffix, header_name)?;
+  Ok(SplitC { header, units })
+}
+
+/// Builds a header with the runtime declarations that the compiled definitions need.
+///
+/// Everything before the definitions is kept, except that functions with external linkage
+/// become prototypes and global variables become `extern` declarations, so that they stay
+/// defined only in the first unit. Static variables are left out, since each unit would
+/// get its own copy of them. The static functions that are only defined after the
+/// compiled definitions are copied too, if the definitions use them.
+///
+/// Fails if a static function copied to the header uses a static variable, since the
+/// function would use a different copy of the variable in each unit.
+fn make_header<'a>(
+  prefix: &[Item<'a>],
+  compiled: &[Item<'a>],
+  suffix: &[Item<'a>],
+  header_name: &str,
+) -> Result<String, String> {
+  let static_vars = prefix
+    .iter()
+    .chain(suffix)
+    .filter(|item| matches!(item.kind, ItemKind::Declaration))
+    .filter(|item| matches!(declaration_kind(item.code()), DeclarationKind::StaticVariable))
+    .filter_map(|item| declared_name(item.code()))
+    .collect::<HashSet<_>>();
+  let copy_static_function = |item: &Item<'a>| -> Result<&'a str, String> {
+    let ItemKind::Function { name, signature, .. } = item.kind else { unreachable!() };
+    let params = parameter_names(signature);
+    let body = strip_comments(&item.code()[signature.len()..]);
+    let used_var = identifiers(&body).find(|id| static_vars.contains(id) && !params.contains(id));
+    match used_var {
+      Some(var) => Err(format!(
+        "The function '{name}' of the generated C code uses the static variable '{var}', so the code can't be split."
+      )),
+      None => Ok(item.text),
+    }
+  };
+
+  let guard = header_name.to_uppercase().replace(|c: char| !c.is_ascii_alphanumeric(), "_");
+  let mut header = format!("#ifndef {guard}\n#define {guard}\n");
+
+  let mut open_conditionals = 0;
+  for item in prefix {
+    match item.kind {
+      ItemKind::Directive => {
+        open_conditionals += conditional_nesting(item.code());
+        header.push_str(item.text);
+      }
+      ItemKind::Function { is_static: true, .. } => header.push_str(copy_static_function(item)?),
+      ItemKind::Function { is_static: false, .. } => header.push_str(&item.declaration()),
+      ItemKind::Declaration => match declaration_kind(item.code()) {
+        DeclarationKind::Keep => header.push_str(item.text),
+        DeclarationKind::StaticVariable => {}
+        DeclarationKind::GlobalVariable => {
+          let decl = item.code().split_once('=').map_or(item.code(), |(decl, _)| decl);
+          let decl = decl.trim_end().trim_end_matches(';');
+          header.push_str(&format!("{}extern {decl};", item.leading()));
+        }
+      },
+    }
+  }
+  // The definitions are usually inside an `#ifdef`, which was open when the prefix ended.
+  for _ in 0..open_conditionals {
+    header.push_str("\n#endif");
+  }
+  header.push('\n');
+
+  for item in compiled {
+    header.push_str(&item.declaration());
+  }
+
+  // Only the functions outside of conditional blocks are considered.
+  let mut nesting = open_conditionals;
+  let mut functions = HashMap::new();
+  for item in suffix {
+    match item.kind {
+      ItemKind::Directive => nesting += conditional_nesting(item.code()),
+      ItemKind::Function { name, .. } if nesting == 0 && name != "main" => {
+        functions.insert(name, item);
+      }
+      _ => {}
+    }
+  }
+
+  let mut used = HashSet::new();
+  let mut to_visit = compiled.iter().flat_map(|item| called_names(item.code())).collect::<Vec<_>>();
+  while let Some(name) = to_visit.pop() {
+    if let Some((&name, item)) = functions.get_key_value(name.as_str()) {
+      if used.insert(name) && matches!(item.kind, ItemKind::Function { is_static: true, .. }) {
+        to_visit.extend(called_names(item.code()));
+      }
+    }
+  }
+
+  let used_functions = suffix.iter().filter(|item| item.name().is_some_and(|name| used.contains(name)));
+  let (static_functions, extern_functions): (Vec<_>, Vec<_>) =
+    used_functions.partition(|item| matches!(item.kind, ItemKind::Function { is_static: true, .. }));
+  for item in extern_functions {
+    header.push_str(&item.declaration());
+  }
+  for item in static_functions {
+    header.push_str(copy_static_function(item)?);
+  }
+
+  header.push_str("\n\n#endif\n");
+  Ok(header)
+}
+
+/// Splits the items into `parts` contiguous chunks of similar sizes.
+fn split_evenly<'a, 'b>(items: &'b [Item<'a>], parts: usize) -> Vec<&'b [Item<'a>]> {
+  let total = items.iter().map(|item| item.text.len()).sum::<usize>();
+  let mut chunks = Vec::with_capacity(parts);
+  let mut start = 0;
+  let mut size = 0;
+  for (i, item) in items.iter().enumerate() {
+    size += item.text.len();
+    // Each chunk ends once the chunks so far hold their share of the total size.
+    if chunks.len() + 1 < parts && size * parts >= total * (chunks.len() + 1) {
+      chunks.push(&items[start..=i]);
+      start = i + 1;
+    }
+  }
+  chunks.push(&items[start..]);
+  while chunks.len() < parts {
+    chunks.push(&[]);
+  }
+  chunks
+}
+
+/// A top-level item of a C file.
+#[derive(Debug)]
+struct Item<'a> {
+  text: &'a str,
+  /// Where the item starts after the whitespace and comments that precede it.
+  code_start: usize,
+  kind: ItemKind<'a>,
+}
+
+#[derive(Debug)]
+enum ItemKind<'a> {
+  /// A preprocessor directive.
+  Directive,
+  /// A function definition.
+  Function { name: &'a str, signature: &'a str, is_static: bool },
+  /// Anything else ending with a `;`, like variables, types and prototypes.
+  Declaration,
+}
+
+enum DeclarationKind {
+  Keep,
+  StaticVariable,
+  GlobalVariable,
+}
+
+impl<'a> Item<'a> {
+  fn leading(&self) -> &'a str {
+    &self.text[..self.code_start]
+  }
+
+  fn code(&self) -> &'a str {
+    &self.text[self.code_start..]
+  }
+
+  fn name(&self) -> Option<&'a str> {
+    match self.kind {
+      ItemKind::Function { name, .. } => Some(name),
+      _ => None,
+    }
+  }
+
+  /// The prototype of a function, or the item itself.
+  fn declaration(&self) -> String {
+    match self.kind {
+      ItemKind::Function { signature, .. } => format!("{}{};", self.leading(), signature.trim_end()),
+      _ => self.text.to_string(),
+    }
+  }
+}
+
+/// Splits C code into its top-level items, keeping all of the text.
+fn top_level_items(code: &str) -> Vec<Item<'_>> {
+  let bytes = code.as_bytes();
+  let mut items = Vec::new();
+  let mut start = 0;
+  let mut code_start = None;
+  let mut body_start = None;
+  let mut depth = 0usize;
+  let mut i = 0;
+
+  while i < bytes.len() {
+    match bytes[i] {
+      b'/' if bytes.get(i + 1) == Some(&b'/') => {
+        i = code[i..].find('\n').map_or(bytes.len(), |end| i + end);
+        continue;
+      }
+      b'/' if bytes.get(i + 1) == Some(&b'*') => {
+        i = code[i + 2..].find("*/").map_or(bytes.len(), |end| i + 2 + end + 2);
+        continue;
+      }
+      b'#' if code_start.is_none() => {
+        let end = directive_end(code, i);
+        items.push(Item { text: &code[start..end], code_start: i - start, kind: ItemKind::Directive });
+        start = end;
+        i = end;
+        continue;
+      }
+      c if c.is_ascii_whitespace() => {
+        i += 1;
+        continue;
+      }
+      _ => {}
+    }
+
+    let item_start = *code_start.get_or_insert(i);
+    match bytes[i] {
+      b'"' | b'\'' => {
+        i = literal_end(bytes, i);
+        continue;
+      }
+      b'{' => {
+        if depth == 0 && body_start.is_none() {
+          body_start = Some(i);
+        }
+        depth += 1;
+      }
+      b'(' | b'[' => depth += 1,
+      b')' | b']' => depth = depth.saturating_sub(1),
+      b'}' => {
+        depth = depth.saturating_sub(1);
+        let signature = body_start.map(|body_start| &code[item_start..body_start]);
+        if let Some(kind) = signature.filter(|_| depth == 0).and_then(function_kind) {
+          items.push(Item { text: &code[start..=i], code_start: item_start - start, kind });
+          (start, code_start, body_start) = (i + 1, None, None);
+        }
+      }
+      b';' if depth == 0 => {
+        items.push(Item {
+          text: &code[start..=i],
+          code_start: item_start - start,
+          kind: ItemKind::Declaration,
+        });
+        (start, code_start, body_start) = (i + 1, None, None);
+      }
+      _ => {}
+    }
+    i += 1;
+  }
+
+  if start < code.len() {
+    // Trailing whitespace and comments, or an unfinished item that is kept as is.
+    let code_start = code_start.unwrap_or(code.len()) - start;
+    items.push(Item { text: &code[start..], code_start, kind: ItemKind::Directive });
+  }
+  items
+}
+
+/// Returns the kind of an item if its text before the body is the signature of a function.
+fn function_kind(signature: &str) -> Option<ItemKind<'_>> {
+  let (before_args, _) = signature.split_once('(')?;
+  if !signature.trim_end().ends_with(')') || before_args.contains('=') {
+    return None;
+  }
+  let name = before_args.trim_end().rsplit(|c: char| !is_ident_char(c)).next()?;
+  let is_static = before_args.split_whitespace().any(|word| word == "static");
+  Some(ItemKind::Function { name, signature, is_static })
+}
+
+fn declaration_kind(decl: &str) -> DeclarationKind {
+  let first_word = decl.split(|c: char| !is_ident_char(c)).next().unwrap_or_default();
+  let is_prototype = match (decl.find('('), decl.find('=')) {
+    (Some(paren), Some(eq)) => paren < eq,
+    (Some(_), None) => true,
+    (None, _) => false,
+  };
+  match first_word {
+    "typedef" | "extern" | "struct" | "union" | "enum" => DeclarationKind::Keep,
+    _ if is_prototype => DeclarationKind::Keep,
+    "static" => DeclarationKind::StaticVariable,
+    _ => DeclarationKind::GlobalVariable,
+  }
+}
+
+/// How much a directive changes the nesting of conditional blocks.
+fn conditional_nesting(directive: &str) -> isize {
+  let name = directive.trim_start_matches(['#', ' ', '\t']);
+  if name.starts_with("if") {
+    1
+  } else if name.starts_with("endif") {
+    -1
+  } else {
+    0
+  }
+}
+
+/// The name declared by a variable declaration, like `tm` in `static TM* tm[TPC];`.
+fn declared_name(decl: &str) -> Option<&str> {
+  let decl = decl.split_once('=').map_or(decl, |(decl, _)| decl);
+  let decl = decl.split_once('[').map_or(decl, |(decl, _)| decl);
+  identifiers(decl).last()
+}
+
+/// The names of the parameters of a function, given its signature.
+fn parameter_names(signature: &str) -> HashSet<&str> {
+  let Some((_, params)) = signature.split_once('(') else { return HashSet::new() };
+  params.split(',').filter_map(|param| identifiers(param).last()).collect()
+}
+
+/// The identifiers and keywords in the given code, without the ones in string and character literals.
+fn identifiers(code: &str) -> impl Iterator<Item = &str> {
+  let bytes = code.as_bytes();
+  let mut i = 0;
+  std::iter::from_fn(move || {
+    while i < bytes.len() {
+      let c = bytes[i];
+      if c == b'"' || c == b'\'' {
+        i = literal_end(bytes, i);
+      } else if is_ident_char(c as char) {
+        let start = i;
+        while i < bytes.len() && is_ident_char(bytes[i] as char) {
+          i += 1;
+        }
+        if !c.is_ascii_digit() {
+          return Some(&code[start..i]);
+        }
+      } else {
+        i += 1;
+      }
+    }
+    None
+  })
+}
+
+/// The code without its comments.
+fn strip_comments(code: &str) -> String {
+  let mut code = code.to_string();
+  while let Some(start) = code.find("//") {
+    let end = code[start..].find('\n').map_or(code.len(), |end| start + end);
+    code.replace_range(start..end, "");
+  }
+  while let Some(start) = code.find("/*") {
+    let end = code[start..].find("*/").map_or(code.len(), |end| start + end + 2);
+    code.replace_range(start..end, "");
+  }
+  code
+}
+
+/// The names of the functions called in the given code, ignoring the comments.
+fn called_names(code: &str) -> Vec<String> {
+  let code = strip_comments(code);
+  let names = code.match_indices('(').filter_map(|(paren, _)| {
+    let name = code[..paren].trim_end().rsplit(|c: char| !is_ident_char(c)).next()?;
+    (!name.is_empty() && !name.starts_with(|c: char| c.is_ascii_digit())).then_some(name)
+  });
+  names.map(str::to_string).collect()
+}
+
+fn is_ident_char(c: char) -> bool {
+  c.is_ascii_alphanumeric() || c == '_'
+}
+
+/// Where a directive that starts at `start` ends, following line continuations.
+fn directive_end(code: &str, start: usize) -> usize {
+  let mut end = start;
+  loop {
+    let line_end = code[end..].find('\n').map_or(code.len(), |n| end + n + 1);
+    if line_end < code.len() && code[end..line_end].trim_end().ends_with('\\') {
+      end = line_end;
+    } else {
+      return line_end;
+    }
+  }
+}
+
+/// Where the string or character literal that starts at `start` ends.
+fn literal_end(bytes: &[u8], start: usize) -> usize {
+  let quote = bytes[start];
+  let mut i = start + 1;
+  while i < bytes.len() {
+    match bytes[i] {
+      b'\\' => i += 2,
+      c if c == quote => return i + 1,
+      _ => i += 1,
+    }
+  }
+  bytes.len()
+}
@@ -0,0 +1,338 @@
+//! Reads back the result of a book while it's evaluated in this process, one list element at a time.
+//!
+//! The nodes hanging from the root of the net, from auxiliary port to principal port, can't take
+//! part in any more interactions, so the cells of a list returned by the program are final as soon
+//! as they appear, even if the rest of the list is still being computed. An element can be read
+//! back once each of its unlinked variables is bound inside of it, since then nothing being
+//! evaluated can be connected to it anymore.
+
+use super::interpreter::Interpreter;
+use crate::{
+  diagnostics::Diagnostics,
+  fun::{builtins, term_to_net::Labels, Book, Name, Num, Term},
+  maybe_grow, readback_hvm_net, AdtEncoding, RunStats,
+};
+use hvm::{
+  ast::{self, Tree},
+  hvm::{Port, CON, DUP, ERA, NONE, NUM, OPR, REF, SWI, VAR},
+};
+use std::collections::{HashMap, VecDeque};
+
+/// How many interactions are performed before checking again if the next chunk of the result is ready.
+/// Doubles each time it isn't, up to `MAX_STEP`, so waiting for a chunk takes time linear in the
+/// interactions needed to compute it.
+const MIN_STEP: u64 = 1 << 10;
+const MAX_STEP: u64 = 1 << 20;
+
+/// A part of the result of a program, as yielded by a [`ResultStream`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum TermChunk {
+  /// The next element of the list returned by the program.
+  Element(Term),
+  /// What is left of the result after the elements read so far, when it isn't the end of a list.
+  /// For a result that isn't a list, this is the whole result.
+  Rest(Term),
+}
+
+/// The result of a program evaluated in this process, read back while it's being evaluated.
+///
+/// If the program returns a list, each element is yielded as soon as it's computed, and the
+/// evaluation only goes on while the next element is requested, so the stream can be stopped
+/// early on very large or infinite lists. Otherwise, the evaluation is finished and the whole
+/// result is yielded at once.
+///
+/// Elements that share a duplicated value with the rest of the list can only be read back once
+/// the evaluation has finished, together with the rest of the list.
+pub struct ResultStream {
+  interpreter: Interpreter,
+  book: Book,
+  labels: Labels,
+  linear: bool,
+  adt_encoding: AdtEncoding,
+  readback_depth_limit: Option<usize>,
+  /// The tags of the `List/Cons` and `List/Nil` constructors in the nets, if they're used.
+  tags: (Option<Port>, Option<Port>),
+  /// The position of the list cell that comes next.
+  cell: Port,
+  /// Elements that were read back together with the rest of the list and not yielded yet.
+  elements: VecDeque<Term>,
+  step: u64,
+  finished: bool,
+  done: bool,
+  diagnostics: Diagnostics,
+}
+
+enum Cell {
+  Cons(Port, Port),
+  Nil,
+}
+
+/// Why a port doesn't have the expected shape.
+enum Mismatch {
+  /// It can't have it, since it was already evaluated to something else.
+  Differs,
+  /// It may still have it, once the variables in the way are linked by the evaluation.
+  Pending,
+}
+
+impl ResultStream {
+  pub fn new(
+    interpreter: Interpreter,
+    book: Book,
+    labels: Labels,
+    linear: bool,
+    adt_encoding: AdtEncoding,
+    readback_depth_limit: Option<usize>,
+  ) -> Self {
+    let tag = |ctr: &str| match adt_encoding {
+      AdtEncoding::Scott => None,
+      AdtEncoding::NumScott => {
+        let tag = format!("{ctr}/tag");
+        let id = interpreter.book.defs.iter().position(|def| def.name == tag)?;
+        Some(Port::new(REF, id as u32))
+      }
+      AdtEncoding::TaggedScott => {
+        let tag = *book.ctr_tags().get(&Name::new(ctr))?;
+        Some(Port::new(NUM, Num::U24(tag).to_bits()))
+      }
+    };
+    let tags = (tag(builtins::LCONS), tag(builtins::LNIL));
+    ResultStream {
+      interpreter,
+      book,
+      labels,
+      linear,
+      adt_encoding,
+      readback_depth_limit,
+      tags,
+      cell: hvm::hvm::ROOT,
+      elements: VecDeque::new(),
+      step: MIN_STEP,
+      finished: false,
+      done: false,
+      diagnostics: Diagnostics::default(),
+    }
+  }
+
+  /// The warnings given when reading back the chunks yielded so far.
+  pub fn diagnostics(&self) -> &Diagnostics {
+    &self.diagnostics
+  }
+
+  /// The stats of the evaluation up to the last chunk yielded.
+  pub fn stats(&self) -> RunStats {
+    self.interpreter.stats()
+  }
+
+  fn next_chunk(&mut self) -> Result<Option<TermChunk>, Diagnostics> {
+    loop {
+      if let Some(element) = self.elements.pop_front() {
+        return Ok(Some(TermChunk::Element(element)));
+      }
+      if self.done {
+        return Ok(None);
+      }
+      match self.read_cell(self.cell) {
+        Ok(Cell::Nil) => {
+          self.done = true;
+        }
+        Ok(Cell::Cons(head, tail)) if self.is_closed(head) => {
+          self.step = MIN_STEP;
+          self.cell = tail;
+          return Ok(Some(TermChunk::Element(self.readback(head))));
+        }
+        // The element shares something with the rest of the list, or the result isn't a list.
+        _ if self.finished => {
+          self.done = true;
+          let mut rest = self.readback(self.cell);
+          match &mut rest {
+            Term::List { els } => self.elements.extend(std::mem::take(els)),
+            _ => return Ok(Some(TermChunk::Rest(rest))),
+          }
+        }
+        _ => {
+          self.finished = self.interpreter.run(Some(self.step))?;
+          self.step = (self.step * 2).min(MAX_STEP);
+        }
+      }
+    }
+  }
+
+  /// Matches the list cell at the port with the encoding of `List/Cons` or `List/Nil`.
+  fn read_cell(&self, port: Port) -> Result<Cell, Mismatch> {
+    let port = self.enter(port);
+    if port.get_tag() == REF {
+      let is_nil = self.ref_name(port) == builtins::LNIL;
+      return if is_nil { Ok(Cell::Nil) } else { Err(Mismatch::Differs) };
+    }
+    match self.adt_encoding {
+      // Cons: ((tag (head (tail x))) x), Nil: ((tag x) x)
+      AdtEncoding::NumScott | AdtEncoding::TaggedScott => {
+        let (fst, x) = self.con(port)?;
+        let (tag, fields) = self.con(fst)?;
+        match self.tag(tag, self.tags.1) {
+          Ok(()) => {
+            self.same_var(fields, x)?;
+            Ok(Cell::Nil)
+          }
+          Err(Mismatch::Pending) => Err(Mismatch::Pending),
+          Err(Mismatch::Differs) => {
+            self.tag(tag, self.tags.0)?;
+            let (head, rest) = self.con(fields)?;
+            let (tail, y) = self.con(rest)?;
+            self.same_var(x, y)?;
+            Ok(Cell::Cons(head, tail))
+          }
+        }
+      }
+      // Cons: (* ((head (tail x)) x)), Nil: (x (* x))
+      AdtEncoding::Scott => {
+        let (fst, snd) = self.con(port)?;
+        let (app, x) = self.con(snd)?;
+        match self.era(app) {
+          Ok(()) => {
+            self.same_var(fst, x)?;
+            Ok(Cell::Nil)
+          }
+          Err(Mismatch::Pending) => Err(Mismatch::Pending),
+          Err(Mismatch::Differs) => {
+            self.era(fst)?;
+            let (head, rest) = self.con(app)?;
+            let (tail, y) = self.con(rest)?;
+            self.same_var(x, y)?;
+            Ok(Cell::Cons(head, tail))
+          }
+        }
+      }
+    }
+  }
+
+  fn con(&self, port: Port) -> Result<(Port, Port), Mismatch> {
+    let port = self.enter(port);
+    match port.get_tag() {
+      CON => {
+        let node = self.interpreter.net.node_load(port.get_val() as usize);
+        Ok((node.get_fst(), node.get_snd()))
+      }
+      VAR => Err(Mismatch::Pending),
+      _ => Err(Mismatch::Differs),
+    }
+  }
+
+  fn era(&self, port: Port) -> Result<(), Mismatch> {
+    match self.enter(port).get_tag() {
+      ERA => Ok(()),
+      VAR => Err(Mismatch::Pending),
+      _ => Err(Mismatch::Differs),
+    }
+  }
+
+  fn tag(&self, port: Port, tag: Option<Port>) -> Result<(), Mismatch> {
+    let port = self.enter(port);
+    match (port.get_tag(), tag) {
+      (VAR, _) => Err(Mismatch::Pending),
+      (REF, Some(tag)) if tag.get_tag() == REF && port.get_val() & 0xFFFFFFF == tag.get_val() => Ok(()),
+      (_, Some(tag)) if port == tag => Ok(()),
+      _ => Err(Mismatch::Differs),
+    }
+  }
+
+  /// Checks that two ports are the ends of the same unlinked variable.
+  fn same_var(&self, a: Port, b: Port) -> Result<(), Mismatch> {
+    match (self.enter(a), self.enter(b)) {
+      (a, b) if a.get_tag() == VAR && a == b => Ok(()),
+      (a, b) if a.get_tag() == VAR || b.get_tag() == VAR => Err(Mismatch::Pending),
+      _ => Err(Mismatch::Differs),
+    }
+  }
+
+  /// Whether every unlinked variable in the tree of the port is bound inside of it.
+  fn is_closed(&self, port: Port) -> bool {
+    let mut vars = HashMap::<u32, usize>::new();
+    let mut stack = vec![port];
+    while let Some(port) = stack.pop() {
+      let port = self.enter(port);
+      match port.get_tag() {
+        VAR => *vars.entry(port.get_val()).or_default() += 1,
+        CON | DUP | OPR | SWI => {
+          let node = self.interpreter.net.node_load(port.get_val() as usize);
+          stack.push(node.get_snd());
+          stack.push(node.get_fst());
+        }
+        _ => {}
+      }
+    }
+    vars.values().all(|&uses| uses == 2)
+  }
+
+  /// Follows the variables that were already linked to something, without modifying the net.
+  fn enter(&self, mut port: Port) -> Port {
+    while port.get_tag() == VAR {
+      let got = self.interpreter.net.vars_load(port.get_val() as usize);
+      if got == NONE || got.0 == 0 {
+        break;
+      }
+      port = got;
+    }
+    port
+  }
+
+  fn ref_name(&self, port: Port) -> &str {
+    &self.interpreter.book.defs[port.get_val() as usize & 0xFFFFFFF].name
+  }
+
+  fn readback(&mut self, port: Port) -> Term {
+    let net = ast::Net { root: self.readback_tree(port), rbag: vec![] };
+    let (term, diags) = readback_hvm_net(
+      &net,
+      &self.book,
+      &self.labels,
+      self.linear,
+      self.adt_encoding,
+      self.readback_depth_limit,
+    );
+    for (origin, diags) in diags.diagnostics {
+      self.diagnostics.diagnostics.entry(origin).or_default().extend(diags);
+    }
+    term
+  }
+
+  fn readback_tree(&self, port: Port) -> Tree {
+    maybe_grow(|| {
+      let port = self.enter(port);
+      match port.get_tag() {
+        VAR => Tree::Var { nam: format!("v{:x}", port.get_val()) },
+        REF => Tree::Ref { nam: self.ref_name(port).to_string() },
+        ERA => Tree::Era,
+        NUM => Tree::Num { val: ast::Numb(port.get_val()) },
+        tag => {
+          let node = self.interpreter.net.node_load(port.get_val() as usize);
+          let fst = Box::new(self.readback_tree(node.get_fst()));
+          let snd = Box::new(self.readback_tree(node.get_snd()));
+          match tag {
+            CON => Tree::Con { fst, snd },
+            DUP => Tree::Dup { fst, snd },
+            OPR => Tree::Opr { fst, snd },
+            SWI => Tree::Swi { fst, snd },
+            _ => unreachable!(),
+          }
+        }
+      }
+    })
+  }
+}
+
+impl Iterator for ResultStream {
+  type Item = Result<TermChunk, Diagnostics>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    match self.next_chunk() {
+      Ok(chunk) => chunk.map(Ok),
+      Err(err) => {
+        self.done = true;
+        self.elements.clear();
+        Some(Err(err))
+      }
+    }
+  }
+}
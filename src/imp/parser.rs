@@ -130,7 +130,7 @@ impl<'a> PyParser<'a> {
       '`' => Expr::Num { val: Num::U24(self.parse_quoted_symbol()?) },
       // String
       '\"' => {
-        let str = self.parse_quoted_string()?;
+        let str = self.parse_string()?;
         let val = STRINGS.get(str);
         Expr::Str { val }
       }
@@ -0,0 +1,330 @@
+//! Checks a book reusing the diagnostics of the definitions that didn't change since the last check.
+//!
+//! The diagnostics of a successful check are saved in a cache file, together with a hash of the
+//! source of each definition and of everything it may reference. On the next check, only the
+//! definitions whose hash changed and the ones they depend on are checked again, and the diagnostics
+//! of the others are taken from the cache.
+//!
+//! Some diagnostics depend on the whole program, like unused definitions or recursion cycles.
+//! Whenever the partial check can't tell that these are still the same, the whole book is checked
+//! instead, so the result is always the same as the one of [`check_book`].
+
+use crate::{
+  check_book,
+  diagnostics::{Diagnostic, DiagnosticOrigin, Diagnostics, DiagnosticsConfig, Severity, WarningType},
+  fun::{Book, Definition, Name, Rule, Term},
+  maybe_grow, CompileOpts, ENTRY_POINT, HVM1_ENTRY_POINT,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+  collections::{BTreeMap, BTreeSet},
+  path::Path,
+};
+
+/// The default name of the file where the results of the last check are saved.
+pub const CACHE_FILE: &str = ".bend-cache";
+
+#[derive(Default, Serialize, Deserialize)]
+struct CheckCache {
+  /// Hash of everything besides the definitions that can change the result of a check.
+  key: u64,
+  defs: BTreeMap<String, CachedDef>,
+  diagnostics: Vec<(DiagnosticOrigin, Vec<Diagnostic>)>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CachedDef {
+  /// Hash of the source of the definition and of the definitions it may reference.
+  hash: u64,
+  /// The definitions referenced by the definition and the ones generated from it, once desugared.
+  refs: BTreeSet<String>,
+}
+
+/// Like [`check_book`], but only checks again the definitions that changed since the last time the
+/// book was checked with the same options, according to the cache in `cache_path`.
+///
+/// The cache is updated after each check without errors.
+/// Failing to update it is only a warning, since the result of the check is still valid.
+pub fn check_book_incremental(
+  book: &Book,
+  diagnostics_cfg: DiagnosticsConfig,
+  compile_opts: CompileOpts,
+  cache_path: &Path,
+) -> Result<Diagnostics, Diagnostics> {
+  let key = book_key(book, &diagnostics_cfg, &compile_opts);
+  let hashes = def_hashes(book);
+
+  let cache = read_cache(cache_path).filter(|cache| cache.key == key);
+  let Some(mut cache) = cache else {
+    return full_check(book, diagnostics_cfg, compile_opts, key, hashes, cache_path);
+  };
+
+  let dirty = hashes
+    .iter()
+    .filter(|(nam, hash)| cache.defs.get(*nam).map(|def| def.hash) != Some(**hash))
+    .map(|(nam, _)| nam.clone())
+    .collect::<BTreeSet<_>>();
+
+  if dirty.is_empty() {
+    let mut diagnostics = Diagnostics::new(diagnostics_cfg);
+    diagnostics.def_spans = book.def_spans.clone();
+    diagnostics.diagnostics.extend(cache.diagnostics);
+    return Ok(diagnostics);
+  }
+
+  // Definitions can only be checked on their own when the result doesn't depend on the rest of the book.
+  if compile_opts.prune
+    || compile_opts.merge
+    || compile_opts.dedup_defs
+    || compile_opts.roots.is_some()
+    || diagnostics_cfg.report_pruned
+  {
+    return full_check(book, diagnostics_cfg, compile_opts, key, hashes, cache_path);
+  }
+
+  match partial_check(book, diagnostics_cfg, compile_opts.clone(), &dirty, &cache) {
+    Some(mut diagnostics) => {
+      for (nam, hash) in hashes {
+        if let Some(def) = cache.defs.get_mut(&nam) {
+          def.hash = hash;
+        }
+      }
+      cache.diagnostics = diagnostics.diagnostics.iter().map(|(o, d)| (o.clone(), d.clone())).collect();
+      if let Err(e) = write_cache(cache_path, &cache) {
+        diagnostics.add_diagnostic(e, Severity::Warning, DiagnosticOrigin::Book);
+      }
+      Ok(diagnostics)
+    }
+    None => full_check(book, diagnostics_cfg, compile_opts, key, hashes, cache_path),
+  }
+}
+
+fn full_check(
+  book: &Book,
+  diagnostics_cfg: DiagnosticsConfig,
+  compile_opts: CompileOpts,
+  key: u64,
+  hashes: BTreeMap<String, u64>,
+  cache_path: &Path,
+) -> Result<Diagnostics, Diagnostics> {
+  let mut checked = book.clone();
+  let mut diagnostics = check_book(&mut checked, diagnostics_cfg, compile_opts)?;
+
+  let mut refs = desugared_refs(&checked);
+  let defs = hashes
+    .into_iter()
+    .map(|(nam, hash)| {
+      let refs = refs.remove(&nam).unwrap_or_default();
+      (nam, CachedDef { hash, refs })
+    })
+    .collect();
+  let diagnostics_list = diagnostics.diagnostics.iter().map(|(o, d)| (o.clone(), d.clone())).collect();
+  if let Err(e) = write_cache(cache_path, &CheckCache { key, defs, diagnostics: diagnostics_list }) {
+    diagnostics.add_diagnostic(e, Severity::Warning, DiagnosticOrigin::Book);
+  }
+
+  Ok(diagnostics)
+}
+
+/// Checks only the changed definitions and the ones they depend on, merging the result with the
+/// cached diagnostics of the other definitions.
+///
+/// Returns `None` if the result may differ from the one of a full check.
+fn partial_check(
+  book: &Book,
+  diagnostics_cfg: DiagnosticsConfig,
+  compile_opts: CompileOpts,
+  dirty: &BTreeSet<String>,
+  cache: &CheckCache,
+) -> Option<Diagnostics> {
+  let deps = dependencies(book);
+  let mut included = BTreeSet::new();
+  let mut to_visit = dirty.iter().cloned().collect::<Vec<_>>();
+  while let Some(nam) = to_visit.pop() {
+    if included.insert(nam.clone()) {
+      to_visit.extend(deps[&nam].iter().cloned());
+    }
+  }
+
+  let mut sub_book = book.clone();
+  sub_book.defs.retain(|nam, def| def.builtin || included.contains(nam.as_ref()));
+  // The entrypoint is required to check a book, but what it does doesn't matter if it didn't change.
+  for entrypoint in [ENTRY_POINT, HVM1_ENTRY_POINT] {
+    let nam = Name::new(entrypoint);
+    if book.defs.contains_key(&nam) && !included.contains(entrypoint) {
+      let rules = vec![Rule { pats: vec![], body: Term::Era }];
+      sub_book.defs.insert(nam.clone(), Definition { name: nam, rules, builtin: false });
+    }
+  }
+
+  let mut checked = sub_book;
+  let partial = check_book(&mut checked, diagnostics_cfg, compile_opts).ok()?;
+
+  // Which definitions are used, and so the unused definition warnings, only stays the same if
+  // the changed definitions reference the same ones as before.
+  let refs = desugared_refs(&checked);
+  for nam in dirty {
+    let cached = cache.defs.get(nam)?;
+    if refs.get(nam).unwrap_or(&BTreeSet::new()) != &cached.refs {
+      return None;
+    }
+  }
+
+  let origin_def = |origin: &DiagnosticOrigin| {
+    let nam = match origin {
+      DiagnosticOrigin::Rule(nam) => nam.def_name_from_generated(),
+      DiagnosticOrigin::Inet(nam) => Name::new(nam.as_str()).def_name_from_generated(),
+      DiagnosticOrigin::Book | DiagnosticOrigin::Readback => return None,
+    };
+    book.defs.contains_key(&nam).then(|| nam.to_string())
+  };
+  let is_unused = |diag: &Diagnostic| diag.warning_type() == Some(WarningType::UnusedDefinition);
+
+  // The diagnostics that aren't about a single definition must be the same as before.
+  let partial_global = partial.diagnostics.iter().filter(|(origin, _)| origin_def(origin).is_none());
+  let cached_global = cache.diagnostics.iter().filter(|(origin, _)| origin_def(origin).is_none());
+  if !partial_global.eq(cached_global.map(|(origin, diags)| (origin, diags))) {
+    return None;
+  }
+
+  let mut diagnostics = Diagnostics::new(diagnostics_cfg);
+  diagnostics.def_spans = book.def_spans.clone();
+  for (origin, diags) in partial.diagnostics {
+    if origin_def(&origin).is_some_and(|nam| dirty.contains(&nam)) {
+      let diags = diags.into_iter().filter(|diag| !is_unused(diag)).collect::<Vec<_>>();
+      if !diags.is_empty() {
+        diagnostics.diagnostics.insert(origin, diags);
+      }
+    }
+  }
+  for (origin, diags) in &cache.diagnostics {
+    let is_dirty = origin_def(origin).is_some_and(|nam| dirty.contains(&nam));
+    // The unused definition warnings are the last ones given for a definition.
+    let diags = diags.iter().filter(|diag| !is_dirty || is_unused(diag)).cloned().collect::<Vec<_>>();
+    if !diags.is_empty() {
+      diagnostics.diagnostics.entry(origin.clone()).or_default().extend(diags);
+    }
+  }
+  Some(diagnostics)
+}
+
+/// Hashes everything that can change the result of a check besides the source of the definitions.
+fn book_key(book: &Book, diagnostics_cfg: &DiagnosticsConfig, compile_opts: &CompileOpts) -> u64 {
+  let mut hasher = Fnv::new();
+  hasher.write(env!("CARGO_PKG_VERSION").as_bytes());
+  hasher.write(format!("{diagnostics_cfg:?}").as_bytes());
+  hasher.write(format!("{compile_opts:?}").as_bytes());
+  hasher.write(&serde_json::to_vec(&book.adts).unwrap());
+  hasher.write(&serde_json::to_vec(&book.entrypoint).unwrap());
+  hasher.write(format!("{:?}", book.host_defs).as_bytes());
+  hasher.write(format!("{:?}", book.allowed_warnings).as_bytes());
+  for nam in book.defs.keys() {
+    hasher.write(nam.as_bytes());
+    hasher.write(&[0]);
+  }
+  hasher.finish()
+}
+
+/// Hashes the source of each definition together with the source of the definitions it may reference.
+fn def_hashes(book: &Book) -> BTreeMap<String, u64> {
+  let deps = dependencies(book);
+  let sources = book
+    .defs
+    .iter()
+    .map(|(nam, def)| (nam.to_string(), serde_json::to_vec(def).unwrap()))
+    .collect::<BTreeMap<_, _>>();
+
+  let mut hashes = BTreeMap::new();
+  for nam in sources.keys() {
+    let mut reached = BTreeSet::from([nam]);
+    let mut to_visit = vec![nam];
+    while let Some(nam) = to_visit.pop() {
+      for dep in &deps[nam] {
+        if reached.insert(dep) {
+          to_visit.push(dep);
+        }
+      }
+    }
+
+    let mut hasher = Fnv::new();
+    hasher.write(&sources[nam]);
+    for dep in reached.into_iter().filter(|dep| *dep != nam) {
+      hasher.write(dep.as_bytes());
+      hasher.write(&sources[dep]);
+    }
+    hashes.insert(nam.clone(), hasher.finish());
+  }
+  hashes
+}
+
+/// The definitions of the book that each definition may reference.
+fn dependencies(book: &Book) -> BTreeMap<String, BTreeSet<String>> {
+  let mut deps = BTreeMap::new();
+  for (nam, def) in &book.defs {
+    let mut refs = vec![];
+    for rule in &def.rules {
+      rule.body.find_possible_refs(&mut refs);
+    }
+    let refs = refs.into_iter().filter(|dep| book.defs.contains_key(dep) && dep != nam);
+    deps.insert(nam.to_string(), refs.map(|dep| dep.to_string()).collect());
+  }
+  deps
+}
+
+/// The definitions referenced by each definition of a desugared book, counting the references of
+/// the definitions generated from it as its own.
+fn desugared_refs(book: &Book) -> BTreeMap<String, BTreeSet<String>> {
+  fn collect(term: &Term, refs: &mut BTreeSet<String>) {
+    maybe_grow(|| {
+      if let Term::Ref { nam } = term {
+        refs.insert(nam.def_name_from_generated().to_string());
+      }
+      for child in term.children() {
+        collect(child, refs);
+      }
+    })
+  }
+
+  let mut refs = BTreeMap::<String, BTreeSet<String>>::new();
+  for (nam, def) in &book.defs {
+    let origin = nam.def_name_from_generated().to_string();
+    let def_refs = refs.entry(origin.clone()).or_default();
+    for rule in &def.rules {
+      collect(&rule.body, def_refs);
+    }
+    def_refs.remove(&origin);
+  }
+  refs
+}
+
+fn read_cache(path: &Path) -> Option<CheckCache> {
+  let file = std::fs::File::open(path).ok()?;
+  serde_json::from_reader(std::io::BufReader::new(file)).ok()
+}
+
+fn write_cache(path: &Path, cache: &CheckCache) -> Result<(), String> {
+  let file = std::fs::File::create(path)
+    .map_err(|e| format!("Failed to write the check cache '{}'.\n{e}", path.display()))?;
+  serde_json::to_writer(std::io::BufWriter::new(file), cache)
+    .map_err(|e| format!("Failed to write the check cache '{}'.\n{e}", path.display()))
+}
+
+/// The FNV-1a hash, which unlike the standard library hashers stays the same between versions.
+pub(crate) struct Fnv(u64);
+
+impl Fnv {
+  pub(crate) fn new() -> Self {
+    Fnv(0xcbf29ce484222325)
+  }
+
+  pub(crate) fn write(&mut self, bytes: &[u8]) {
+    for byte in bytes {
+      self.0 ^= *byte as u64;
+      self.0 = self.0.wrapping_mul(0x100000001b3);
+    }
+  }
+
+  pub(crate) fn finish(&self) -> u64 {
+    self.0
+  }
+}
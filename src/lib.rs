@@ -1,31 +1,157 @@
 use crate::{
-  fun::{book_to_hvm, net_to_term::net_to_term, term_to_net::Labels, Book, Ctx, Term},
+  fun::{
+    book_to_hvm, display::FloatFormat, load_book::do_parse_book, net_to_term::net_to_term,
+    parser::INFIX_OPER_CHARS, term_to_net::Labels, Book, Ctx, Definition, Name, Rule, Term,
+  },
   hvm::{
     add_recursive_priority::add_recursive_priority,
     check_net_size::{check_net_sizes, MAX_NET_SIZE},
     display_hvm_book,
     eta_reduce::eta_reduce_hvm_net,
     inline::inline_hvm_book,
+    interpreter::{interpret_hvm_book, Coverage, Interpreter, InterpreterOpts, Profile},
     mutual_recursion,
+    optimize_nets::optimize_hvm_net,
     prune::prune_hvm_book,
+    stream::ResultStream,
   },
 };
 use diagnostics::{Diagnostics, DiagnosticsConfig, ERR_INDENT_SIZE};
+use itertools::Itertools;
 use net::hvm_to_net::hvm_to_net;
+use std::{
+  collections::HashMap,
+  io::Write,
+  path::{Path, PathBuf},
+  sync::atomic::{AtomicUsize, Ordering},
+  time::{Duration, Instant},
+};
 
+pub mod compile_cache;
 pub mod diagnostics;
 pub mod fun;
 pub mod hvm;
 pub mod imp;
+pub mod incremental;
 pub mod net;
+pub mod repl;
 mod utils;
 
-pub use fun::load_book::load_file_to_book;
+pub use fun::load_book::{
+  load_file_to_book, load_file_to_book_with_prelude, load_files_to_book, load_files_to_book_with_prelude,
+  load_prelude_file, load_reader_to_book, parse_book, parse_book_with_prelude, STDIN_PATH,
+};
 
 pub const ENTRY_POINT: &str = "main";
 pub const HVM1_ENTRY_POINT: &str = "Main";
 pub const HVM_OUTPUT_END_MARKER: &str = "Result: ";
 
+/// Parses a program and prints it back in the canonical Bend syntax.
+///
+/// The user-written datatypes and definitions keep the order they were declared in.
+/// Fails if the program has comments, infix operator declarations or imp definitions,
+/// since they would be lost, or if the formatted program doesn't parse back into the exact same book.
+pub fn format_book(code: &str, path: &Path) -> Result<String, Diagnostics> {
+  if let Some(syntax) = unformattable_syntax(code) {
+    return Err(
+      format!("Unable to format '{}', it has {syntax}, which would be lost.", path.display()).into(),
+    );
+  }
+  let book = do_parse_book(code, path, Book::builtins())?;
+  let formatted = book.display_source(FloatFormat::Decimal).to_string();
+
+  let reformatted = do_parse_book(&formatted, path, Book::builtins())
+    .map_err(|_| format!("Unable to format '{}', the result doesn't parse.", path.display()))?;
+  if let Some(nam) = first_source_mismatch(&book, &reformatted) {
+    let mut diags = Diagnostics::default();
+    diags.add_rule_error("Unable to format this definition without changing its meaning.", nam);
+    return Err(diags);
+  }
+
+  Ok(formatted)
+}
+
+/// Finds the syntax that the formatter can't print back: comments and infix operator declarations,
+/// which are not kept in the book, and imp definitions, which would be printed in the fun syntax.
+fn unformattable_syntax(code: &str) -> Option<&'static str> {
+  let mut chars = code.char_indices();
+  let mut line_start = true;
+  while let Some((idx, c)) = chars.next() {
+    if line_start {
+      let line = &code[idx..];
+      let item = line.split(|ch: char| ch.is_ascii_whitespace()).next().unwrap_or_default();
+      let is_imp_type = item == "type" && line.lines().next().is_some_and(|l| l.trim_end().ends_with(':'));
+      if item == "def" || item == "object" || is_imp_type {
+        return Some("imp definitions");
+      }
+      if matches!(item, "infixl" | "infixr" | "infix") && is_infix_decl(&line[item.len()..]) {
+        return Some("infix operator declarations");
+      }
+    }
+    line_start = c == '\n';
+    match c {
+      '#' => return Some("comments"),
+      // Skips string, char and symbol literals, which may have a '#'.
+      '"' | '\'' | '`' => {
+        while let Some((_, d)) = chars.next() {
+          if d == '\\' {
+            chars.next();
+          } else if d == c {
+            break;
+          }
+        }
+      }
+      _ => (),
+    }
+  }
+  None
+}
+
+/// Checks if what follows an `infixl`, `infixr` or `infix` at the start of a line is a precedence and
+/// an operator, like the parser does to tell a declaration from a rule of a function with that name.
+fn is_infix_decl(rest: &str) -> bool {
+  let rest = rest.trim_start();
+  let prec_len = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+  if prec_len == 0 {
+    return false;
+  }
+  let rest = rest[prec_len..].trim_start();
+  let oper_len = rest.find(|c: char| !INFIX_OPER_CHARS.contains(c)).unwrap_or(rest.len());
+  !matches!(&rest[..oper_len], "" | "=")
+}
+
+/// Returns the name of the first user-written datatype or definition that differs between two books,
+/// or that is declared in a different position.
+fn first_source_mismatch(a: &Book, b: &Book) -> Option<Name> {
+  if a.source_order != b.source_order {
+    let idx = a.source_order.iter().zip(&b.source_order).take_while(|(a, b)| a == b).count();
+    let item = a.source_order.get(idx).or(b.source_order.get(idx)).unwrap();
+    return Some(item.name().clone());
+  }
+
+  let a_adts = a.adts.iter().filter(|(_, adt)| !adt.builtin);
+  let mut b_adts = b.adts.iter().filter(|(_, adt)| !adt.builtin);
+  for (nam, adt) in a_adts {
+    match b_adts.next() {
+      Some((b_nam, b_adt)) if b_nam == nam && adt.ctrs.iter().eq(b_adt.ctrs.iter()) => (),
+      _ => return Some(nam.clone()),
+    }
+  }
+  if let Some((nam, _)) = b_adts.next() {
+    return Some(nam.clone());
+  }
+
+  let a_defs = a.defs.values().filter(|def| !def.builtin);
+  let mut b_defs = b.defs.values().filter(|def| !def.builtin);
+  for def in a_defs {
+    match b_defs.next() {
+      Some(b_def) if b_def.name == def.name && b_def.rules == def.rules => (),
+      _ => return Some(def.name.clone()),
+    }
+  }
+  b_defs.next().map(|def| def.name.clone())
+}
+
 pub fn check_book(
   book: &mut Book,
   diagnostics_cfg: DiagnosticsConfig,
@@ -42,114 +168,361 @@ pub fn compile_book(
   diagnostics_cfg: DiagnosticsConfig,
   args: Option<Vec<Term>>,
 ) -> Result<CompileResult, Diagnostics> {
-  let mut diagnostics = desugar_book(book, opts.clone(), diagnostics_cfg, args)?;
+  compile_book_with_hook(book, opts, diagnostics_cfg, args, None)
+}
 
-  let (mut hvm_book, labels) = book_to_hvm(book, &mut diagnostics)?;
+/// Like [`compile_book`], but calls `hook` after each phase of the compilation with its name
+/// and how long it took.
+///
+/// The phases are each of the [`DESUGAR_PASSES`], followed by the generation of the HVM nets
+/// (`to-hvm`) and the passes on them that are enabled by `opts`.
+/// Without a hook, the phases aren't timed at all.
+///
+/// ```
+/// use bend::{compile_book_with_hook, diagnostics::DiagnosticsConfig, load_reader_to_book, CompileOpts};
+/// use std::path::Path;
+///
+/// let code = "main = (λx x 1)";
+/// let mut book = load_reader_to_book(code.as_bytes(), Path::new("example.bend")).unwrap();
+/// let mut phases = vec![];
+/// let mut hook = |phase: bend::PhaseInfo| phases.push(phase.name);
+/// compile_book_with_hook(&mut book, CompileOpts::default(), DiagnosticsConfig::default(), None, Some(&mut hook))
+///   .unwrap();
+///
+/// assert_eq!(phases.first(), Some(&"check-shared-names"));
+/// assert!(phases.contains(&"linearize-vars") && phases.contains(&"to-hvm"));
+/// ```
+pub fn compile_book_with_hook(
+  book: &mut Book,
+  opts: CompileOpts,
+  diagnostics_cfg: DiagnosticsConfig,
+  args: Option<Vec<Term>>,
+  mut hook: Option<&mut dyn FnMut(PhaseInfo)>,
+) -> Result<CompileResult, Diagnostics> {
+  let desugar_hook = hook.as_mut().map(|hook| &mut **hook as &mut dyn FnMut(PhaseInfo));
+  let mut diagnostics = desugar_book_until(book, opts.clone(), diagnostics_cfg, args, None, desugar_hook)?;
+
+  let (mut hvm_book, labels) = run_phase("to-hvm", &mut hook, || book_to_hvm(book, &mut diagnostics))?;
+
+  if opts.optimize_nets {
+    run_phase("optimize-nets", &mut hook, || hvm_book.defs.values_mut().for_each(optimize_hvm_net));
+  }
 
   if opts.eta {
-    hvm_book.defs.values_mut().for_each(eta_reduce_hvm_net);
+    run_phase("eta-reduce", &mut hook, || hvm_book.defs.values_mut().for_each(eta_reduce_hvm_net));
   }
 
-  mutual_recursion::check_cycles(&hvm_book, &mut diagnostics)?;
+  run_phase("check-cycles", &mut hook, || mutual_recursion::check_cycles(&hvm_book, &mut diagnostics))?;
 
   if opts.eta {
-    hvm_book.defs.values_mut().for_each(eta_reduce_hvm_net);
+    run_phase("eta-reduce", &mut hook, || hvm_book.defs.values_mut().for_each(eta_reduce_hvm_net));
   }
 
   if opts.inline {
-    diagnostics.start_pass();
-    if let Err(e) = inline_hvm_book(&mut hvm_book) {
-      diagnostics.add_book_error(format!("During inlining:\n{:ERR_INDENT_SIZE$}{}", "", e));
-    }
-    diagnostics.fatal(())?;
+    run_phase("inline", &mut hook, || {
+      diagnostics.start_pass();
+      if let Err(e) = inline_hvm_book(&mut hvm_book) {
+        diagnostics.add_book_error(format!("During inlining:\n{:ERR_INDENT_SIZE$}{}", "", e));
+      }
+      diagnostics.fatal(())
+    })?;
   }
 
   if opts.prune {
-    let prune_entrypoints = vec![book.hvm_entrypoint().to_string()];
-    prune_hvm_book(&mut hvm_book, &prune_entrypoints);
+    run_phase("prune-nets", &mut hook, || {
+      let mut prune_entrypoints = vec![book.hvm_entrypoint().to_string()];
+      prune_entrypoints.extend(opts.roots.iter().flatten().map(|root| root.to_string()));
+      prune_hvm_book(&mut hvm_book, &prune_entrypoints);
+    });
   }
 
   if opts.check_net_size {
-    check_net_sizes(&hvm_book, &mut diagnostics)?;
+    let max_size = opts.max_net_size.unwrap_or(MAX_NET_SIZE);
+    run_phase("check-net-size", &mut hook, || check_net_sizes(&hvm_book, max_size, &mut diagnostics))?;
   }
 
-  add_recursive_priority(&mut hvm_book);
+  run_phase("add-recursive-priority", &mut hook, || add_recursive_priority(&mut hvm_book));
+
+  let name_map = hvm_name_map(book, &hvm_book);
+
+  Ok(CompileResult { hvm_book, labels, name_map, diagnostics })
+}
 
-  Ok(CompileResult { hvm_book, labels, diagnostics })
+/// Maps the name of each net of `hvm_book` to the definition of `book` that it was compiled from.
+fn hvm_name_map(book: &Book, hvm_book: &::hvm::ast::Book) -> HashMap<String, Name> {
+  hvm_book
+    .defs
+    .keys()
+    .map(|net_name| {
+      let source = if net_name == book.hvm_entrypoint() {
+        book.entrypoint.clone().unwrap_or_else(|| Name::new(net_name.as_str()))
+      } else {
+        Name::new(net_name.as_str()).def_name_from_generated()
+      };
+      (net_name.clone(), source)
+    })
+    .collect()
 }
 
+/// A phase of the compilation that has finished, as reported to the hook of [`compile_book_with_hook`]
+/// and [`run_book_with_hook`].
+#[derive(Clone, Copy, Debug)]
+pub struct PhaseInfo {
+  pub name: &'static str,
+  pub elapsed: Duration,
+}
+
+/// Runs a phase of the compilation, timing it only if there's a hook to report it to.
+fn run_phase<T>(
+  name: &'static str,
+  hook: &mut Option<&mut dyn FnMut(PhaseInfo)>,
+  phase: impl FnOnce() -> T,
+) -> T {
+  match hook {
+    Some(hook) => {
+      let start = Instant::now();
+      let res = phase();
+      hook(PhaseInfo { name, elapsed: start.elapsed() });
+      res
+    }
+    None => phase(),
+  }
+}
+
+/// A step of [`desugar_book`]. Receives the program arguments that still have to be applied to the entrypoint.
+pub type DesugarPass = fn(&mut Ctx, &CompileOpts, &mut Option<Vec<Term>>) -> Result<(), Diagnostics>;
+
+/// The passes run by [`desugar_book`], in order, with their names.
+pub const DESUGAR_PASSES: &[(&str, DesugarPass)] = &[
+  ("check-shared-names", |ctx, _, _| {
+    ctx.check_shared_names();
+    Ok(())
+  }),
+  ("add-host-defs", |ctx, _, _| ctx.add_host_defs()),
+  ("check-shadowed-builtins", |ctx, _, _| {
+    ctx.check_shadowed_builtins();
+    Ok(())
+  }),
+  ("check-numeric-overflow", |ctx, _, _| {
+    ctx.check_numeric_overflow();
+    Ok(())
+  }),
+  ("set-entrypoint", |ctx, _, _| {
+    ctx.set_entrypoint();
+    Ok(())
+  }),
+  ("prune-unreachable", |ctx, opts, args| match &opts.roots {
+    Some(roots) => ctx.prune_unreachable(roots, args.as_deref()),
+    None => Ok(()),
+  }),
+  ("encode-adts", |ctx, opts, _| {
+    ctx.book.encode_adts(opts.adt_encoding);
+    Ok(())
+  }),
+  ("fix-match-defs", |ctx, _, _| ctx.fix_match_defs()),
+  ("apply-args", |ctx, _, args| ctx.apply_args(args.take())),
+  ("desugar-open", |ctx, _, _| ctx.desugar_open()),
+  ("encode-builtins", |ctx, _, _| {
+    ctx.book.encode_builtins();
+    Ok(())
+  }),
+  ("resolve-refs", |ctx, _, _| ctx.resolve_refs()),
+  ("check-ctr-arity", |ctx, _, _| {
+    ctx.check_ctr_arity();
+    Ok(())
+  }),
+  ("check-shadowed-binds", |ctx, _, _| {
+    ctx.check_shadowed_binds();
+    Ok(())
+  }),
+  ("check-unused-binds", |ctx, _, _| {
+    ctx.check_unused_binds();
+    Ok(())
+  }),
+  ("check-naming-convention", |ctx, _, _| {
+    ctx.check_naming_convention();
+    Ok(())
+  }),
+  ("check-redundant-lambdas", |ctx, _, _| {
+    ctx.check_redundant_lambdas();
+    Ok(())
+  }),
+  ("check-match-arm-order", |ctx, opts, _| {
+    // Only with NumScott the arms are dispatched by the numeric tag of the constructors.
+    if let AdtEncoding::NumScott = opts.adt_encoding {
+      ctx.check_match_arm_order();
+    }
+    Ok(())
+  }),
+  ("desugar-match-defs", |ctx, _, _| ctx.desugar_match_defs()),
+  ("fix-match-terms", |ctx, _, _| ctx.fix_match_terms()),
+  ("desugar-bend", |ctx, _, _| ctx.desugar_bend()),
+  ("desugar-fold", |ctx, _, _| ctx.desugar_fold()),
+  ("desugar-with-blocks", |ctx, _, _| ctx.desugar_with_blocks()),
+  ("check-unbound-vars", |ctx, _, _| ctx.check_unbound_vars()),
+  ("check-missing-base-case", |ctx, _, _| {
+    ctx.check_missing_base_case();
+    Ok(())
+  }),
+  ("check-potential-exponential", |ctx, _, _| {
+    ctx.check_potential_exponential();
+    Ok(())
+  }),
+  ("specialize", |ctx, opts, _| {
+    if opts.specialize {
+      ctx.specialize();
+    }
+    Ok(())
+  }),
+  ("linearize-matches", |ctx, opts, _| {
+    // Auto match linearization
+    ctx.book.make_var_names_unique();
+    match opts.linearize_matches {
+      OptLevel::Disabled => (),
+      OptLevel::Alt => ctx.book.linearize_match_binds(),
+      OptLevel::Enabled => ctx.book.linearize_matches(),
+    }
+    // Manual match linearization
+    ctx.book.linearize_match_with();
+    Ok(())
+  }),
+  ("instrument-coverage", |ctx, opts, _| {
+    if opts.coverage {
+      ctx.book.instrument_coverage();
+    }
+    Ok(())
+  }),
+  ("encode-matches", |ctx, opts, _| {
+    ctx.book.encode_matches(opts.adt_encoding);
+    if opts.coverage {
+      ctx.book.eta_reduce_coverage_arms();
+    }
+    // sanity check
+    ctx.check_unbound_vars()
+  }),
+  ("desugar-use", |ctx, _, _| {
+    ctx.book.make_var_names_unique();
+    ctx.book.desugar_use();
+    Ok(())
+  }),
+  ("fold-constants", |ctx, opts, _| {
+    if opts.const_fold {
+      ctx.fold_constants()?;
+    }
+    Ok(())
+  }),
+  ("eliminate-common-subexpressions", |ctx, opts, _| {
+    if opts.cse {
+      ctx.book.eliminate_common_subexpressions();
+    }
+    Ok(())
+  }),
+  ("eta-expand", |ctx, opts, _| {
+    if opts.eta_expand {
+      ctx.book.eta_expand();
+    }
+    Ok(())
+  }),
+  ("linearize-vars", |ctx, _, _| {
+    ctx.book.make_var_names_unique();
+    ctx.book.linearize_vars();
+    // sanity check
+    ctx.check_unbound_vars()
+  }),
+  // Optimizing passes
+  ("float-combinators", |ctx, opts, _| {
+    if opts.float_combinators {
+      ctx.book.float_combinators(MAX_NET_SIZE);
+    }
+    Ok(())
+  }),
+  ("check-unbound-refs", |ctx, _, _| ctx.check_unbound_refs()),
+  ("prune", |ctx, opts, _| {
+    ctx.prune(opts.prune, opts.roots.as_deref().unwrap_or_default());
+    Ok(())
+  }),
+  ("merge-definitions", |ctx, opts, _| {
+    if opts.merge {
+      ctx.book.merge_definitions();
+    }
+    Ok(())
+  }),
+  ("dedup-definitions", |ctx, opts, _| {
+    if opts.dedup_defs {
+      ctx.dedup_definitions();
+    }
+    Ok(())
+  }),
+  ("make-var-names-unique", |ctx, _, _| {
+    ctx.book.make_var_names_unique();
+    Ok(())
+  }),
+];
+
+/// Desugars the book in place into the core language that can be compiled to HVM.
+///
+/// Nothing is printed. On success, returns the warnings found during desugaring, which the caller
+/// can display with `{diagnostics}`. If there were any errors, returns all the diagnostics instead,
+/// and the book is left partially desugared.
 pub fn desugar_book(
   book: &mut Book,
   opts: CompileOpts,
   diagnostics_cfg: DiagnosticsConfig,
   args: Option<Vec<Term>>,
 ) -> Result<Diagnostics, Diagnostics> {
-  let mut ctx = Ctx::new(book, diagnostics_cfg);
-
-  ctx.check_shared_names();
-
-  ctx.set_entrypoint();
-
-  ctx.book.encode_adts(opts.adt_encoding);
-
-  ctx.fix_match_defs()?;
-
-  ctx.apply_args(args)?;
-
-  ctx.desugar_open()?;
-
-  ctx.book.encode_builtins();
-
-  ctx.resolve_refs()?;
-
-  ctx.desugar_match_defs()?;
-
-  ctx.fix_match_terms()?;
-
-  ctx.desugar_bend()?;
-  ctx.desugar_fold()?;
-  ctx.desugar_with_blocks()?;
-
-  ctx.check_unbound_vars()?;
-
-  // Auto match linearization
-  ctx.book.make_var_names_unique();
-  match opts.linearize_matches {
-    OptLevel::Disabled => (),
-    OptLevel::Alt => ctx.book.linearize_match_binds(),
-    OptLevel::Enabled => ctx.book.linearize_matches(),
-  }
-  // Manual match linearization
-  ctx.book.linearize_match_with();
-
-  ctx.book.encode_matches(opts.adt_encoding);
-
-  // sanity check
-  ctx.check_unbound_vars()?;
-
-  ctx.book.make_var_names_unique();
-  ctx.book.desugar_use();
-  ctx.book.make_var_names_unique();
-  ctx.book.linearize_vars();
-
-  // sanity check
-  ctx.check_unbound_vars()?;
-
-  // Optimizing passes
-  if opts.float_combinators {
-    ctx.book.float_combinators(MAX_NET_SIZE);
-  }
+  desugar_book_until(book, opts, diagnostics_cfg, args, None, None)
+}
 
-  ctx.check_unbound_refs()?;
+/// Like [`desugar_book`], but leaves `book` untouched and returns a desugared copy of it
+/// together with the warnings, so that it can be displayed with either `{book}` or
+/// [`Book::display_pretty`].
+///
+/// ```
+/// use bend::{desugared_book, diagnostics::DiagnosticsConfig, load_reader_to_book, CompileOpts};
+/// use std::path::Path;
+///
+/// let code = "main = (λx x 1)";
+/// let book = load_reader_to_book(code.as_bytes(), Path::new("example.bend")).unwrap();
+/// let (desugared, diagnostics) =
+///   desugared_book(&book, CompileOpts::default(), DiagnosticsConfig::default(), None).unwrap();
+///
+/// assert!(!diagnostics.has_errors());
+/// println!("{desugared}");
+/// println!("{}", desugared.display_pretty());
+/// // The original book can still be used, for example to desugar it with other options.
+/// assert_ne!(book.to_string(), desugared.to_string());
+/// ```
+pub fn desugared_book(
+  book: &Book,
+  opts: CompileOpts,
+  diagnostics_cfg: DiagnosticsConfig,
+  args: Option<Vec<Term>>,
+) -> Result<(Book, Diagnostics), Diagnostics> {
+  let mut book = book.clone();
+  let diagnostics = desugar_book(&mut book, opts, diagnostics_cfg, args)?;
+  Ok((book, diagnostics))
+}
 
-  ctx.prune(opts.prune);
+/// Runs the passes of [`desugar_book`] up to and including the one named `stop_after`.
+/// If `stop_after` is `None`, all the passes are run.
+/// If a `hook` is given, it's called after each pass, like in [`compile_book_with_hook`].
+pub fn desugar_book_until(
+  book: &mut Book,
+  opts: CompileOpts,
+  diagnostics_cfg: DiagnosticsConfig,
+  mut args: Option<Vec<Term>>,
+  stop_after: Option<&str>,
+  mut hook: Option<&mut dyn FnMut(PhaseInfo)>,
+) -> Result<Diagnostics, Diagnostics> {
+  let mut ctx = Ctx::new(book, diagnostics_cfg);
 
-  if opts.merge {
-    ctx.book.merge_definitions();
+  for (name, pass) in DESUGAR_PASSES {
+    run_phase(name, &mut hook, || pass(&mut ctx, &opts, &mut args))?;
+    if stop_after == Some(*name) {
+      break;
+    }
   }
 
-  ctx.book.make_var_names_unique();
-
   if !ctx.info.has_errors() {
     Ok(ctx.info)
   } else {
@@ -158,45 +531,242 @@ pub fn desugar_book(
 }
 
 pub fn run_book(
+  book: Book,
+  run_opts: RunOpts,
+  compile_opts: CompileOpts,
+  diagnostics_cfg: DiagnosticsConfig,
+  args: Option<Vec<Term>>,
+  cmd: &str,
+) -> Result<Option<(Term, RunStats, Diagnostics)>, Diagnostics> {
+  run_book_with_hook(book, run_opts, compile_opts, diagnostics_cfg, args, cmd, None)
+}
+
+/// Like [`run_book`], but calls `hook` after each phase, like [`compile_book_with_hook`].
+///
+/// After the phases of the compilation come the evaluation of the program, `run-hvm`
+/// or `interpret` when using the built-in interpreter, and the readback of its result, `readback`.
+pub fn run_book_with_hook(
   mut book: Book,
   run_opts: RunOpts,
   compile_opts: CompileOpts,
   diagnostics_cfg: DiagnosticsConfig,
   args: Option<Vec<Term>>,
   cmd: &str,
-) -> Result<Option<(Term, String, Diagnostics)>, Diagnostics> {
-  let CompileResult { hvm_book: core_book, labels, diagnostics } =
-    compile_book(&mut book, compile_opts.clone(), diagnostics_cfg, args)?;
+  mut hook: Option<&mut dyn FnMut(PhaseInfo)>,
+) -> Result<Option<(Term, RunStats, Diagnostics)>, Diagnostics> {
+  let compile_opts = if run_opts.coverage { compile_opts.set_coverage() } else { compile_opts };
+  let compile_hook = hook.as_mut().map(|hook| &mut **hook as &mut dyn FnMut(PhaseInfo));
+  let CompileResult { hvm_book: core_book, labels, diagnostics, .. } =
+    compile_book_with_hook(&mut book, compile_opts.clone(), diagnostics_cfg, args, compile_hook)?;
 
   // TODO: Printing should be taken care by the cli module, but we'd
   // like to print any warnings before running so that the user can
   // cancel the run if a problem is detected.
   eprint!("{diagnostics}");
 
-  let out = run_hvm(&core_book, cmd, &run_opts)?;
-  let (net, stats) = parse_hvm_output(&out)?;
-  let (term, diags) =
-    readback_hvm_net(&net, &book, &labels, run_opts.linear_readback, compile_opts.adt_encoding);
+  let (net, stats) = if run_opts.profile || run_opts.trace || run_opts.coverage {
+    if run_opts.threads.is_some() {
+      return Err(
+        "The built-in interpreter is sequential, so the number of threads can't be set.".to_string().into(),
+      );
+    }
+    if let Some(out_path) = &run_opts.emit_hvm {
+      std::fs::write(out_path, display_hvm_book(&core_book).to_string())
+        .map_err(|e| format!("Failed to write HVM file '{}'.\n{e}", out_path.display()))?;
+    }
+    let interpreter_opts = InterpreterOpts {
+      timeout: run_opts.timeout,
+      profile: run_opts.profile,
+      trace: run_opts.trace,
+      trace_limit: run_opts.trace_limit,
+      coverage: run_opts.coverage.then(|| book.coverage_arms.clone()),
+      breadth_first: false,
+    };
+    run_phase("interpret", &mut hook, || interpret_hvm_book(&core_book, &interpreter_opts))?
+  } else {
+    run_phase("run-hvm", &mut hook, || parse_hvm_output(&run_hvm(&core_book, cmd, &run_opts)?))?
+  };
+  let (term, diags) = run_phase("readback", &mut hook, || {
+    readback_hvm_net(
+      &net,
+      &book,
+      &labels,
+      run_opts.linear_readback,
+      compile_opts.adt_encoding,
+      run_opts.readback_depth_limit,
+    )
+  });
 
   Ok(Some((term, stats, diags)))
 }
 
+/// Like [`run_book`], but evaluates the book in this process and returns its result as a stream
+/// of chunks, read back while the evaluation goes on.
+///
+/// If the result is a list, each of its elements is yielded as soon as it's computed, and the
+/// evaluation only goes on while more elements are requested, so a consumer can stop early on
+/// very large or infinite lists. Any other result is yielded as a single [`TermChunk::Rest`].
+///
+/// ```
+/// use bend::diagnostics::{DiagnosticsConfig, Severity};
+/// use bend::{fun::Term, hvm::stream::TermChunk};
+/// use bend::{load_reader_to_book, run_book_streaming, CompileOpts, RunOpts};
+/// use std::path::Path;
+///
+/// // An infinite list, which can only be evaluated eagerly without the recursion cycle check.
+/// let code = "
+/// nats n = (List/Cons n (nats (+ n 1)))
+/// main = (nats 0)
+/// ";
+/// let book = load_reader_to_book(code.as_bytes(), Path::new("example.bend")).unwrap();
+/// let diagnostics_cfg = DiagnosticsConfig { recursion_cycle: Severity::Allow, ..DiagnosticsConfig::default() };
+/// let stream = run_book_streaming(book, RunOpts::default(), CompileOpts::default(), diagnostics_cfg, None);
+/// let first = stream.unwrap().take(3).map(Result::unwrap).collect::<Vec<_>>();
+///
+/// assert_eq!(first, [0, 1, 2].map(|n| TermChunk::Element(Term::Num { val: bend::fun::Num::U24(n) })));
+/// ```
+pub fn run_book_streaming(
+  mut book: Book,
+  run_opts: RunOpts,
+  compile_opts: CompileOpts,
+  diagnostics_cfg: DiagnosticsConfig,
+  args: Option<Vec<Term>>,
+) -> Result<ResultStream, Diagnostics> {
+  let CompileResult { hvm_book: core_book, labels, diagnostics, .. } =
+    compile_book(&mut book, compile_opts.clone(), diagnostics_cfg, args)?;
+
+  eprint!("{diagnostics}");
+
+  let interpreter_opts = InterpreterOpts {
+    timeout: run_opts.timeout,
+    profile: run_opts.profile,
+    trace: run_opts.trace,
+    trace_limit: run_opts.trace_limit,
+    coverage: None,
+    breadth_first: true,
+  };
+  let interpreter = Interpreter::new(&core_book, &interpreter_opts)?;
+  Ok(ResultStream::new(
+    interpreter,
+    book,
+    labels,
+    run_opts.linear_readback,
+    compile_opts.adt_encoding,
+    run_opts.readback_depth_limit,
+  ))
+}
+
+/// Evaluates a single term as the body of the entrypoint of the given book, returning its result.
+///
+/// The term can reference the definitions of the book, which is left untouched. The definitions
+/// that were the entrypoint of the book, like `main`, `Main` or one marked with `#[entrypoint]`,
+/// are kept as normal definitions, so the term can also reference them.
+///
+/// ```
+/// use bend::{eval_term, fun::{Num, Term}, load_reader_to_book, CompileOpts, RunOpts};
+/// use std::path::Path;
+///
+/// let code = "
+/// double x = (* x 2)
+/// main = (double 3)
+/// ";
+/// let book = load_reader_to_book(code.as_bytes(), Path::new("example.bend")).unwrap();
+/// let term = Term::app(Term::r#ref("double"), Term::r#ref("main"));
+/// let (result, _) = eval_term(&book, term, RunOpts::default(), CompileOpts::default()).unwrap();
+///
+/// assert_eq!(result, Term::Num { val: Num::U24(12) });
+/// ```
+pub fn eval_term(
+  book: &Book,
+  term: Term,
+  run_opts: RunOpts,
+  compile_opts: CompileOpts,
+) -> Result<(Term, RunStats), Diagnostics> {
+  let (term, stats, mut diags) =
+    eval_term_with_diagnostics(book, term, run_opts, compile_opts, DiagnosticsConfig::default())?;
+  diags.fatal((term, stats))
+}
+
+/// Like [`eval_term`], but checks the book with the given diagnostics config and also returns
+/// the diagnostics of the readback of the result.
+pub fn eval_term_with_diagnostics(
+  book: &Book,
+  term: Term,
+  run_opts: RunOpts,
+  compile_opts: CompileOpts,
+  diagnostics_cfg: DiagnosticsConfig,
+) -> Result<(Term, RunStats, Diagnostics), Diagnostics> {
+  let mut diagnostics = Diagnostics::new(diagnostics_cfg);
+  let unbound =
+    term.free_vars().into_keys().filter(|nam| !book.defs.contains_key(nam) && !book.ctrs.contains_key(nam));
+  for nam in unbound.sorted() {
+    diagnostics.add_book_error(format!("Unbound variable '{nam}' in the evaluated term."));
+  }
+  diagnostics.fatal(())?;
+
+  // The term is added with a name of its own first, so that its references to the entrypoints
+  // of the book follow them when they're renamed out of the way of the new entrypoint.
+  let mut book = book.clone();
+  let eval = Name::new(format!("{ENTRY_POINT}__eval"));
+  let rule = Rule { pats: vec![], body: term };
+  book.defs.insert(eval.clone(), Definition { name: eval.clone(), rules: vec![rule], builtin: false });
+  for entrypoint in [ENTRY_POINT, HVM1_ENTRY_POINT].map(Name::new) {
+    if book.defs.contains_key(&entrypoint) {
+      book.rename_def(&entrypoint, &Name::new(format!("{entrypoint}__user")))?;
+    }
+  }
+  book.rename_def(&eval, &Name::new(ENTRY_POINT))?;
+  book.entrypoint = None;
+
+  let res = run_book(book, run_opts, compile_opts, diagnostics_cfg, None, "run")?;
+  Ok(res.ok_or("The evaluated term didn't produce a result.".to_string())?)
+}
+
 pub fn readback_hvm_net(
   net: &::hvm::ast::Net,
   book: &Book,
   labels: &Labels,
   linear: bool,
   adt_encoding: AdtEncoding,
+  depth_limit: Option<usize>,
 ) -> (Term, Diagnostics) {
   let mut diags = Diagnostics::default();
   let net = hvm_to_net(net);
-  let mut term = net_to_term(&net, book, labels, linear, &mut diags);
+  let mut term = net_to_term(&net, book, labels, linear, depth_limit, &mut diags);
   term.expand_generated(book);
+  if let AdtEncoding::TaggedScott = adt_encoding {
+    term.resugar_tagged_scott(book);
+  }
   term.resugar_strings(adt_encoding);
   term.resugar_lists(adt_encoding);
   (term, diags)
 }
 
+/// Writes an HVM book to a new temporary file, returning its path.
+///
+/// The file is created in `dir`, or in the system's temporary directory (`TMPDIR`) if none is given.
+/// Its name is unique, so concurrent compilations don't overwrite each other's files.
+pub fn write_temp_hvm_file(book: &::hvm::ast::Book, dir: Option<&Path>) -> Result<PathBuf, String> {
+  static FILE_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+  let dir = dir.map_or_else(std::env::temp_dir, Path::to_path_buf);
+  loop {
+    let count = FILE_COUNT.fetch_add(1, Ordering::Relaxed);
+    let path = dir.join(format!("bend-{}-{count}.hvm", std::process::id()));
+    match std::fs::OpenOptions::new().write(true).create_new(true).open(&path) {
+      Ok(mut file) => {
+        return file
+          .write_all(display_hvm_book(book).to_string().as_bytes())
+          .map(|_| path.clone())
+          .map_err(|e| format!("Failed to write HVM file '{}'.\n{e}", path.display()));
+      }
+      // Left behind by another process with the same id, try the next name.
+      Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => continue,
+      Err(e) => return Err(format!("Failed to create HVM file in '{}'.\n{e}", dir.display())),
+    }
+  }
+}
+
 /// Runs an HVM book by invoking HVM as a subprocess.
 fn run_hvm(book: &::hvm::ast::Book, cmd: &str, run_opts: &RunOpts) -> Result<String, String> {
   fn filter_hvm_output(
@@ -245,29 +815,225 @@ fn run_hvm(book: &::hvm::ast::Book, cmd: &str, run_opts: &RunOpts) -> Result<Str
     }
   }
 
-  let out_path = ".out.hvm";
-  std::fs::write(out_path, display_hvm_book(book).to_string()).map_err(|x| x.to_string())?;
-  let mut process = std::process::Command::new(run_opts.hvm_path.clone())
-    .arg(cmd)
-    .arg(out_path)
-    .stdout(std::process::Stdio::piped())
-    .spawn()
-    .map_err(|e| format!("Failed to start hvm process.\n{e}"))?;
+  // The parallel runtimes can't be made to reduce in a fixed order.
+  if run_opts.deterministic && cmd != "run" {
+    return Err(format!(
+      "Can't evaluate deterministically with '{cmd}', only with the sequential runtime 'run'."
+    ));
+  }
+
+  // Unless asked to keep it, the book is written to a temporary file that is removed after running.
+  let out_path = match &run_opts.emit_hvm {
+    Some(out_path) => {
+      std::fs::write(out_path, display_hvm_book(book).to_string())
+        .map_err(|e| format!("Failed to write HVM file '{}'.\n{e}", out_path.display()))?;
+      out_path.clone()
+    }
+    None => write_temp_hvm_file(book, run_opts.temp_dir.as_deref())?,
+  };
+  let remove_out_files = |program: Option<&Path>| {
+    if run_opts.emit_hvm.is_none() {
+      if let Err(e) = std::fs::remove_file(&out_path) {
+        eprintln!("Error removing HVM output file. {e}");
+      }
+    }
+    if let Some(program) = program {
+      if let Err(e) = std::fs::remove_file(program) {
+        eprintln!("Error removing the compiled program. {e}");
+      }
+    }
+  };
+  // HVM only takes the number of threads when it's compiled, so the program is compiled with it.
+  let program = match run_opts.threads {
+    Some(threads) => match compile_with_threads(&out_path, cmd, threads, run_opts) {
+      Ok(program) => Some(program),
+      Err(e) => {
+        remove_out_files(None);
+        return Err(e);
+      }
+    },
+    None => None,
+  };
+  let mut command = match &program {
+    Some(program) => std::process::Command::new(program),
+    None => {
+      let mut command = std::process::Command::new(run_opts.hvm_path.clone());
+      command.arg(cmd).arg(&out_path).args(&run_opts.backend_flags);
+      command
+    }
+  };
+  let spawn_res = command.stdout(std::process::Stdio::piped()).spawn();
+  let mut process = match spawn_res {
+    Ok(process) => process,
+    Err(e) => {
+      remove_out_files(program.as_deref());
+      return Err(format!("Failed to start hvm process.\n{e}"));
+    }
+  };
 
   let child_out = std::mem::take(&mut process.stdout).expect("Failed to attach to hvm output");
   let thread_out = std::thread::spawn(move || filter_hvm_output(child_out, std::io::stdout()));
 
-  let _ = process.wait().expect("Failed to wait on hvm subprocess");
-  if let Err(e) = std::fs::remove_file(out_path) {
-    eprintln!("Error removing HVM output file. {e}");
+  let exceeded_limit = if run_opts.timeout.is_some() || run_opts.max_memory.is_some() {
+    wait_with_limits(&mut process, run_opts)
+  } else {
+    let _ = process.wait().expect("Failed to wait on hvm subprocess");
+    None
+  };
+  remove_out_files(program.as_deref());
+
+  let result = thread_out.join().map_err(|_| "HVM output thread panicked.".to_string())?;
+  match exceeded_limit {
+    Some(ExceededLimit::Time) => {
+      let timeout = run_opts.timeout.unwrap();
+      Err(format!("The program exceeded the time limit of {timeout:?} and was stopped."))
+    }
+    Some(ExceededLimit::Memory) => {
+      let max_memory = run_opts.max_memory.unwrap();
+      Err(format!("The program exceeded the memory limit of {max_memory} bytes and was stopped."))
+    }
+    None => result,
+  }
+}
+
+/// Generates the C or Cuda program of the HVM book at `book_path` for the runtime of `cmd`, sets
+/// its number of threads and compiles it, returning the path of the executable.
+fn compile_with_threads(
+  book_path: &Path,
+  cmd: &str,
+  threads: usize,
+  run_opts: &RunOpts,
+) -> Result<PathBuf, String> {
+  let (gen_cmd, extension, compiler, compiler_args): (_, _, _, &[_]) = match cmd {
+    "run-c" => ("gen-c", ".c", "cc", &["-O2", "-lm", "-lpthread"]),
+    "run-cu" => ("gen-cu", ".cu", "nvcc", &["-O3", "-w"]),
+    _ => return Err("The Rust runtime of HVM is sequential, so the number of threads can't be set.".into()),
+  };
+  if !threads.is_power_of_two() {
+    return Err(format!("The number of threads must be a power of two, found {threads}."));
+  }
+
+  let output = std::process::Command::new(&run_opts.hvm_path)
+    .arg(gen_cmd)
+    .arg(book_path)
+    .args(&run_opts.backend_flags)
+    .output()
+    .map_err(|e| format!("Failed to start hvm process.\n{e}"))?;
+  if !output.status.success() {
+    return Err(format!("Failed to generate the program.\n{}", String::from_utf8_lossy(&output.stderr)));
+  }
+  let code = String::from_utf8_lossy(&output.stdout);
+  let code = match cmd {
+    "run-c" => set_c_threads(&code, threads)?,
+    _ => set_cuda_threads(&code, threads)?,
+  };
+
+  // Named after the book so that they don't overwrite other files.
+  let with_suffix = |suffix: &str| {
+    let mut path = book_path.as_os_str().to_owned();
+    path.push(suffix);
+    PathBuf::from(path)
+  };
+  let code_path = with_suffix(extension);
+  let program_path = with_suffix(".out");
+  std::fs::write(&code_path, code)
+    .map_err(|e| format!("Failed to write the program '{}'.\n{e}", code_path.display()))?;
+  let compile_res = std::process::Command::new(compiler)
+    .arg(&code_path)
+    .arg("-o")
+    .arg(&program_path)
+    .args(compiler_args)
+    .output();
+  let _ = std::fs::remove_file(&code_path);
+  let output = compile_res.map_err(|e| format!("Failed to start the compiler '{compiler}'.\n{e}"))?;
+  if !output.status.success() {
+    return Err(format!("Failed to compile the program.\n{}", String::from_utf8_lossy(&output.stderr)));
+  }
+  Ok(program_path)
+}
+
+/// Sets the threads of the program generated by `hvm gen-c`, given by the log2 of the threads per core.
+fn set_c_threads(code: &str, threads: usize) -> Result<String, String> {
+  replace_definition(code, "#define TPC_L2 ", &format!("#define TPC_L2 {}", threads.ilog2()))
+}
+
+/// Sets the threads of the program generated by `hvm gen-cu`, which run in blocks of a fixed
+/// number of threads, by setting the number of blocks.
+fn set_cuda_threads(code: &str, threads: usize) -> Result<String, String> {
+  let tpb_l2 = code
+    .lines()
+    .find_map(|line| line.strip_prefix("const u32 TPB_L2 = ")?.strip_suffix(';')?.trim().parse::<u32>().ok())
+    .ok_or_else(|| "This version of HVM doesn't allow setting the number of threads.".to_string())?;
+  let Some(bpg_l2) = threads.ilog2().checked_sub(tpb_l2) else {
+    return Err(format!("The Cuda runtime needs at least {} threads, found {threads}.", 1usize << tpb_l2));
+  };
+  replace_definition(code, "const u32 BPG_L2 = ", &format!("const u32 BPG_L2 = {bpg_l2};"))
+}
+
+/// Replaces the line of the code that starts with `prefix` with `definition`.
+fn replace_definition(code: &str, prefix: &str, definition: &str) -> Result<String, String> {
+  let mut found = false;
+  let code = code
+    .lines()
+    .map(|line| {
+      if !found && line.starts_with(prefix) {
+        found = true;
+        definition
+      } else {
+        line
+      }
+    })
+    .join("\n");
+  if found {
+    Ok(code)
+  } else {
+    Err("This version of HVM doesn't allow setting the number of threads.".into())
+  }
+}
+
+/// A resource limit that the HVM process went over.
+enum ExceededLimit {
+  Time,
+  Memory,
+}
+
+/// Waits for the HVM process to finish, killing it if it runs for longer than the timeout
+/// or uses more memory than allowed. Returns which limit was exceeded, if any.
+fn wait_with_limits(process: &mut std::process::Child, run_opts: &RunOpts) -> Option<ExceededLimit> {
+  let deadline = run_opts.timeout.map(|timeout| Instant::now() + timeout);
+  loop {
+    if process.try_wait().expect("Failed to wait on hvm subprocess").is_some() {
+      return None;
+    }
+    let out_of_time = deadline.is_some_and(|deadline| Instant::now() >= deadline);
+    let out_of_memory = || run_opts.max_memory.is_some_and(|max| process_memory(process.id()) > Some(max));
+    let exceeded_limit = if out_of_time {
+      Some(ExceededLimit::Time)
+    } else if out_of_memory() {
+      Some(ExceededLimit::Memory)
+    } else {
+      None
+    };
+    if exceeded_limit.is_some() {
+      let _ = process.kill();
+      let _ = process.wait();
+      return exceeded_limit;
+    }
+    std::thread::sleep(Duration::from_millis(10));
   }
+}
 
-  let result = thread_out.join().map_err(|_| "HVM output thread panicked.".to_string())??;
-  Ok(result)
+/// The resident memory of a process in bytes.
+/// Only available on systems that have a `/proc` filesystem, like Linux.
+fn process_memory(pid: u32) -> Option<usize> {
+  let status = std::fs::read_to_string(format!("/proc/{pid}/status")).ok()?;
+  let rss = status.lines().find_map(|line| line.strip_prefix("VmRSS:"))?;
+  let kb: usize = rss.trim().strip_suffix("kB")?.trim().parse().ok()?;
+  Some(kb * 1024)
 }
 
 /// Reads the final output from HVM and separates the extra information.
-fn parse_hvm_output(out: &str) -> Result<(::hvm::ast::Net, String), String> {
+fn parse_hvm_output(out: &str) -> Result<(::hvm::ast::Net, RunStats), String> {
   let Some((result, stats)) = out.split_once('\n') else {
     return Err(format!(
       "Failed to parse result from HVM (unterminated result).\nOutput from HVM was:\n{:?}",
@@ -278,7 +1044,68 @@ fn parse_hvm_output(out: &str) -> Result<(::hvm::ast::Net, String), String> {
   let Ok(net) = p.parse_net() else {
     return Err(format!("Failed to parse result from HVM (invalid net).\nOutput from HVM was:\n{:?}", out));
   };
-  Ok((net, stats.to_string()))
+  Ok((net, RunStats::parse(stats)))
+}
+
+/// The statistics reported by HVM after running a program.
+///
+/// Each field is `None` if the runtime that was used doesn't report it.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RunStats {
+  /// Number of interactions performed during the evaluation.
+  pub interactions: Option<u64>,
+  /// Number of nodes leaked by the evaluation.
+  pub leaks: Option<u64>,
+  /// Time taken by the evaluation.
+  pub elapsed: Option<Duration>,
+  /// Millions of interactions performed per second.
+  pub mips: Option<f64>,
+  /// Interactions performed by each definition, only available when the book was profiled.
+  pub profile: Option<Profile>,
+  /// The match arms that were executed, only available when measuring the coverage.
+  pub coverage: Option<Coverage>,
+}
+
+impl RunStats {
+  /// Parses the `- NAME: value` lines that HVM prints after the result.
+  fn parse(stats: &str) -> Self {
+    let mut res = RunStats::default();
+    for line in stats.lines() {
+      let Some((name, value)) = line.strip_prefix("- ").and_then(|line| line.split_once(": ")) else {
+        continue;
+      };
+      match name {
+        "ITRS" => res.interactions = value.parse().ok(),
+        "LEAK" => res.leaks = value.parse().ok(),
+        "TIME" => {
+          res.elapsed =
+            value.trim_end_matches('s').parse().ok().and_then(|s| Duration::try_from_secs_f64(s).ok())
+        }
+        "MIPS" => res.mips = value.parse().ok(),
+        _ => {}
+      }
+    }
+    res
+  }
+}
+
+impl std::fmt::Display for RunStats {
+  /// Displays the stats the same way HVM prints them.
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    if let Some(interactions) = self.interactions {
+      writeln!(f, "- ITRS: {interactions}")?;
+    }
+    if let Some(leaks) = self.leaks {
+      writeln!(f, "- LEAK: {leaks}")?;
+    }
+    if let Some(elapsed) = self.elapsed {
+      writeln!(f, "- TIME: {:.2}s", elapsed.as_secs_f64())?;
+    }
+    if let Some(mips) = self.mips {
+      writeln!(f, "- MIPS: {mips:.2}")?;
+    }
+    Ok(())
+  }
 }
 
 #[derive(Clone, Debug)]
@@ -286,11 +1113,61 @@ pub struct RunOpts {
   pub linear_readback: bool,
   pub pretty: bool,
   pub hvm_path: String,
+  /// Maximum wall-clock time the program is allowed to run for.
+  pub timeout: Option<Duration>,
+  /// Where to save the generated HVM book instead of a temporary file.
+  pub emit_hvm: Option<PathBuf>,
+  /// Directory for the temporary HVM file, instead of the system's temporary directory.
+  pub temp_dir: Option<PathBuf>,
+  /// Evaluates with the sequential HVM runtime, so that the results and stats are reproducible.
+  /// Running with one of the parallel runtimes, like `run-c`, is an error when set.
+  pub deterministic: bool,
+  /// Maximum memory, in bytes, the HVM process is allowed to use.
+  pub max_memory: Option<usize>,
+  /// Evaluates in this process instead of with HVM, counting the interactions of each definition.
+  pub profile: bool,
+  /// Evaluates in this process instead of with HVM, printing each interaction.
+  pub trace: bool,
+  /// Maximum number of interactions printed when tracing.
+  pub trace_limit: Option<u64>,
+  /// Evaluates in this process instead of with HVM, counting how many times each match arm is executed.
+  /// The book is compiled with [`CompileOpts::set_coverage`].
+  pub coverage: bool,
+  /// How the floats of the result are displayed.
+  pub float_format: FloatFormat,
+  /// Number of threads used by the parallel runtimes, instead of the ones chosen by HVM.
+  /// Must be a power of two.
+  pub threads: Option<usize>,
+  /// Maximum depth of the result that is read back. Deeper subterms are reported and replaced
+  /// with errors, so that very deep results don't exhaust the memory of the readback.
+  pub readback_depth_limit: Option<usize>,
+  /// Extra arguments given to the HVM process, for options that Bend doesn't have.
+  pub backend_flags: Vec<String>,
 }
 
+/// The default of [`RunOpts::readback_depth_limit`].
+pub const READBACK_DEPTH_LIMIT: usize = 1 << 26;
+
 impl Default for RunOpts {
   fn default() -> Self {
-    RunOpts { linear_readback: false, pretty: false, hvm_path: "hvm".to_string() }
+    RunOpts {
+      linear_readback: false,
+      pretty: false,
+      hvm_path: "hvm".to_string(),
+      timeout: None,
+      emit_hvm: None,
+      temp_dir: None,
+      deterministic: false,
+      max_memory: None,
+      profile: false,
+      trace: false,
+      trace_limit: None,
+      coverage: false,
+      float_format: FloatFormat::Rounded,
+      threads: None,
+      readback_depth_limit: Some(READBACK_DEPTH_LIMIT),
+      backend_flags: vec![],
+    }
   }
 }
 
@@ -317,6 +1194,12 @@ pub struct CompileOpts {
   /// Enables [hvm::eta_reduce].
   pub eta: bool,
 
+  /// Enables [fun::transform::eta_expand]. Experimental.
+  pub eta_expand: bool,
+
+  /// Enables [fun::transform::specialize].
+  pub specialize: bool,
+
   /// Enables [fun::transform::definition_pruning] and [hvm::prune].
   pub prune: bool,
 
@@ -329,14 +1212,36 @@ pub struct CompileOpts {
   /// Enables [fun::transform::definition_merge]
   pub merge: bool,
 
+  /// Enables [fun::transform::definition_dedup].
+  pub dedup_defs: bool,
+
+  /// Enables [fun::transform::fold_constants].
+  pub const_fold: bool,
+
+  /// Enables [fun::transform::eliminate_common_subexpressions].
+  pub cse: bool,
+
   /// Enables [hvm::inline].
   pub inline: bool,
 
+  /// Enables [hvm::optimize_nets].
+  pub optimize_nets: bool,
+
   /// Enables [hvm::check_net_size].
   pub check_net_size: bool,
 
+  /// The largest number of nodes a net can have when [hvm::check_net_size] is enabled.
+  /// Defaults to [hvm::check_net_size::MAX_NET_SIZE], the limit of the Cuda runtime.
+  pub max_net_size: Option<usize>,
+
   /// Determines the encoding of constructors and matches.
   pub adt_encoding: AdtEncoding,
+
+  /// If given, only these definitions, the entrypoint and the definitions they use are compiled.
+  pub roots: Option<Vec<Name>>,
+
+  /// Enables [fun::transform::instrument_coverage]. Set with [`CompileOpts::set_coverage`].
+  pub coverage: bool,
 }
 
 impl CompileOpts {
@@ -345,13 +1250,22 @@ impl CompileOpts {
   pub fn set_all(self) -> Self {
     Self {
       eta: true,
+      eta_expand: self.eta_expand,
+      specialize: self.specialize,
       prune: true,
       float_combinators: true,
       merge: true,
+      dedup_defs: true,
+      const_fold: true,
+      cse: true,
       inline: true,
+      optimize_nets: true,
       linearize_matches: OptLevel::Enabled,
       check_net_size: self.check_net_size,
+      max_net_size: self.max_net_size,
       adt_encoding: self.adt_encoding,
+      roots: self.roots,
+      coverage: self.coverage,
     }
   }
 
@@ -360,13 +1274,81 @@ impl CompileOpts {
   pub fn set_no_all(self) -> Self {
     Self {
       eta: false,
+      eta_expand: self.eta_expand,
+      specialize: self.specialize,
       prune: false,
       linearize_matches: OptLevel::Disabled,
       float_combinators: false,
       merge: false,
+      dedup_defs: false,
+      const_fold: false,
+      cse: false,
       inline: false,
+      optimize_nets: false,
       check_net_size: self.check_net_size,
+      max_net_size: self.max_net_size,
       adt_encoding: self.adt_encoding,
+      roots: self.roots,
+      coverage: self.coverage,
+    }
+  }
+
+  /// Sets the optimizing options to the ones of the given optimization level.
+  ///
+  /// * 0: Disables all optimizing options.
+  /// * 1: Enables only linearize_matches and float_combinators, which strict evaluation needs.
+  /// * 2: The default options, which also enable eta.
+  /// * 3: Enables all optimizing options, like [`CompileOpts::set_all`].
+  ///
+  /// Levels above 3 are the same as 3.
+  #[must_use]
+  pub fn preset(self, level: u8) -> Self {
+    match level {
+      0 => self.set_no_all(),
+      1 => Self {
+        eta: false,
+        prune: false,
+        linearize_matches: OptLevel::Enabled,
+        float_combinators: true,
+        merge: false,
+        dedup_defs: false,
+        const_fold: false,
+        cse: false,
+        inline: false,
+        optimize_nets: false,
+        ..self
+      },
+      2 => Self {
+        eta: true,
+        prune: false,
+        linearize_matches: OptLevel::Enabled,
+        float_combinators: true,
+        merge: false,
+        dedup_defs: false,
+        const_fold: false,
+        cse: false,
+        inline: false,
+        optimize_nets: false,
+        ..self
+      },
+      _ => self.set_all(),
+    }
+  }
+
+  /// Instruments the matches to count how many times each of their arms is executed,
+  /// disabling the optimizations that would inline or merge the instrumented arms.
+  #[must_use]
+  pub fn set_coverage(self) -> Self {
+    Self {
+      coverage: true,
+      linearize_matches: OptLevel::Enabled,
+      float_combinators: true,
+      merge: false,
+      dedup_defs: false,
+      cse: false,
+      inline: false,
+      optimize_nets: false,
+      ..self
     }
   }
 
@@ -390,13 +1372,22 @@ impl Default for CompileOpts {
   fn default() -> Self {
     Self {
       eta: true,
+      eta_expand: false,
+      specialize: false,
       prune: false,
       linearize_matches: OptLevel::Enabled,
       float_combinators: true,
       merge: false,
+      dedup_defs: false,
+      const_fold: false,
+      cse: false,
       inline: false,
+      optimize_nets: false,
       check_net_size: false,
+      max_net_size: None,
       adt_encoding: AdtEncoding::NumScott,
+      roots: None,
+      coverage: false,
     }
   }
 }
@@ -405,6 +1396,11 @@ impl Default for CompileOpts {
 pub enum AdtEncoding {
   Scott,
   NumScott,
+  /// Like `NumScott`, but with the constructor tags inlined as numbers instead of references.
+  ///
+  /// The tags are unique across the book, so a match subtracts the first tag of the matched type,
+  /// which only saves interactions over `NumScott` for `String`, whose first tag is 0.
+  TaggedScott,
 }
 
 impl std::fmt::Display for AdtEncoding {
@@ -412,6 +1408,7 @@ impl std::fmt::Display for AdtEncoding {
     match self {
       AdtEncoding::Scott => write!(f, "Scott"),
       AdtEncoding::NumScott => write!(f, "NumScott"),
+      AdtEncoding::TaggedScott => write!(f, "TaggedScott"),
     }
   }
 }
@@ -420,6 +1417,16 @@ pub struct CompileResult {
   pub diagnostics: Diagnostics,
   pub hvm_book: ::hvm::ast::Book,
   pub labels: Labels,
+  /// The source definition of each net of `hvm_book`, by the name of the net.
+  ///
+  /// The entrypoint net, `main`, is mapped to the entrypoint of the book, whatever its name.
+  /// Definitions generated by the compiler are mapped to the definition they were extracted from:
+  /// - `foo__C0`: a combinator floated out of `foo` by the `float-combinators` optimization.
+  /// - `foo__bend0` and `foo__fold0`: the recursive functions of a `bend` or a `fold` in `foo`.
+  ///
+  /// Only the definitions merged by the `merge` optimization, like `foo__M_bar`,
+  /// have no single source definition. They're mapped to the first one, `foo`.
+  pub name_map: HashMap<String, Name>,
 }
 
 fn maybe_grow<R, F>(f: F) -> R
@@ -428,3 +1435,37 @@ where
 {
   stacker::maybe_grow(1024 * 32, 1024 * 1024, f)
 }
+
+#[test]
+fn run_stats_parse() {
+  let stats = RunStats::parse("- ITRS: 1234\n- LEAK: 5\n- TIME: 0.25s\n- MIPS: 4.94\n");
+  assert_eq!(stats.interactions, Some(1234));
+  assert_eq!(stats.leaks, Some(5));
+  assert_eq!(stats.elapsed, Some(Duration::from_millis(250)));
+  assert_eq!(stats.mips, Some(4.94));
+
+  // The stats are read back the same way they're displayed.
+  let stats = RunStats {
+    interactions: Some(7),
+    leaks: Some(0),
+    elapsed: Some(Duration::from_millis(1500)),
+    mips: Some(0.01),
+    ..RunStats::default()
+  };
+  assert_eq!(RunStats::parse(&stats.to_string()), stats);
+
+  // The stats that a runtime doesn't print are `None`, and the lines that aren't stats are skipped.
+  let stats = RunStats::parse("Some log line\n- ITRS: 10\n");
+  assert_eq!(stats, RunStats { interactions: Some(10), ..RunStats::default() });
+
+  // So are the values that can't be parsed.
+  let stats = RunStats::parse("- ITRS: many\n- LEAK: -1\n- TIME: soon\n- MIPS:\nITRS: 5\n- ITRS 5\n");
+  assert_eq!(stats, RunStats::default());
+  assert_eq!(RunStats::parse(""), RunStats::default());
+
+  // Times that aren't a valid duration are also skipped instead of panicking.
+  for time in ["-1s", "NaN", "inf", "-inf", "1e300s"] {
+    let stats = RunStats::parse(&format!("- ITRS: 3\n- TIME: {time}\n"));
+    assert_eq!(stats, RunStats { interactions: Some(3), ..RunStats::default() }, "{time}");
+  }
+}
@@ -1,15 +1,36 @@
 use bend::{
-  check_book, compile_book, desugar_book,
-  diagnostics::{Diagnostics, DiagnosticsConfig, Severity},
-  fun::{Book, Name},
-  hvm::display_hvm_book,
-  load_file_to_book, run_book, AdtEncoding, CompileOpts, OptLevel, RunOpts,
+  check_book, compile_book_with_hook,
+  compile_cache::compile_book_cached,
+  desugar_book_until,
+  diagnostics::{Diagnostics, DiagnosticsConfig, DiagnosticsFormat, Severity},
+  eval_term_with_diagnostics, format_book,
+  fun::{
+    dependencies::{def_dependencies, dependency_order, reachable_defs},
+    display::FloatFormat,
+    json::{json_to_term, term_to_json},
+    parser::TermParser,
+    sexpr::term_to_sexpr,
+    Book, Definition, Name, Num, Pattern, Term,
+  },
+  hvm::{
+    check_net_size::count_nodes, display_annotated_hvm_book, display_hvm_book, dot::display_hvm_book_dot,
+    interpreter::Coverage, split_c::split_c_program,
+  },
+  incremental::{check_book_incremental, CACHE_FILE},
+  load_files_to_book_with_prelude, load_prelude_file,
+  repl::{parse_repl_line, ReplLine},
+  run_book, run_book_with_hook, write_temp_hvm_file, AdtEncoding, CompileOpts, OptLevel, PhaseInfo, RunOpts,
+  DESUGAR_PASSES, READBACK_DEPTH_LIMIT, STDIN_PATH,
 };
-use clap::{Args, CommandFactory, Parser, Subcommand};
+use clap::{Args, CommandFactory, Parser, Subcommand, ValueEnum};
 use std::{
+  collections::{BTreeSet, HashMap},
+  io::{IsTerminal, Read, Write},
   path::{Path, PathBuf},
   process::ExitCode,
+  time::{Duration, Instant},
 };
+use TSPL::Parser as _;
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
@@ -17,14 +38,62 @@ struct Cli {
   #[command(subcommand)]
   pub mode: Mode,
 
-  #[arg(short, long, global = true)]
-  pub verbose: bool,
+  #[arg(
+    short,
+    long,
+    global = true,
+    action = clap::ArgAction::Count,
+    help = "Print the loaded program. Repeat (-vv) to also print the time taken by each phase"
+  )]
+  pub verbose: u8,
+
+  #[arg(
+    short,
+    long,
+    global = true,
+    conflicts_with = "verbose",
+    help = "Don't print warnings and other messages, only errors"
+  )]
+  pub quiet: bool,
 
   #[arg(long, global = true, default_value = "hvm", help = "Path to hvm binary")]
   pub hvm_path: String,
 
+  #[arg(
+    long,
+    global = true,
+    help = "Directory for the intermediate files passed to hvm, instead of the system's temporary directory"
+  )]
+  pub temp_dir: Option<PathBuf>,
+
   #[arg(short = 'e', long, global = true, help = "Use other entrypoint rather than main or Main")]
   pub entrypoint: Option<String>,
+
+  #[arg(long = "prelude", global = true, help = "Load the given file instead of the built-in definitions")]
+  pub prelude_path: Option<PathBuf>,
+
+  #[arg(long, global = true, conflicts_with = "prelude_path", help = "Don't load the built-in definitions")]
+  pub no_prelude: bool,
+
+  #[arg(
+    long,
+    global = true,
+    help = "Read the default options and warnings from the given file instead of './bend.toml'"
+  )]
+  pub config: Option<PathBuf>,
+
+  #[arg(long, global = true, value_enum, default_value = "text", help = "How to print warnings and errors")]
+  pub diagnostics_format: DiagnosticsFormatArg,
+
+  #[arg(
+    long,
+    global = true,
+    value_enum,
+    default_value = "auto",
+    long_help = r#"When to color warnings and errors
+      'auto' colors them when printing to a terminal, unless NO_COLOR is set."#
+  )]
+  pub color: ColorArg,
 }
 
 #[derive(Subcommand, Clone, Debug)]
@@ -34,40 +103,78 @@ enum Mode {
     #[arg(
       short = 'O',
       value_delimiter = ' ',
+      value_parser = ScopedOptParser,
       action = clap::ArgAction::Append,
       long_help = r#"Enables or disables the given optimizations
-      float_combinators is enabled by default on strict mode."#,
+      float_combinators is enabled by default on strict mode.
+      Prefix an option with rust:, c: or cu: to only apply it to that backend."#,
     )]
-    comp_opts: Vec<OptArgs>,
+    comp_opts: Vec<ScopedOptArg>,
 
     #[command(flatten)]
     warn_opts: CliWarnOpts,
 
-    #[arg(help = "Path to the input file")]
-    path: PathBuf,
+    #[arg(
+      required_unless_present_any = ["explain", "explain_opt"],
+      help = "Paths to the input files, or '-' to read from stdin"
+    )]
+    paths: Vec<PathBuf>,
+
+    #[arg(
+      long,
+      value_enum,
+      num_args = 0..=1,
+      help = "Explain what the given diagnostic rule checks for, or list all rules if none is given"
+    )]
+    explain: Option<Option<WarningArgs>>,
+
+    #[arg(
+      long = "explain-opt",
+      value_enum,
+      num_args = 0..=1,
+      conflicts_with = "explain",
+      help = "Explain what the given optimization pass does, or list all passes and their defaults if none is given"
+    )]
+    explain_opt: Option<Option<OptPass>>,
+
+    #[arg(long = "report-pruned", help = "List the definitions removed by the 'prune' optimization")]
+    report_pruned: bool,
+
+    #[arg(long = "strict-types", help = "Report constructors applied to too many arguments as errors")]
+    strict_types: bool,
+
+    #[arg(
+      long = "check-only-changed",
+      help = "Only check again the definitions that changed since the last check, using the results saved in '.bend-cache'"
+    )]
+    check_only_changed: bool,
   },
   /// Compiles the program and runs it with the Rust HVM implementation.
-  Run(RunArgs),
+  Run(RunRustArgs),
   /// Compiles the program and runs it with the C HVM implementation.
   RunC(RunArgs),
   /// Compiles the program and runs it with the Cuda HVM implementation.
   RunCu(RunArgs),
+  /// Runs the program several times and reports statistics of the time and interactions of the runs.
+  Bench(BenchArgs),
   /// Compiles the program to hvm and prints to stdout.
-  GenHvm(GenArgs),
+  GenHvm(GenHvmArgs),
   /// Compiles the program to standalone C and prints to stdout.
-  GenC(GenArgs),
+  GenC(GenCArgs),
   /// Compiles the program to standalone Cuda and prints to stdout.
-  GenCu(GenArgs),
+  GenCu(GenNativeArgs),
   /// Runs the lambda-term level desugaring passes.
   Desugar {
     #[arg(
       short = 'O',
       value_delimiter = ' ',
+      value_parser = ScopedOptParser,
       action = clap::ArgAction::Append,
       long_help = r#"Enables or disables the given optimizations
-      float_combinators is enabled by default on strict mode."#,
+      float_combinators is enabled by default on strict mode.
+      Prefix an option with rust:, c: or cu: to only apply it to that backend."#,
     )]
-    comp_opts: Vec<OptArgs>,
+    comp_opts: Vec<ScopedOptArg>,
 
     #[arg(short = 'p', help = "Debug and normalization pretty printing")]
     pretty: bool,
@@ -75,9 +182,102 @@ enum Mode {
     #[command(flatten)]
     warn_opts: CliWarnOpts,
 
+    #[arg(
+      required_unless_present = "list_passes",
+      help = "Paths to the input files, or '-' to read from stdin"
+    )]
+    paths: Vec<PathBuf>,
+
+    #[arg(
+      long = "stop-after",
+      value_parser = clap::builder::PossibleValuesParser::new(DESUGAR_PASSES.iter().map(|(name, _)| *name)),
+      help = "Print the program as it is right after the given pass, instead of after all of them"
+    )]
+    stop_after: Option<String>,
+
+    #[arg(long = "list-passes", help = "List the names of the desugaring passes, in the order they run")]
+    list_passes: bool,
+
+    #[arg(
+      long = "to-encoding",
+      help = "Encode the types with the given encoding, overriding the one chosen by the -O options"
+    )]
+    to_encoding: Option<EncodingArg>,
+  },
+  /// Starts an interactive session that evaluates terms with the Rust HVM implementation.
+  Repl(ReplArgs),
+  /// Runs every definition whose name starts with `test_` with the Rust HVM implementation.
+  Test(TestArgs),
+  /// Formats the program and prints it to stdout.
+  Fmt {
+    #[arg(short = 'w', long = "write", help = "Write the formatted program back to the input file")]
+    write: bool,
+
     #[arg(help = "Path to the input file")]
     path: PathBuf,
   },
+  /// Prints the definitions and datatypes of the program as JSON, as they are right after parsing.
+  DumpAst {
+    #[arg(long = "builtins", help = "Also include the definitions and datatypes of the builtins")]
+    builtins: bool,
+
+    #[arg(required = true, help = "Paths to the input files, or '-' to read from stdin")]
+    paths: Vec<PathBuf>,
+  },
+  /// Compares the definitions of two programs, listing the ones that were added, removed or changed.
+  Diff {
+    #[arg(
+      long = "exit-code",
+      help = "Exit with 1 if the programs have different definitions and with 0 otherwise"
+    )]
+    exit_code: bool,
+
+    #[arg(help = "Path to the old program")]
+    old: PathBuf,
+
+    #[arg(help = "Path to the new program")]
+    new: PathBuf,
+  },
+  /// Prints the definitions and constructors that each definition of the program references.
+  Deps {
+    #[arg(
+      long,
+      conflicts_with = "dot",
+      help = "Print the definitions so that each one comes after its dependencies, marking the cycles"
+    )]
+    order: bool,
+
+    #[arg(long, help = "Print the dependency graph in the Graphviz dot format")]
+    dot: bool,
+
+    #[arg(required = true, help = "Paths to the input files, or '-' to read from stdin")]
+    paths: Vec<PathBuf>,
+  },
+  /// Prints the definitions of the program with their arity, and whether they're recursive and
+  /// reachable from the entrypoint.
+  ListDefs {
+    #[arg(long, value_enum, default_value_t = DefSort::Source, help = "The order of the definitions")]
+    sort: DefSort,
+
+    #[arg(required = true, help = "Paths to the input files, or '-' to read from stdin")]
+    paths: Vec<PathBuf>,
+  },
+  /// Prints a summary of the shape of the program, as it is right after parsing: the number of definitions,
+  /// term nodes, types, constructors and recursion cycles, and the largest definition.
+  Stats {
+    #[arg(required = true, help = "Paths to the input files, or '-' to read from stdin")]
+    paths: Vec<PathBuf>,
+  },
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+pub enum DefSort {
+  /// By name, alphabetically.
+  Name,
+  /// By arity, from the lowest, keeping the order of the source among the ones with the same arity.
+  Arity,
+  /// In the order they're written in the source.
+  Source,
 }
 
 #[derive(Args, Clone, Debug)]
@@ -91,38 +291,372 @@ struct RunArgs {
   #[arg(
     short = 'O',
     value_delimiter = ' ',
+    value_parser = ScopedOptParser,
     action = clap::ArgAction::Append,
     long_help = r#"Enables or disables the given optimizations
-    float_combinators is enabled by default on strict mode."#,
+    float_combinators is enabled by default on strict mode.
+    Prefix an option with rust:, c: or cu: to only apply it to that backend."#,
   )]
-  comp_opts: Vec<OptArgs>,
+  comp_opts: Vec<ScopedOptArg>,
 
   #[command(flatten)]
   warn_opts: CliWarnOpts,
 
-  #[arg(help = "Path to the input file")]
+  #[arg(help = "Path to the input file, or '-' to read from stdin")]
   path: PathBuf,
 
-  #[arg(value_parser = |arg: &str| bend::fun::parser::TermParser::new(arg).parse_term())]
+  #[arg(
+    short = 'i',
+    long = "include",
+    help = "Path to another file whose definitions are added to the program"
+  )]
+  include: Vec<PathBuf>,
+
+  #[arg(long = "emit-hvm", help = "Save the generated HVM book to the given path before running it")]
+  emit_hvm: Option<PathBuf>,
+
+  #[arg(
+    long,
+    conflicts_with = "max_memory",
+    help = "Evaluate with a built-in interpreter and print the interactions performed by each definition"
+  )]
+  profile: bool,
+
+  #[arg(
+    long,
+    value_parser = parse_threads,
+    conflicts_with = "profile",
+    help = "Number of threads used by the C and Cuda runtimes, which must be a power of two"
+  )]
+  threads: Option<usize>,
+
+  #[arg(
+    long = "backend-flags",
+    allow_hyphen_values = true,
+    value_parser = parse_backend_flags,
+    help = "Extra arguments passed to hvm, split like in a shell. Unstable, since they depend on the version of hvm"
+  )]
+  backend_flags: Option<BackendFlags>,
+
+  #[arg(
+    long,
+    help = "Exit with the result of the program, which must be a number or a constructor of a type like 'type Bool = True | False'"
+  )]
+  exit_code: bool,
+
+  #[arg(
+    long,
+    conflicts_with_all = ["pretty", "print_stats", "profile"],
+    help = "Print only the result, which can be passed as an argument to another program with '--args-stdin'"
+  )]
+  result_only: bool,
+
+  #[arg(
+    long = "output-json",
+    conflicts_with_all = ["pretty", "print_stats", "profile", "result_only"],
+    help = "Print only the result, as JSON. The result can only have numbers, strings, lists and tuples"
+  )]
+  output_json: bool,
+
+  #[arg(
+    long = "output-sexpr",
+    conflicts_with_all = ["pretty", "print_stats", "profile", "result_only", "output_json"],
+    help = "Print only the result, as an S-expression with the constructors as the heads of the lists"
+  )]
+  output_sexpr: bool,
+
+  #[arg(value_parser = |arg: &str| bend::fun::parser::TermParser::new(arg).parse_term().map_err(|e| e.to_string()))]
   arguments: Option<Vec<bend::fun::Term>>,
+
+  #[arg(
+    long = "args-file",
+    conflicts_with_all = ["arguments", "args_stdin"],
+    help = "Read the program arguments from a file, separated by whitespace"
+  )]
+  args_file: Option<PathBuf>,
+
+  #[arg(
+    long = "args-stdin",
+    conflicts_with = "arguments",
+    help = "Read the program arguments from stdin, separated by whitespace"
+  )]
+  args_stdin: bool,
+
+  #[arg(
+    long = "input-json",
+    help = "Read a JSON file and pass its data as the last argument of the program"
+  )]
+  input_json: Option<PathBuf>,
 }
 
 #[derive(Args, Clone, Debug)]
-struct GenArgs {
+struct RunRustArgs {
+  #[command(flatten)]
+  run: RunArgs,
+
+  #[arg(
+    long,
+    conflicts_with_all = ["max_memory", "threads", "result_only", "output_json", "output_sexpr"],
+    help = "Evaluate with a built-in interpreter and print each interaction as it's performed"
+  )]
+  trace: bool,
+
+  #[arg(long, requires = "trace", help = "Stop printing the interactions after the given number of them")]
+  trace_limit: Option<u64>,
+
+  #[arg(
+    long,
+    conflicts_with_all = ["max_memory", "threads", "result_only", "output_json", "output_sexpr"],
+    help = "Evaluate with a built-in interpreter and print the match arms that were never executed"
+  )]
+  coverage: bool,
+
+  #[arg(
+    long,
+    conflicts_with = "threads",
+    help = "Evaluate with the sequential HVM runtime so that results and stats are reproducible across runs"
+  )]
+  deterministic: bool,
+}
+
+#[derive(Args, Clone, Debug)]
+struct BenchArgs {
+  #[arg(
+    long,
+    default_value_t = 10,
+    value_parser = parse_positive_number,
+    help = "Number of measured runs"
+  )]
+  runs: u64,
+
+  #[arg(
+    long,
+    default_value_t = 0,
+    help = "Number of runs done before the measured ones, whose stats are discarded"
+  )]
+  warmup: u64,
+
+  #[arg(
+    long,
+    value_enum,
+    default_value_t = Backend::Rust,
+    help = "HVM implementation used to run the program"
+  )]
+  backend: Backend,
+
+  #[arg(long, help = "Print the statistics as JSON")]
+  json: bool,
+
+  #[arg(
+    long,
+    value_parser = parse_threads,
+    conflicts_with = "deterministic",
+    help = "Number of threads used by the C and Cuda runtimes, which must be a power of two"
+  )]
+  threads: Option<usize>,
+
+  #[arg(
+    long,
+    help = "Evaluate with the sequential HVM runtime so that results and stats are reproducible across runs. Only supported by the Rust backend"
+  )]
+  deterministic: bool,
+
+  #[command(flatten)]
+  run_opts: CliRunOpts,
+
   #[arg(
     short = 'O',
     value_delimiter = ' ',
+    value_parser = ScopedOptParser,
     action = clap::ArgAction::Append,
     long_help = r#"Enables or disables the given optimizations
-    float_combinators is enabled by default on strict mode."#,
+    float_combinators is enabled by default on strict mode.
+    Prefix an option with rust:, c: or cu: to only apply it to that backend."#,
   )]
-  comp_opts: Vec<OptArgs>,
+  comp_opts: Vec<ScopedOptArg>,
 
   #[command(flatten)]
   warn_opts: CliWarnOpts,
 
-  #[arg(help = "Path to the input file")]
+  #[arg(help = "Path to the input file, or '-' to read from stdin")]
   path: PathBuf,
+
+  #[arg(value_parser = |arg: &str| bend::fun::parser::TermParser::new(arg).parse_term().map_err(|e| e.to_string()))]
+  arguments: Option<Vec<bend::fun::Term>>,
+}
+
+#[derive(Args, Clone, Debug)]
+struct ReplArgs {
+  #[arg(short = 'p', help = "Debug and normalization pretty printing")]
+  pretty: bool,
+
+  #[command(flatten)]
+  run_opts: CliRunOpts,
+
+  #[arg(
+    short = 'O',
+    value_delimiter = ' ',
+    value_parser = ScopedOptParser,
+    action = clap::ArgAction::Append,
+    long_help = r#"Enables or disables the given optimizations
+    float_combinators is enabled by default on strict mode.
+    Prefix an option with rust:, c: or cu: to only apply it to that backend."#,
+  )]
+  comp_opts: Vec<ScopedOptArg>,
+
+  #[command(flatten)]
+  warn_opts: CliWarnOpts,
+
+  #[arg(help = "Path to a file whose definitions are loaded before starting")]
+  prelude: Option<PathBuf>,
+
+  #[arg(
+    long,
+    help = "Evaluate with the sequential HVM runtime so that results and stats are reproducible across runs"
+  )]
+  deterministic: bool,
+}
+
+#[derive(Args, Clone, Debug)]
+struct TestArgs {
+  #[arg(short = 'p', help = "Debug and normalization pretty printing")]
+  pretty: bool,
+
+  #[command(flatten)]
+  run_opts: CliRunOpts,
+
+  #[arg(
+    short = 'O',
+    value_delimiter = ' ',
+    value_parser = ScopedOptParser,
+    action = clap::ArgAction::Append,
+    long_help = r#"Enables or disables the given optimizations
+    float_combinators is enabled by default on strict mode.
+    Prefix an option with rust:, c: or cu: to only apply it to that backend."#,
+  )]
+  comp_opts: Vec<ScopedOptArg>,
+
+  #[command(flatten)]
+  warn_opts: CliWarnOpts,
+
+  #[arg(required = true, help = "Paths to the input files, or '-' to read from stdin")]
+  paths: Vec<PathBuf>,
+
+  #[arg(long, help = "Only run the tests whose names contain the given text")]
+  filter: Option<String>,
+
+  #[arg(long, help = "Evaluate with a built-in interpreter and print the match arms that no test executed")]
+  coverage: bool,
+
+  #[arg(
+    long,
+    help = "Evaluate with the sequential HVM runtime so that results and stats are reproducible across runs"
+  )]
+  deterministic: bool,
+}
+
+#[derive(Args, Clone, Debug)]
+struct GenArgs {
+  #[arg(
+    short = 'O',
+    value_delimiter = ' ',
+    value_parser = ScopedOptParser,
+    action = clap::ArgAction::Append,
+    long_help = r#"Enables or disables the given optimizations
+    float_combinators is enabled by default on strict mode.
+    Prefix an option with rust:, c: or cu: to only apply it to that backend."#,
+  )]
+  comp_opts: Vec<ScopedOptArg>,
+
+  #[command(flatten)]
+  warn_opts: CliWarnOpts,
+
+  #[arg(required = true, help = "Paths to the input files, or '-' to read from stdin")]
+  paths: Vec<PathBuf>,
+
+  #[arg(
+    long = "only",
+    value_delimiter = ',',
+    help = "Only compile the given definitions, the entrypoint and the definitions they use"
+  )]
+  only: Vec<String>,
+
+  #[arg(long = "report-pruned", help = "List the definitions removed by the 'prune' optimization")]
+  report_pruned: bool,
+
+  #[arg(short = 'o', long = "output", help = "Write the generated code to the given file instead of stdout")]
+  output: Option<PathBuf>,
+}
+
+#[derive(Args, Clone, Debug)]
+struct GenHvmArgs {
+  #[command(flatten)]
+  gen: GenArgs,
+
+  #[arg(long = "annotate", help = "Precede each generated HVM definition with a comment naming its origin")]
+  annotate: bool,
+
+  #[arg(
+    long = "validate",
+    help = "Check that hvm can parse the generated code, reporting its errors on the definitions they come from"
+  )]
+  validate: bool,
+
+  #[arg(
+    long = "dump-nets",
+    value_name = "PATH",
+    help = "Also write the generated nets to the given file as a Graphviz DOT graph"
+  )]
+  dump_nets: Option<PathBuf>,
+
+  #[arg(
+    long = "cache-dir",
+    value_name = "PATH",
+    help = "Reuse the result of compiling the same program with the same options, saved in the given directory"
+  )]
+  cache_dir: Option<PathBuf>,
+
+  #[arg(long = "no-cache", help = "Don't use the compilation cache, even if a cache directory is given")]
+  no_cache: bool,
+}
+
+#[derive(Args, Clone, Debug)]
+struct GenNativeArgs {
+  #[command(flatten)]
+  gen: GenArgs,
+
+  #[arg(long = "keep-temp", help = "Don't remove the intermediate HVM file passed to hvm")]
+  keep_temp: bool,
+
+  #[arg(
+    long = "backend-flags",
+    allow_hyphen_values = true,
+    value_parser = parse_backend_flags,
+    help = "Extra arguments passed to hvm, split like in a shell. Unstable, since they depend on the version of hvm"
+  )]
+  backend_flags: Option<BackendFlags>,
+
+  #[arg(
+    long = "dry-run",
+    conflicts_with_all = ["output", "backend_flags"],
+    help = "Only compile the program and print statistics of the generated nets, without running hvm"
+  )]
+  dry_run: bool,
+}
+
+#[derive(Args, Clone, Debug)]
+struct GenCArgs {
+  #[command(flatten)]
+  native: GenNativeArgs,
+
+  #[arg(
+    long,
+    requires = "output",
+    conflicts_with = "dry_run",
+    value_parser = parse_positive_number,
+    help = "Split the generated C code into the given number of files plus a header, named after the output"
+  )]
+  split: Option<u64>,
 }
 
 #[derive(Args, Clone, Debug)]
@@ -132,6 +666,35 @@ struct CliRunOpts {
 
   #[arg(short = 's', long = "stats", help = "Shows runtime stats and rewrite counts")]
   print_stats: bool,
+
+  #[arg(
+    long,
+    value_parser = parse_duration,
+    help = "Stops the program if it runs for longer than the given time (e.g. 500ms, 5s, 2m)"
+  )]
+  timeout: Option<Duration>,
+
+  #[arg(
+    long,
+    value_parser = parse_memory_size,
+    help = "Stops the program if it uses more than the given amount of memory (e.g. 512M, 2G)"
+  )]
+  max_memory: Option<usize>,
+
+  #[arg(
+    long,
+    value_enum,
+    help = "How the floats of the result are displayed, exactly and in a way that parses back to the same value"
+  )]
+  float_format: Option<FloatFormatArg>,
+
+  #[arg(
+    long = "readback-depth-limit",
+    value_parser = parse_positive_number,
+    default_value_t = READBACK_DEPTH_LIMIT as u64,
+    help = "Maximum depth of the result that is read back. Deeper parts of the result are reported as errors"
+  )]
+  readback_depth_limit: u64,
 }
 
 #[derive(Args, Debug, Clone)]
@@ -163,6 +726,12 @@ struct CliWarnOpts {
     help = "Allow the specified compilation warning",
   )]
   pub allows: Vec<WarningArgs>,
+
+  #[arg(
+    long = "fail-on-warning",
+    help = "Turn every warning that is still enabled after -W, -D and -A into an error"
+  )]
+  pub fail_on_warning: bool,
 }
 
 #[derive(clap::ValueEnum, Clone, Debug)]
@@ -171,6 +740,10 @@ pub enum OptArgs {
   NoAll,
   Eta,
   NoEta,
+  EtaExpand,
+  NoEtaExpand,
+  Specialize,
+  NoSpecialize,
   Prune,
   NoPrune,
   LinearizeMatches,
@@ -180,32 +753,143 @@ pub enum OptArgs {
   NoFloatCombinators,
   Merge,
   NoMerge,
+  DedupDefs,
+  NoDedupDefs,
+  ConstFold,
+  NoConstFold,
+  Cse,
+  NoCse,
   Inline,
   NoInline,
+  OptimizeNets,
+  NoOptimizeNets,
   CheckNetSize,
   NoCheckNetSize,
   AdtScott,
   AdtNumScott,
+  AdtTaggedScott,
+  NoAdtTaggedScott,
+  #[value(name = "0")]
+  Level0,
+  #[value(name = "1")]
+  Level1,
+  #[value(name = "2")]
+  Level2,
+  #[value(name = "3")]
+  Level3,
 }
 
-fn compile_opts_from_cli(args: &Vec<OptArgs>) -> CompileOpts {
-  use OptArgs::*;
-  let mut opts = CompileOpts::default();
+/// The backends that can be given to scope an optimization option, as in `-O cu:no-eta`.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Backend {
+  Rust,
+  C,
+  Cu,
+}
 
-  for arg in args {
-    match arg {
-      All => opts = opts.set_all(),
-      NoAll => opts = opts.set_no_all(),
-      Eta => opts.eta = true,
-      NoEta => opts.eta = false,
-      Prune => opts.prune = true,
-      NoPrune => opts.prune = false,
-      FloatCombinators => opts.float_combinators = true,
-      NoFloatCombinators => opts.float_combinators = false,
+/// An optimization option, either one of the [`OptArgs`] or an option that takes a value.
+#[derive(Clone, Debug)]
+pub enum OptArg {
+  Flag(OptArgs),
+  /// `max-net-size=<N>`, the largest number of nodes accepted by check-net-size.
+  MaxNetSize(usize),
+}
+
+/// An optimization option that only applies to the given backend, or to all of them if none is given.
+#[derive(Clone, Debug)]
+pub struct ScopedOptArg {
+  backend: Option<Backend>,
+  opt: OptArg,
+}
+
+/// Parses an [`OptArg`] optionally preceded by a backend and a colon.
+#[derive(Clone)]
+struct ScopedOptParser;
+
+impl ScopedOptParser {
+  fn parse_opt(
+    cmd: &clap::Command,
+    arg: Option<&clap::Arg>,
+    opt: &std::ffi::OsStr,
+  ) -> Result<OptArg, clap::Error> {
+    use clap::builder::TypedValueParser;
+    if let Some(size) = opt.to_str().and_then(|opt| opt.strip_prefix("max-net-size=")) {
+      let size = parse_positive_number.parse_ref(cmd, arg, size.as_ref())?;
+      return Ok(OptArg::MaxNetSize(size as usize));
+    }
+    let opt_parser = clap::builder::EnumValueParser::<OptArgs>::new();
+    Ok(OptArg::Flag(opt_parser.parse_ref(cmd, arg, opt)?))
+  }
+}
+
+impl clap::builder::TypedValueParser for ScopedOptParser {
+  type Value = ScopedOptArg;
+
+  fn parse_ref(
+    &self,
+    cmd: &clap::Command,
+    arg: Option<&clap::Arg>,
+    value: &std::ffi::OsStr,
+  ) -> Result<Self::Value, clap::Error> {
+    let Some((backend, opt)) = value.to_str().and_then(|value| value.split_once(':')) else {
+      return Ok(ScopedOptArg { backend: None, opt: Self::parse_opt(cmd, arg, value)? });
+    };
+    let backend_parser = clap::builder::EnumValueParser::<Backend>::new();
+    let backend = backend_parser.parse_ref(cmd, arg, backend.as_ref())?;
+    let opt = Self::parse_opt(cmd, arg, opt.as_ref())?;
+    Ok(ScopedOptArg { backend: Some(backend), opt })
+  }
+
+  fn possible_values(&self) -> Option<Box<dyn Iterator<Item = clap::builder::PossibleValue> + '_>> {
+    let max_net_size = clap::builder::PossibleValue::new("max-net-size=<N>");
+    Some(Box::new(
+      OptArgs::value_variants().iter().filter_map(|opt| opt.to_possible_value()).chain([max_net_size]),
+    ))
+  }
+}
+
+/// The options that apply to `backend`, in the order they were given, on top of the ones of the config file.
+/// Options scoped to a backend are ignored when `backend` is `None`.
+fn compile_opts_from_cli(config: &Config, args: &[ScopedOptArg], backend: Option<Backend>) -> CompileOpts {
+  use OptArgs::*;
+  let mut opts = CompileOpts::default();
+
+  for ScopedOptArg { backend: arg_backend, opt: arg } in config.opts.iter().chain(args) {
+    if arg_backend.is_some() && *arg_backend != backend {
+      continue;
+    }
+    let arg = match arg {
+      OptArg::Flag(arg) => arg,
+      OptArg::MaxNetSize(size) => {
+        opts.max_net_size = Some(*size);
+        continue;
+      }
+    };
+    match arg {
+      All => opts = opts.set_all(),
+      NoAll => opts = opts.set_no_all(),
+      Eta => opts.eta = true,
+      NoEta => opts.eta = false,
+      EtaExpand => opts.eta_expand = true,
+      NoEtaExpand => opts.eta_expand = false,
+      Specialize => opts.specialize = true,
+      NoSpecialize => opts.specialize = false,
+      Prune => opts.prune = true,
+      NoPrune => opts.prune = false,
+      FloatCombinators => opts.float_combinators = true,
+      NoFloatCombinators => opts.float_combinators = false,
       Merge => opts.merge = true,
       NoMerge => opts.merge = false,
+      DedupDefs => opts.dedup_defs = true,
+      NoDedupDefs => opts.dedup_defs = false,
+      ConstFold => opts.const_fold = true,
+      NoConstFold => opts.const_fold = false,
+      Cse => opts.cse = true,
+      NoCse => opts.cse = false,
       Inline => opts.inline = true,
       NoInline => opts.inline = false,
+      OptimizeNets => opts.optimize_nets = true,
+      NoOptimizeNets => opts.optimize_nets = false,
       CheckNetSize => opts.check_net_size = true,
       NoCheckNetSize => opts.check_net_size = false,
 
@@ -215,12 +899,191 @@ fn compile_opts_from_cli(args: &Vec<OptArgs>) -> CompileOpts {
 
       AdtScott => opts.adt_encoding = AdtEncoding::Scott,
       AdtNumScott => opts.adt_encoding = AdtEncoding::NumScott,
+      AdtTaggedScott => opts.adt_encoding = AdtEncoding::TaggedScott,
+      NoAdtTaggedScott => opts.adt_encoding = AdtEncoding::NumScott,
+
+      Level0 => opts = opts.preset(0),
+      Level1 => opts = opts.preset(1),
+      Level2 => opts = opts.preset(2),
+      Level3 => opts = opts.preset(3),
     }
   }
 
   opts
 }
 
+/// The optimization passes that can be explained with `--explain-opt`,
+/// each standing for the [`OptArgs`] that enable, disable or configure it.
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+pub enum OptPass {
+  Eta,
+  #[value(alias = "eta_expand")]
+  EtaExpand,
+  Specialize,
+  Prune,
+  #[value(alias = "linearize_matches")]
+  LinearizeMatches,
+  #[value(alias = "float_combinators")]
+  FloatCombinators,
+  Merge,
+  #[value(alias = "dedup_defs")]
+  DedupDefs,
+  #[value(alias = "const_fold")]
+  ConstFold,
+  Cse,
+  Inline,
+  #[value(alias = "optimize_nets")]
+  OptimizeNets,
+  #[value(alias = "check_net_size")]
+  CheckNetSize,
+  #[value(alias = "adt_encoding")]
+  AdtEncoding,
+}
+
+/// The state of a pass in the given options, as listed by `--explain-opt`.
+fn opt_default(pass: OptPass, opts: &CompileOpts) -> &'static str {
+  let enabled = |enabled: bool| if enabled { "enabled" } else { "disabled" };
+  match pass {
+    OptPass::Eta => enabled(opts.eta),
+    OptPass::EtaExpand => enabled(opts.eta_expand),
+    OptPass::Specialize => enabled(opts.specialize),
+    OptPass::Prune => enabled(opts.prune),
+    OptPass::LinearizeMatches => match opts.linearize_matches {
+      OptLevel::Disabled => "disabled",
+      OptLevel::Enabled => "enabled",
+      OptLevel::Alt => "enabled (alt)",
+    },
+    OptPass::FloatCombinators if opts.float_combinators => "enabled (default in strict mode)",
+    OptPass::FloatCombinators => "disabled",
+    OptPass::Merge => enabled(opts.merge),
+    OptPass::DedupDefs => enabled(opts.dedup_defs),
+    OptPass::ConstFold => enabled(opts.const_fold),
+    OptPass::Cse => enabled(opts.cse),
+    OptPass::Inline => enabled(opts.inline),
+    OptPass::OptimizeNets => enabled(opts.optimize_nets),
+    OptPass::CheckNetSize => enabled(opts.check_net_size),
+    OptPass::AdtEncoding => match opts.adt_encoding {
+      AdtEncoding::Scott => "adt-scott",
+      AdtEncoding::NumScott => "adt-num-scott",
+      AdtEncoding::TaggedScott => "adt-tagged-scott",
+    },
+  }
+}
+
+fn opt_explanation(pass: OptPass) -> &'static str {
+  match pass {
+    OptPass::Eta => {
+      "eta (-Oeta, -Ono-eta): Replaces the lambdas that only apply a function to their argument, like 'λx (f x)', with the function.
+The compiled nets get smaller and each call through them saves an interaction.
+Enabled from -O2. There is no reason to disable it other than to inspect the nets as they were written."
+    }
+    OptPass::EtaExpand => {
+      "eta-expand (-Oeta-expand, -Ono-eta-expand): Experimental. Adds a lambda for each missing argument of the
+partial applications of definitions, like turning '(Add x)' into 'λy (Add x y)'.
+The call is then evaluated again each time the lambda is called, repeating its work when the lambda is used more
+than once. It's not known to make programs faster, so measure before keeping it. Disabled by default and not changed by the optimization levels.
+Since eta reduces the expanded lambdas back on the compiled nets, use it with -Ono-eta to see its effect there."
+    }
+    OptPass::Specialize => {
+      "specialize (-Ospecialize, -Ono-specialize): Creates specialized versions of the recursive definitions
+that are called with numbers as the arguments they pass unchanged to every recursive call,
+like turning '(Pow 2 e)' into '(Pow__spec0 e)' with the 2 substituted in the body of 'Pow__spec0'.
+Each definition gets at most 8 specialized versions. Use it with -Oconst-fold to compute with the constants.
+Disabled by default and not changed by the optimization levels. Lists the specializations when run with --verbose."
+    }
+    OptPass::Prune => {
+      "prune (-Oprune, -Ono-prune): Removes the definitions that can't be reached from the entrypoint.
+Makes the generated HVM, C and Cuda files smaller, without changing what the program does.
+Enabled from -O3. Keep it disabled when generating code meant to be used as a library,
+and use '--report-pruned' to see which definitions were removed."
+    }
+    OptPass::LinearizeMatches => {
+      "linearize-matches (-Olinearize-matches, -Olinearize-matches-alt, -Ono-linearize-matches): Moves the variables
+used inside of the arms of a match into lambdas in each arm, so that the arms become closed terms.
+The closed arms can then be floated out by float-combinators, which strict evaluation needs to not expand
+recursive functions infinitely. The alt version linearizes all the variables used in the arms, which gives
+more combinators at the cost of more applications.
+Enabled from -O1. Only disable it for programs that aren't recursive or when comparing the generated code."
+    }
+    OptPass::FloatCombinators => {
+      "float-combinators (-Ofloat-combinators, -Ono-float-combinators): Extracts the closed terms of the definitions,
+like lambdas without free variables, to new definitions that are only expanded when they're used.
+HVM evaluates eagerly, so without it recursive functions can expand infinitely. It's enabled by default on strict
+mode, from -O1, and should only be disabled for programs without recursion, to get fewer and larger definitions."
+    }
+    OptPass::Merge => {
+      "merge (-Omerge, -Ono-merge): Merges the definitions that are identical after compilation into a single one.
+Makes the compiled book smaller when many definitions share a body, like the constructors of different types.
+Enabled from -O3. The merged definition takes the names of all of them, which can make the output harder to read."
+    }
+    OptPass::DedupDefs => {
+      "dedup-defs (-Odedup-defs, -Ono-dedup-defs): Removes the definitions that are the same as another one up to the
+names of their variables, making their references point to the one that is kept, which keeps its name.
+Recursive definitions that only differ in their names are also removed. Useful for generated programs with many
+copies of the same function. Enabled from -O3. Pass '-v' to see how many definitions were removed."
+    }
+    OptPass::ConstFold => {
+      "const-fold (-Oconst-fold, -Ono-const-fold): Computes the numeric operations on number literals at compile time.
+The results are the same ones the runtime computes, so it only saves the interactions of the operations.
+Enabled from -O3. An integer division by zero on literals becomes a compile error instead of a runtime result."
+    }
+    OptPass::Cse => {
+      "cse (-Ocse, -Ono-cse): Computes the repeated numeric operations and full calls of a definition only once,
+sharing the result with a duplication (common subexpression elimination).
+Enabled from -O3. It can turn exponential recursions into linear ones, but duplications cost interactions too,
+so terms that are cheap to compute are not shared."
+    }
+    OptPass::Inline => {
+      "inline (-Oinline, -Ono-inline): Replaces the references to definitions that compile to a single number,
+erasure or reference with their value.
+Enabled from -O3. Saves the interaction of expanding those references, at the cost of repeating their values."
+    }
+    OptPass::OptimizeNets => {
+      "optimize-nets (-Ooptimize-nets, -Ono-optimize-nets): Performs the interactions between the nodes of the net of
+each definition that don't create new nodes, like applying a lambda written in place or erasing a constructor,
+and removes the redexes that only connect two wires.
+Enabled from -O3. Saves those interactions every time the definition is used, and only makes the nets smaller."
+    }
+    OptPass::CheckNetSize => {
+      "check-net-size (-Ocheck-net-size, -Ono-check-net-size, -Omax-net-size=<N>): Reports the definitions that
+compile to more nodes than the Cuda runtime supports, 64 by default or the number given with max-net-size.
+Disabled by default and not changed by the optimization levels. Enable it when targeting run-cu or gen-cu,
+where those definitions would fail at runtime, or set max-net-size for hardware with other limits."
+    }
+    OptPass::AdtEncoding => {
+      "adt-encoding (-Oadt-scott, -Oadt-num-scott, -Oadt-tagged-scott, -Ono-adt-tagged-scott): Selects how the
+constructors of the types are encoded as lambdas.
+num-scott, the default, passes a numeric tag to a single continuation. scott passes the fields to one lambda per
+constructor, which needs no tags but makes matches on types with many constructors larger. tagged-scott writes
+the tags as numbers unique across the whole program, so no tag definitions are generated. A match then subtracts
+the first tag of the matched type instead of calling a tag definition, so it only saves interactions on String,
+whose first tag is 0.
+Not changed by the optimization levels."
+    }
+  }
+}
+
+/// The number of parameters of a definition, counting the patterns of its rules and the lambdas
+/// at the start of the body of its first rule.
+fn def_arity(def: &Definition) -> usize {
+  let mut body = &def.rules[0].body;
+  let mut lambdas = 0;
+  while let Term::Lam { bod, .. } = body {
+    lambdas += 1;
+    body = bod;
+  }
+  def.arity() + lambdas
+}
+
+/// The definitions selected with `--only`, if any.
+fn roots_from_cli(only: Vec<String>) -> Option<Vec<Name>> {
+  if only.is_empty() {
+    None
+  } else {
+    Some(only.into_iter().map(Name::new).collect())
+  }
+}
+
 #[derive(clap::ValueEnum, Clone, Debug)]
 pub enum WarningArgs {
   All,
@@ -230,6 +1093,87 @@ pub enum WarningArgs {
   UnusedDefinition,
   RepeatedBind,
   RecursionCycle,
+  ShadowedBuiltin,
+  MissingBaseCase,
+  CtrArity,
+  ShadowedBind,
+  NumericOverflow,
+  UnusedBind,
+  NamingConvention,
+  RedundantLambda,
+  PotentialExponential,
+  MatchArmOrder,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+pub enum DiagnosticsFormatArg {
+  Text,
+  Json,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+pub enum FloatFormatArg {
+  Decimal,
+  Scientific,
+  Hex,
+}
+
+impl From<FloatFormatArg> for FloatFormat {
+  fn from(value: FloatFormatArg) -> Self {
+    match value {
+      FloatFormatArg::Decimal => FloatFormat::Decimal,
+      FloatFormatArg::Scientific => FloatFormat::Scientific,
+      FloatFormatArg::Hex => FloatFormat::Hex,
+    }
+  }
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+pub enum EncodingArg {
+  Scott,
+  NumScott,
+  TaggedScott,
+}
+
+impl From<EncodingArg> for AdtEncoding {
+  fn from(value: EncodingArg) -> Self {
+    match value {
+      EncodingArg::Scott => AdtEncoding::Scott,
+      EncodingArg::NumScott => AdtEncoding::NumScott,
+      EncodingArg::TaggedScott => AdtEncoding::TaggedScott,
+    }
+  }
+}
+
+impl From<DiagnosticsFormatArg> for DiagnosticsFormat {
+  fn from(value: DiagnosticsFormatArg) -> Self {
+    match value {
+      DiagnosticsFormatArg::Text => DiagnosticsFormat::Text,
+      DiagnosticsFormatArg::Json => DiagnosticsFormat::Json,
+    }
+  }
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+pub enum ColorArg {
+  Auto,
+  Always,
+  Never,
+}
+
+impl ColorArg {
+  /// Whether the diagnostics, which are printed to stderr, should be colored.
+  fn use_color(self) -> bool {
+    match self {
+      ColorArg::Always => true,
+      ColorArg::Never => false,
+      ColorArg::Auto => {
+        // See https://no-color.org
+        let no_color = std::env::var_os("NO_COLOR").is_some_and(|val| !val.is_empty());
+        !no_color && std::io::stderr().is_terminal()
+      }
+    }
+  }
 }
 
 fn main() -> ExitCode {
@@ -237,21 +1181,51 @@ fn main() -> ExitCode {
   compile_error!("The 'cli' feature is needed for the Bend cli");
 
   let cli = Cli::parse();
+  let display_cfg = DiagnosticsConfig {
+    format: cli.diagnostics_format.into(),
+    color: cli.color.use_color(),
+    quiet: cli.quiet,
+    ..Default::default()
+  };
 
-  if let Err(diagnostics) = execute_cli_mode(cli) {
-    eprint!("{diagnostics}");
-    return ExitCode::FAILURE;
+  match execute_cli_mode(cli) {
+    Ok(exit_code) => exit_code,
+    Err(mut diagnostics) => {
+      diagnostics.set_display_config(display_cfg);
+      eprint!("{diagnostics}");
+      ExitCode::FAILURE
+    }
   }
-  ExitCode::SUCCESS
 }
 
-fn execute_cli_mode(mut cli: Cli) -> Result<(), Diagnostics> {
-  let arg_verbose = cli.verbose;
+fn execute_cli_mode(mut cli: Cli) -> Result<ExitCode, Diagnostics> {
+  let arg_verbose = cli.verbose > 0;
+  let timing = cli.verbose > 1;
+  let mut print_phase = print_phase_time;
+  let quiet = cli.quiet;
   let entrypoint = cli.entrypoint.take();
+  let prelude_path = cli.prelude_path.take();
+  let no_prelude = cli.no_prelude;
+  let diagnostics_format = cli.diagnostics_format.into();
+  let color = cli.color.use_color();
+  let default_diagnostics_cfg =
+    DiagnosticsConfig { format: diagnostics_format, color, quiet, ..Default::default() };
+  let config = Config::load(cli.config.take().as_deref())?;
+
+  let load_prelude = || -> Result<Book, Diagnostics> {
+    match &prelude_path {
+      Some(path) => load_prelude_file(path),
+      None if no_prelude => Ok(Book::default()),
+      None => Ok(Book::builtins()),
+    }
+  };
 
-  let load_book = |path: &Path| -> Result<Book, Diagnostics> {
-    let mut book = load_file_to_book(path)?;
-    book.entrypoint = entrypoint.map(Name::new);
+  let load_book = |paths: &[PathBuf]| -> Result<Book, Diagnostics> {
+    let mut book = load_files_to_book_with_prelude(paths, load_prelude()?)?;
+    // The entrypoint given in the command line overrides the one marked in the program.
+    if let Some(entrypoint) = &entrypoint {
+      book.entrypoint = Some(Name::new(entrypoint));
+    }
 
     if arg_verbose {
       println!("{book}");
@@ -267,71 +1241,224 @@ fn execute_cli_mode(mut cli: Cli) -> Result<(), Diagnostics> {
   };
 
   let run_cmd = match &cli.mode {
-    Mode::RunC(..) => "run-c",
-    Mode::RunCu(..) => "run-cu",
+    Mode::RunC(..) | Mode::Bench(BenchArgs { backend: Backend::C, .. }) => "run-c",
+    Mode::RunCu(..) | Mode::Bench(BenchArgs { backend: Backend::Cu, .. }) => "run-cu",
     _ => "run",
   };
 
-  match cli.mode {
-    Mode::Check { comp_opts, warn_opts, path } => {
-      let diagnostics_cfg = set_warning_cfg_from_cli(DiagnosticsConfig::default(), warn_opts);
-      let compile_opts = compile_opts_from_cli(&comp_opts);
+  let backend = match &cli.mode {
+    Mode::Run(..) | Mode::Repl(..) | Mode::Test(..) => Some(Backend::Rust),
+    Mode::RunC(..) | Mode::GenC(..) => Some(Backend::C),
+    Mode::RunCu(..) | Mode::GenCu(..) => Some(Backend::Cu),
+    Mode::Bench(BenchArgs { backend, .. }) => Some(*backend),
+    _ => None,
+  };
+
+  if let Mode::Bench(BenchArgs { backend: Backend::C | Backend::Cu, deterministic: true, .. }) = &cli.mode {
+    return Err(
+      "The '--deterministic' option can't be used with the parallel C and Cuda runtimes.".to_string().into(),
+    );
+  }
+
+  let mut exit_code = ExitCode::SUCCESS;
 
-      let mut book = load_book(&path)?;
-      let diagnostics = check_book(&mut book, diagnostics_cfg, compile_opts)?;
-      eprintln!("{}", diagnostics);
+  // The C and Cuda modes take a subset of the arguments of the Rust ones, so they share their arms.
+  let mode = match cli.mode {
+    Mode::GenCu(native) => Mode::GenC(GenCArgs { native, split: None }),
+    Mode::RunC(run) | Mode::RunCu(run) => {
+      Mode::Run(RunRustArgs { run, trace: false, trace_limit: None, coverage: false, deterministic: false })
     }
+    mode => mode,
+  };
 
-    Mode::GenHvm(GenArgs { comp_opts, warn_opts, path, .. }) => {
-      let diagnostics_cfg = set_warning_cfg_from_cli(DiagnosticsConfig::default(), warn_opts);
-      let opts = compile_opts_from_cli(&comp_opts);
+  match mode {
+    Mode::Check { explain: Some(rule), .. } => match rule {
+      Some(WarningArgs::All) | None => {
+        for rule in WarningArgs::value_variants().iter().filter(|rule| !matches!(rule, WarningArgs::All)) {
+          println!("{}", rule.to_possible_value().unwrap().get_name());
+        }
+      }
+      Some(rule) => println!("{}", warning_explanation(&rule)),
+    },
+
+    Mode::Check { explain_opt: Some(pass), .. } => match pass {
+      Some(pass) => println!("{}", opt_explanation(pass)),
+      None => {
+        let defaults = CompileOpts::default();
+        let names = OptPass::value_variants().iter().map(|pass| pass.to_possible_value().unwrap());
+        let width = names.clone().map(|name| name.get_name().len()).max().unwrap();
+        for (pass, name) in OptPass::value_variants().iter().zip(names) {
+          println!("{:<width$}  {}", name.get_name(), opt_default(*pass, &defaults));
+        }
+      }
+    },
+
+    Mode::Check {
+      comp_opts,
+      warn_opts,
+      paths,
+      explain: None,
+      explain_opt: None,
+      report_pruned,
+      strict_types,
+      check_only_changed,
+    } => {
+      let diagnostics_cfg = if strict_types {
+        DiagnosticsConfig { ctr_arity: Severity::Error, ..default_diagnostics_cfg }
+      } else {
+        default_diagnostics_cfg
+      };
+      let diagnostics_cfg = set_warning_cfg_from_cli(&config, diagnostics_cfg, warn_opts);
+      let diagnostics_cfg = DiagnosticsConfig { report_pruned, ..diagnostics_cfg };
+      let compile_opts = compile_opts_from_cli(&config, &comp_opts, backend);
 
-      let mut book = load_book(&path)?;
-      let compile_res = compile_book(&mut book, opts, diagnostics_cfg, None)?;
+      let mut book = load_book(&paths)?;
+      let diagnostics = if check_only_changed {
+        check_book_incremental(&book, diagnostics_cfg, compile_opts, Path::new(CACHE_FILE))?
+      } else {
+        check_book(&mut book, diagnostics_cfg, compile_opts)?
+      };
+      if !quiet {
+        eprintln!("{}", diagnostics);
+      }
+    }
+
+    Mode::GenHvm(GenHvmArgs {
+      gen: GenArgs { comp_opts, warn_opts, paths, only, report_pruned, output },
+      annotate,
+      validate,
+      dump_nets,
+      cache_dir,
+      no_cache,
+    }) => {
+      let diagnostics_cfg = set_warning_cfg_from_cli(&config, default_diagnostics_cfg, warn_opts);
+      let diagnostics_cfg = DiagnosticsConfig { report_pruned, ..diagnostics_cfg };
+      let opts =
+        CompileOpts { roots: roots_from_cli(only), ..compile_opts_from_cli(&config, &comp_opts, backend) };
+
+      let mut book = load_book(&paths)?;
+      let hook = timing.then_some(&mut print_phase as &mut dyn FnMut(PhaseInfo));
+      let compile_res = match cache_dir.filter(|_| !no_cache) {
+        Some(cache_dir) => compile_book_cached(&mut book, opts, diagnostics_cfg, &cache_dir, hook)?,
+        None => compile_book_with_hook(&mut book, opts, diagnostics_cfg, None, hook)?,
+      };
 
       eprint!("{}", compile_res.diagnostics);
-      println!("{}", display_hvm_book(&compile_res.hvm_book));
+      if validate {
+        validate_hvm_book(
+          &compile_res.hvm_book,
+          &compile_res.name_map,
+          &cli.hvm_path,
+          cli.temp_dir.as_deref(),
+        )?;
+      }
+      let out = if annotate {
+        display_annotated_hvm_book(&compile_res.hvm_book).to_string()
+      } else {
+        display_hvm_book(&compile_res.hvm_book).to_string()
+      };
+      if let Some(output) = output {
+        write_output(&output, &out)?;
+      } else {
+        println!("{out}");
+      }
+      if let Some(dump_nets) = dump_nets {
+        write_output(&dump_nets, &display_hvm_book_dot(&compile_res.hvm_book).to_string())?;
+      }
     }
 
-    Mode::GenC(GenArgs { comp_opts, warn_opts, path })
-    | Mode::GenCu(GenArgs { comp_opts, warn_opts, path }) => {
-      let diagnostics_cfg = set_warning_cfg_from_cli(DiagnosticsConfig::default(), warn_opts);
-      let opts = compile_opts_from_cli(&comp_opts);
+    Mode::GenC(GenCArgs {
+      native:
+        GenNativeArgs {
+          gen: GenArgs { comp_opts, warn_opts, paths, only, report_pruned, output },
+          keep_temp,
+          backend_flags,
+          dry_run,
+        },
+      split,
+    }) => {
+      let diagnostics_cfg = set_warning_cfg_from_cli(&config, default_diagnostics_cfg, warn_opts);
+      let diagnostics_cfg = DiagnosticsConfig { report_pruned, ..diagnostics_cfg };
+      let opts =
+        CompileOpts { roots: roots_from_cli(only), ..compile_opts_from_cli(&config, &comp_opts, backend) };
+
+      let mut book = load_book(&paths)?;
+      let hook = timing.then_some(&mut print_phase as &mut dyn FnMut(PhaseInfo));
+      let compile_res = compile_book_with_hook(&mut book, opts, diagnostics_cfg, None, hook)?;
+      eprint!("{}", compile_res.diagnostics);
 
-      let mut book = load_book(&path)?;
-      let compile_res = compile_book(&mut book, opts, diagnostics_cfg, None)?;
+      if dry_run {
+        if keep_temp {
+          let out_path = write_temp_hvm_file(&compile_res.hvm_book, cli.temp_dir.as_deref())?;
+          eprintln!("Kept the intermediate HVM file at '{}'.", out_path.display());
+        }
+        print!("{}", net_stats(&compile_res.hvm_book));
+        return Ok(exit_code);
+      }
 
-      let out_path = ".out.hvm";
-      std::fs::write(out_path, display_hvm_book(&compile_res.hvm_book).to_string())
-        .map_err(|x| x.to_string())?;
+      let out_path = write_temp_hvm_file(&compile_res.hvm_book, cli.temp_dir.as_deref())?;
 
-      let gen_fn = |out_path: &str| {
+      let gen_fn = |out_path: &Path| {
         let mut process = std::process::Command::new(cli.hvm_path);
-        process.arg(gen_cmd).arg(out_path);
+        process.arg(gen_cmd).arg(out_path).args(backend_flags.iter().flat_map(|flags| &flags.0));
         process.output().map_err(|e| format!("While running hvm: {e}"))
       };
 
-      let std::process::Output { stdout, stderr, status } = gen_fn(out_path)?;
+      let start = Instant::now();
+      let gen_res = gen_fn(&out_path);
+      if timing {
+        print_phase(PhaseInfo { name: gen_cmd, elapsed: start.elapsed() });
+      }
+
+      if keep_temp {
+        eprintln!("Kept the intermediate HVM file at '{}'.", out_path.display());
+      } else if let Err(e) = std::fs::remove_file(&out_path) {
+        eprintln!("Error removing HVM output file. {e}");
+      }
+
+      let std::process::Output { stdout, stderr, status } = gen_res?;
       let out = String::from_utf8_lossy(&stdout);
       let err = String::from_utf8_lossy(&stderr);
       let status = if !status.success() { status.to_string() } else { String::new() };
 
-      if let Err(e) = std::fs::remove_file(out_path) {
-        eprintln!("Error removing HVM output file. {e}");
+      // The output of a failed run is not code, so it's not written over the output file.
+      if let Some(output) = &output {
+        if !status.is_empty() {
+          return Err(
+            format!("hvm failed ({status}), so '{}' was not written.\n{err}", output.display()).into(),
+          );
+        }
       }
 
       eprintln!("{err}");
-      println!("{out}");
-      println!("{status}");
+      match (output, split) {
+        (Some(output), Some(parts)) => write_split_c_output(&output, &out, parts as usize)?,
+        (Some(output), None) => write_output(&output, &out)?,
+        (None, _) => {
+          println!("{out}");
+          println!("{status}");
+        }
+      }
     }
 
-    Mode::Desugar { path, comp_opts, warn_opts, pretty } => {
-      let diagnostics_cfg = set_warning_cfg_from_cli(DiagnosticsConfig::default(), warn_opts);
+    Mode::Desugar { list_passes: true, .. } => {
+      for (name, _) in DESUGAR_PASSES {
+        println!("{name}");
+      }
+    }
 
-      let opts = compile_opts_from_cli(&comp_opts);
+    Mode::Desugar { paths, comp_opts, warn_opts, pretty, stop_after, list_passes: false, to_encoding } => {
+      let diagnostics_cfg = set_warning_cfg_from_cli(&config, default_diagnostics_cfg, warn_opts);
 
-      let mut book = load_book(&path)?;
-      let diagnostics = desugar_book(&mut book, opts, diagnostics_cfg, None)?;
+      let mut opts = compile_opts_from_cli(&config, &comp_opts, backend);
+      if let Some(encoding) = to_encoding {
+        opts.adt_encoding = encoding.into();
+      }
+
+      let mut book = load_book(&paths)?;
+      let hook = timing.then_some(&mut print_phase as &mut dyn FnMut(PhaseInfo));
+      let diagnostics =
+        desugar_book_until(&mut book, opts, diagnostics_cfg, None, stop_after.as_deref(), hook)?;
 
       eprint!("{diagnostics}");
       if pretty {
@@ -341,40 +1468,1139 @@ fn execute_cli_mode(mut cli: Cli) -> Result<(), Diagnostics> {
       }
     }
 
-    Mode::Run(RunArgs { pretty, run_opts, comp_opts, warn_opts, path, arguments })
-    | Mode::RunC(RunArgs { pretty, run_opts, comp_opts, warn_opts, path, arguments })
-    | Mode::RunCu(RunArgs { pretty, run_opts, comp_opts, warn_opts, path, arguments }) => {
-      let CliRunOpts { linear, print_stats } = run_opts;
+    Mode::Repl(ReplArgs { pretty, run_opts, comp_opts, warn_opts, prelude, deterministic }) => {
+      let CliRunOpts { linear, print_stats, timeout, max_memory, float_format, readback_depth_limit } =
+        run_opts;
+
+      let diagnostics_cfg = DiagnosticsConfig {
+        format: diagnostics_format,
+        color,
+        quiet,
+        ..DiagnosticsConfig::new(Severity::Allow, arg_verbose)
+      };
+      let diagnostics_cfg = set_warning_cfg_from_cli(&config, diagnostics_cfg, warn_opts);
+
+      let compile_opts = compile_opts_from_cli(&config, &comp_opts, backend);
+
+      if !quiet {
+        compile_opts.check_for_strict();
+      }
+
+      let run_opts = RunOpts {
+        linear_readback: linear,
+        pretty,
+        hvm_path: cli.hvm_path,
+        timeout,
+        emit_hvm: None,
+        temp_dir: cli.temp_dir,
+        deterministic,
+        max_memory,
+        profile: false,
+        trace: false,
+        trace_limit: None,
+        coverage: false,
+        float_format: float_format.map_or(FloatFormat::Rounded, Into::into),
+        threads: None,
+        readback_depth_limit: Some(readback_depth_limit as usize),
+        backend_flags: vec![],
+      };
+
+      let book = if let Some(prelude) = prelude { load_book(&[prelude])? } else { load_prelude()? };
+      repl(book, run_opts, compile_opts, diagnostics_cfg, print_stats);
+    }
+
+    Mode::Test(TestArgs {
+      pretty,
+      run_opts,
+      comp_opts,
+      warn_opts,
+      paths,
+      filter,
+      coverage,
+      deterministic,
+    }) => {
+      let CliRunOpts { linear, print_stats, timeout, max_memory, float_format, readback_depth_limit } =
+        run_opts;
+
+      let diagnostics_cfg = DiagnosticsConfig {
+        format: diagnostics_format,
+        color,
+        quiet,
+        ..DiagnosticsConfig::new(Severity::Allow, arg_verbose)
+      };
+      let diagnostics_cfg = set_warning_cfg_from_cli(&config, diagnostics_cfg, warn_opts);
+
+      let compile_opts = compile_opts_from_cli(&config, &comp_opts, backend);
+
+      if !quiet {
+        compile_opts.check_for_strict();
+      }
+
+      let run_opts = RunOpts {
+        linear_readback: linear,
+        pretty,
+        hvm_path: cli.hvm_path,
+        timeout,
+        emit_hvm: None,
+        temp_dir: cli.temp_dir,
+        deterministic,
+        max_memory,
+        profile: false,
+        trace: false,
+        trace_limit: None,
+        coverage,
+        float_format: float_format.map_or(FloatFormat::Rounded, Into::into),
+        threads: None,
+        readback_depth_limit: Some(readback_depth_limit as usize),
+        backend_flags: vec![],
+      };
+
+      let book = load_book(&paths)?;
+      run_tests(&book, filter.as_deref(), run_opts, compile_opts, diagnostics_cfg, print_stats)?;
+    }
+
+    Mode::Fmt { write, path } => {
+      let code = std::fs::read_to_string(&path).map_err(|e| e.to_string())?;
+      let formatted = format_book(&code, &path)?;
+
+      if write {
+        std::fs::write(&path, formatted).map_err(|e| e.to_string())?;
+      } else {
+        print!("{formatted}");
+      }
+    }
+
+    Mode::DumpAst { builtins, paths } => {
+      let mut book = load_book(&paths)?;
+      if !builtins {
+        book.defs.retain(|_, def| !def.builtin);
+        book.adts.retain(|_, adt| !adt.builtin);
+        book.ctrs.retain(|_, adt| book.adts.contains_key(adt));
+      }
+
+      // Written directly to stdout, so that big programs don't need to be rendered in memory first.
+      let mut out = std::io::BufWriter::new(std::io::stdout().lock());
+      serde_json::to_writer(&mut out, &book).map_err(|e| e.to_string())?;
+      writeln!(out).map_err(|e| e.to_string())?;
+    }
+
+    Mode::Deps { order, dot, paths } => {
+      let book = load_book(&paths)?;
+      let deps = def_dependencies(&book);
+      let sorted_deps = |name: &Name| {
+        let mut names = deps[name].iter().map(|dep| dep.to_string()).collect::<Vec<_>>();
+        names.sort();
+        names
+      };
+
+      if dot {
+        println!("digraph deps {{");
+        for name in book.defs.keys().filter(|name| deps.contains_key(*name)) {
+          println!("  \"{name}\";");
+          for dep in sorted_deps(name) {
+            println!("  \"{name}\" -> \"{dep}\";");
+          }
+        }
+        println!("}}");
+      } else if order {
+        for group in dependency_order(book.defs.keys(), &deps) {
+          let is_cycle = group.len() > 1 || deps[&group[0]].contains(&group[0]);
+          if is_cycle {
+            let group = group.iter().map(|name| name.to_string()).collect::<Vec<_>>();
+            println!("{} (cycle)", group.join(" "));
+          } else {
+            println!("{}", group[0]);
+          }
+        }
+      } else {
+        for name in book.defs.keys().filter(|name| deps.contains_key(*name)) {
+          println!("{name}: {}", sorted_deps(name).join(" "));
+        }
+      }
+    }
+
+    Mode::Diff { exit_code: use_exit_code, old, new } => {
+      let old_book = load_book(&[old])?;
+      let new_book = load_book(&[new])?;
+      let (mut added, mut removed, mut changed) = (0, 0, 0);
+
+      // Definitions are compared up to the names of their variables, so renaming a variable is not a change.
+      for def in old_book.defs.values().filter(|def| !def.builtin) {
+        match new_book.defs.get(&def.name) {
+          None => {
+            println!("removed: {}", def.name);
+            removed += 1;
+          }
+          Some(new_def) if !def.alpha_eq(new_def) => {
+            println!("changed: {}", def.name);
+            changed += 1;
+          }
+          Some(_) => {}
+        }
+      }
+      for def in new_book.defs.values().filter(|def| !def.builtin) {
+        if !old_book.defs.contains_key(&def.name) {
+          println!("added: {}", def.name);
+          added += 1;
+        }
+      }
+
+      println!("{added} added, {removed} removed, {changed} changed");
+      if use_exit_code && added + removed + changed > 0 {
+        exit_code = ExitCode::FAILURE;
+      }
+    }
+
+    Mode::ListDefs { sort, paths } => {
+      let book = load_book(&paths)?;
+      let deps = def_dependencies(&book);
+      let reachable = reachable_defs(&book, &deps);
+      let recursive = dependency_order(book.defs.keys(), &deps)
+        .into_iter()
+        .filter(|group| group.len() > 1 || deps[&group[0]].contains(&group[0]))
+        .flatten()
+        .collect::<Vec<_>>();
+
+      let mut defs = book.defs.values().filter(|def| !def.builtin).collect::<Vec<_>>();
+      match sort {
+        DefSort::Name => defs.sort_by(|a, b| a.name.cmp(&b.name)),
+        DefSort::Arity => defs.sort_by_key(|def| def_arity(def)),
+        DefSort::Source => {}
+      }
+
+      let yes_no = |cond: bool| if cond { "yes" } else { "no" };
+      let width = defs.iter().map(|def| def.name.len()).max().unwrap_or(0).max("name".len());
+      println!("{:<width$}  arity  recursive  reachable", "name");
+      for def in defs {
+        let is_recursive = yes_no(recursive.contains(&def.name));
+        let is_reachable = yes_no(reachable.contains(&def.name));
+        println!("{:<width$}  {:<5}  {is_recursive:<9}  {is_reachable}", def.name, def_arity(def));
+      }
+    }
+
+    Mode::Stats { paths } => {
+      let book = load_book(&paths)?;
+      let deps = def_dependencies(&book);
+      let cycles = dependency_order(book.defs.keys(), &deps)
+        .into_iter()
+        .filter(|group| group.len() > 1 || deps[&group[0]].contains(&group[0]))
+        .count();
+
+      let defs = book.defs.values().filter(|def| !def.builtin).collect::<Vec<_>>();
+      let def_size = |def: &Definition| def.rules.iter().map(|rule| rule.body.node_count()).sum::<usize>();
+      // Reversed so that the first of the largest definitions is picked.
+      let largest = defs.iter().map(|def| (&def.name, def_size(def))).rev().max_by_key(|(_, size)| *size);
+      let adts = book.adts.values().filter(|adt| !adt.builtin).collect::<Vec<_>>();
+
+      println!("definitions       {}", defs.len());
+      println!("term nodes        {}", defs.iter().map(|def| def_size(def)).sum::<usize>());
+      match largest {
+        Some((name, size)) => println!("largest def       {name} ({size} nodes)"),
+        None => println!("largest def       -"),
+      }
+      println!("types             {}", adts.len());
+      println!("constructors      {}", adts.iter().map(|adt| adt.ctrs.len()).sum::<usize>());
+      println!("recursion cycles  {cycles}");
+    }
+
+    Mode::Run(RunRustArgs { run, trace, trace_limit, coverage, deterministic }) => {
+      let RunArgs {
+        pretty,
+        run_opts,
+        comp_opts,
+        warn_opts,
+        path,
+        include,
+        emit_hvm,
+        profile,
+        threads,
+        backend_flags,
+        exit_code: use_exit_code,
+        result_only,
+        output_json,
+        output_sexpr,
+        arguments,
+        args_file,
+        args_stdin,
+        input_json,
+      } = run;
+      let CliRunOpts { linear, print_stats, timeout, max_memory, float_format, readback_depth_limit } =
+        run_opts;
+
+      let diagnostics_cfg = DiagnosticsConfig {
+        format: diagnostics_format,
+        color,
+        quiet,
+        ..DiagnosticsConfig::new(Severity::Allow, arg_verbose)
+      };
+      let diagnostics_cfg = set_warning_cfg_from_cli(&config, diagnostics_cfg, warn_opts);
 
-      let diagnostics_cfg =
-        set_warning_cfg_from_cli(DiagnosticsConfig::new(Severity::Allow, arg_verbose), warn_opts);
+      let compile_opts = compile_opts_from_cli(&config, &comp_opts, backend);
 
-      let compile_opts = compile_opts_from_cli(&comp_opts);
+      if !quiet {
+        compile_opts.check_for_strict();
+      }
 
-      compile_opts.check_for_strict();
+      let run_opts = RunOpts {
+        linear_readback: linear,
+        pretty,
+        hvm_path: cli.hvm_path,
+        timeout,
+        emit_hvm,
+        temp_dir: cli.temp_dir,
+        deterministic,
+        max_memory,
+        profile,
+        trace,
+        trace_limit,
+        coverage,
+        float_format: float_format.map_or(FloatFormat::Rounded, Into::into),
+        threads,
+        readback_depth_limit: Some(readback_depth_limit as usize),
+        backend_flags: backend_flags.map_or_else(Vec::new, |flags| flags.0),
+      };
 
-      let run_opts = RunOpts { linear_readback: linear, pretty, hvm_path: cli.hvm_path };
+      let paths = [path].into_iter().chain(include).collect::<Vec<_>>();
+      if args_stdin && paths.iter().any(|path| path == Path::new(STDIN_PATH)) {
+        return Err("Can't read both the program and its arguments from stdin.".to_string().into());
+      }
 
-      let book = load_book(&path)?;
-      if let Some((term, stats, diags)) =
-        run_book(book, run_opts, compile_opts, diagnostics_cfg, arguments, run_cmd)?
-      {
+      let arguments = if let Some(args_file) = args_file {
+        let code = std::fs::read_to_string(&args_file)
+          .map_err(|e| format!("Failed to read arguments file '{}'.\n{e}", args_file.display()))?;
+        Some(parse_arguments(&code)?)
+      } else if args_stdin {
+        let mut code = String::new();
+        std::io::stdin().read_to_string(&mut code).map_err(|e| format!("Failed to read stdin.\n{e}"))?;
+        Some(parse_arguments(&code)?)
+      } else {
+        arguments
+      };
+      let arguments = match input_json {
+        Some(input_json) => {
+          Some(arguments.into_iter().flatten().chain([read_json_argument(&input_json)?]).collect())
+        }
+        None => arguments,
+      };
+
+      let book = load_book(&paths)?;
+      let bool_ctrs = bool_like_ctrs(&book);
+      let float_format = run_opts.float_format;
+      let hook = timing.then_some(&mut print_phase as &mut dyn FnMut(PhaseInfo));
+      let res = run_book_with_hook(book, run_opts, compile_opts, diagnostics_cfg, arguments, run_cmd, hook)?;
+      if let Some((term, stats, mut diags)) = res {
+        diags.set_display_config(diagnostics_cfg);
         eprint!("{diags}");
-        if pretty {
-          println!("Result:\n{}", term.display_pretty(0));
+        if output_json {
+          println!("{}", term_to_json(&term)?);
+        } else if output_sexpr {
+          println!("{}", term_to_sexpr(&term, float_format)?);
+        } else if result_only {
+          println!("{}", term.display_with_floats(float_format));
+        } else if pretty {
+          println!("Result:\n{}", term.display_pretty_with_floats(0, float_format));
         } else {
-          println!("Result: {}", term);
+          println!("Result: {}", term.display_with_floats(float_format));
         }
         if print_stats {
           println!("{stats}");
         }
+        if let Some(profile) = &stats.profile {
+          println!("{profile}");
+        }
+        if let Some(coverage) = &stats.coverage {
+          print!("{coverage}");
+        }
+        if use_exit_code {
+          exit_code = exit_code_from_result(&term, &bool_ctrs)?;
+        }
+      }
+    }
+
+    Mode::RunC(..) | Mode::RunCu(..) | Mode::GenCu(..) => unreachable!("merged into run and gen-c above"),
+
+    Mode::Bench(BenchArgs {
+      runs,
+      warmup,
+      json,
+      threads,
+      deterministic,
+      run_opts,
+      comp_opts,
+      warn_opts,
+      path,
+      arguments,
+      ..
+    }) => {
+      let CliRunOpts { linear, timeout, max_memory, float_format, readback_depth_limit, .. } = run_opts;
+
+      let diagnostics_cfg = DiagnosticsConfig {
+        format: diagnostics_format,
+        color,
+        quiet,
+        ..DiagnosticsConfig::new(Severity::Allow, arg_verbose)
+      };
+      let diagnostics_cfg = set_warning_cfg_from_cli(&config, diagnostics_cfg, warn_opts);
+      // The warnings are the same on every run, so only the first one reports them.
+      let quiet_diagnostics_cfg = DiagnosticsConfig {
+        format: diagnostics_format,
+        color,
+        ..DiagnosticsConfig::new(Severity::Allow, false)
+      };
+
+      let compile_opts = compile_opts_from_cli(&config, &comp_opts, backend);
+
+      if !quiet {
+        compile_opts.check_for_strict();
+      }
+
+      let run_opts = RunOpts {
+        linear_readback: linear,
+        pretty: false,
+        hvm_path: cli.hvm_path,
+        timeout,
+        emit_hvm: None,
+        temp_dir: cli.temp_dir,
+        deterministic,
+        max_memory,
+        profile: false,
+        trace: false,
+        trace_limit: None,
+        coverage: false,
+        float_format: float_format.map_or(FloatFormat::Rounded, Into::into),
+        threads,
+        readback_depth_limit: Some(readback_depth_limit as usize),
+        backend_flags: vec![],
+      };
+
+      let book = load_book(&[path])?;
+      let mut stats = Vec::new();
+      for i in 0..warmup + runs {
+        let diagnostics_cfg = if i == 0 { diagnostics_cfg } else { quiet_diagnostics_cfg };
+        let res = run_book(
+          book.clone(),
+          run_opts.clone(),
+          compile_opts.clone(),
+          diagnostics_cfg,
+          arguments.clone(),
+          run_cmd,
+        )?;
+        if let Some((_, run_stats, _)) = res {
+          if i >= warmup {
+            stats.push(run_stats);
+          }
+        }
+      }
+
+      let times = stats.iter().filter_map(|stats| stats.elapsed).map(|time| time.as_secs_f64());
+      let interactions = stats.iter().filter_map(|stats| stats.interactions).map(|itrs| itrs as f64);
+      let report = BenchReport {
+        runs,
+        warmup,
+        time: BenchSummary::new(times.collect()),
+        interactions: BenchSummary::new(interactions.collect()),
+      };
+
+      if json {
+        println!("{}", serde_json::to_string(&report).map_err(|e| e.to_string())?);
+      } else {
+        print!("{report}");
       }
     }
   };
+  Ok(exit_code)
+}
+
+/// The constructors of the types with exactly two constructors without fields, like
+/// `type Bool = True | False`, with the index of each constructor in its type.
+fn bool_like_ctrs(book: &Book) -> Vec<(Name, u8)> {
+  book
+    .adts
+    .values()
+    .filter(|adt| adt.ctrs.len() == 2 && adt.ctrs.values().all(|fields| fields.is_empty()))
+    .flat_map(|adt| adt.ctrs.keys().cloned().zip(0..))
+    .collect()
+}
+
+/// The exit code for the result of a program run with `--exit-code`.
+///
+/// Numbers exit with their value modulo 256, and the constructors returned by [`bool_like_ctrs`] with
+/// their index, so that the first constructor, like `True`, means success.
+fn exit_code_from_result(term: &Term, bool_ctrs: &[(Name, u8)]) -> Result<ExitCode, String> {
+  let code = match term {
+    Term::Num { val: Num::U24(val) } => Some((val % 256) as u8),
+    Term::Num { val: Num::I24(val) } => Some(val.rem_euclid(256) as u8),
+    Term::Num { val: Num::F24(val) } if val.fract() == 0.0 => Some((*val as i64).rem_euclid(256) as u8),
+    term => {
+      result_ctr(term).and_then(|nam| bool_ctrs.iter().find(|(ctr, _)| ctr == nam)).map(|(_, idx)| *idx)
+    }
+  };
+  code.map(ExitCode::from).ok_or_else(|| {
+    format!(
+      "Can't use the result '{term}' as the exit code. \
+       It must be a number or a constructor of a type like 'type Bool = True | False'."
+    )
+  })
+}
+
+/// The statistics of the measured runs of `bend bench`.
+#[derive(serde::Serialize)]
+struct BenchReport {
+  runs: u64,
+  warmup: u64,
+  /// Time taken by the evaluation, in seconds.
+  time: Option<BenchSummary>,
+  interactions: Option<BenchSummary>,
+}
+
+/// Summary of the values of one statistic over several runs.
+#[derive(serde::Serialize)]
+struct BenchSummary {
+  min: f64,
+  median: f64,
+  mean: f64,
+  stddev: f64,
+}
+
+impl BenchSummary {
+  /// Summarizes the given values, or returns None if there are none,
+  /// for example because the runtime didn't report that statistic.
+  fn new(mut values: Vec<f64>) -> Option<Self> {
+    if values.is_empty() {
+      return None;
+    }
+    values.sort_by(f64::total_cmp);
+    let len = values.len();
+    let min = values[0];
+    let median = if len % 2 == 0 { (values[len / 2 - 1] + values[len / 2]) / 2.0 } else { values[len / 2] };
+    let mean = values.iter().sum::<f64>() / len as f64;
+    let stddev = (values.iter().map(|value| (value - mean).powi(2)).sum::<f64>() / len as f64).sqrt();
+    Some(BenchSummary { min, median, mean, stddev })
+  }
+}
+
+impl std::fmt::Display for BenchReport {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    writeln!(f, "Runs: {} ({} warmup runs discarded)", self.runs, self.warmup)?;
+    if let Some(BenchSummary { min, median, mean, stddev }) = &self.time {
+      writeln!(f, "Time: min {min:.3}s, median {median:.3}s, mean {mean:.3}s, stddev {stddev:.3}s")?;
+    }
+    if let Some(BenchSummary { min, median, mean, stddev }) = &self.interactions {
+      writeln!(f, "Interactions: min {min}, median {median}, mean {mean:.1}, stddev {stddev:.1}")?;
+    }
+    Ok(())
+  }
+}
+
+/// A description of what a diagnostic rule checks for and how to address it.
+fn warning_explanation(rule: &WarningArgs) -> &'static str {
+  match rule {
+    WarningArgs::All => unreachable!("'all' is not a single diagnostic rule"),
+    WarningArgs::IrrefutableMatch => {
+      "irrefutable-match: The first arm of a 'match' expression is a variable pattern instead of a constructor.
+A variable pattern matches any value, so the first arm is always taken and all the other arms are ignored.
+This is usually caused by misspelling a constructor name or by matching on constructors that were never
+declared in a 'type' definition, which makes them be read as variables. Check the spelling of the
+constructors, declare the type, or use a 'let' expression if the match was meant to just bind the value."
+    }
+    WarningArgs::RedundantMatch => {
+      "redundant-match: A 'match' expression has more than one arm for the same constructor.
+Only the first of these arms is ever taken and the others are ignored.
+Remove the duplicated arms or merge their bodies into the first one."
+    }
+    WarningArgs::UnreachableMatch => {
+      "unreachable-match: A 'match' expression has arms after a default case that already covers them.
+Those arms are never taken and are discarded during compilation.
+Move the default case to the end of the match or remove the unreachable arms."
+    }
+    WarningArgs::UnusedDefinition => {
+      "unused-definition: A definition is never referenced, directly or indirectly, by the entrypoint.
+Unused definitions are removed from the compiled program, so they don't affect how it runs.
+Use the definition somewhere, remove it, or allow this rule if the file is meant to be used as a library."
+    }
+    WarningArgs::RepeatedBind => {
+      "repeated-bind: The same variable name is bound more than once in the patterns of a rule.
+Only the last of the binds is visible in the body of the rule and the previous values are erased.
+Rename the variables so that each one has a unique name, or use '*' for the values that are not needed."
+    }
+    WarningArgs::RecursionCycle => {
+      "recursion-cycle: A group of functions calls itself through strict references.
+HVM evaluates eagerly, so a function that directly expands into a call to itself never stops expanding,
+making the program loop forever or run out of memory even if the recursion would terminate lazily.
+Use 'match', 'fold' and 'bend' so the recursive calls are lifted to lazy references, pass the recursive
+function as an argument instead of calling it, or replace non-linear 'let' with 'use'.
+See docs/lazy-definitions.md for a detailed explanation. This rule is an error by default."
+    }
+    WarningArgs::ShadowedBuiltin => {
+      "shadowed-builtin: A user definition has the same name as a built-in type or function.
+References to that name can end up pointing to a different item than the one expected, which leads to
+confusing errors or results. Rename the definition so that it doesn't clash with the built-ins listed in
+BUILTINS.md."
+    }
+    WarningArgs::MissingBaseCase => {
+      "missing-base-case: A recursive function calls itself in every branch, so the recursion never stops.
+This usually means that the base case, the branch that returns without recursing, is missing or also makes
+a recursive call by mistake. Add a branch that doesn't recurse, like the 'Nil' case of a list or the '0'
+case of a number. This check is a heuristic and is disabled by default; enable it with -W missing-base-case."
+    }
+    WarningArgs::CtrArity => {
+      "ctr-arity: A constructor is applied to more arguments than the fields it has.
+Applying a constructor to fewer arguments is allowed, since the result is a function that takes the remaining
+fields. Extra arguments are applied to the constructed value, which is usually a mistake, like a missing pair
+of parentheses. 'check --strict-types' reports this as an error."
+    }
+    WarningArgs::ShadowedBind => {
+      "shadowed-bind: A variable bound by a pattern has the same name as a variable bound by an enclosing lambda,
+'let' or parameter. Inside the pattern's scope the name refers to the new variable, so uses that meant the
+outer one silently get a different value. This is narrower than repeated-bind, which reports a name bound
+twice in the same pattern. Rename one of the variables. This check is disabled by default; enable it with
+-W shadowed-bind."
+    }
+    WarningArgs::NumericOverflow => {
+      "numeric-overflow: A number doesn't fit in the numeric type it has at runtime. Number patterns in the
+rules of a function become U24 numbers, which wrap around above 16777215, so the rule matches a different
+number than the one written. Float literals become F24 numbers, which only represent every whole number up
+to 65536, so larger whole numbers may be rounded. Number literals in terms that don't fit their type are
+already rejected when parsing."
+    }
+    WarningArgs::UnusedBind => {
+      "unused-bind: A variable bound by a 'let', 'ask', 'use' or by a pattern in the parameters of a rule is
+never used. The value is just erased, so binding it is dead code and often a mistake, like using
+the wrong variable. Replace the variable with '*' if the value is not needed, or remove the 'let' or 'use'. Variables
+bound by lambdas, parameters that aren't destructured and names starting with '_' are not reported."
+    }
+    WarningArgs::NamingConvention => {
+      "naming-convention: The name of a type, constructor or function doesn't follow the naming convention:
+PascalCase for types and constructors, like 'List/Cons', and camelCase for functions, like 'List/sumAll'.
+Only the part after the last '/' is checked. Following one convention keeps handwritten and generated code
+consistent. Since conventions vary, this check is disabled by default and not enabled by -W all; enable it
+with -W naming-convention."
+    }
+    WarningArgs::RedundantLambda => {
+      "redundant-lambda: A lambda only applies a function to its variable, like 'λx (f x)', so it can be replaced
+with the function itself, 'f'. The 'eta' optimization already removes these lambdas when compiling, but
+without telling, so this check helps to keep the source simple. The function can't use the variable of the
+lambda. This check is disabled by default; enable it with -W redundant-lambda."
+    }
+    WarningArgs::PotentialExponential => {
+      "potential-exponential: A variable is used to compute the arguments of several recursive calls in the
+same branch of a function, like 'n' in '(+ (fib (- n 1)) (fib (- n 2)))'. Each of those calls can make as
+many calls again, so the work can grow exponentially with the depth of the recursion. If the calls compute
+the same value, compute it once with a 'let' and use the result twice, since a variable is only evaluated
+once. Variables that are passed unchanged to every call, like the function given to a 'map', are not reported.
+This check is a heuristic and is disabled by default; enable it with -W potential-exponential."
+    }
+    WarningArgs::MatchArmOrder => {
+      "match-arm-order: The arms of a 'match' or 'fold' are not in the order in which the constructors of the
+type are declared. With the NumScott encoding, a match becomes a dispatch on the numeric tag of the
+constructor, so writing the arms in the declared order keeps the compiled match simple to follow and avoids
+mixing up arms whose bodies look alike. Only reported when compiling with the NumScott encoding, the default
+one. This check is disabled by default; enable it with -W match-arm-order."
+    }
+  }
+}
+
+/// Prints the time taken by a phase, reported with `-vv`.
+fn print_phase_time(phase: PhaseInfo) {
+  eprintln!("{}: {:.2?}", phase.name, phase.elapsed);
+}
+
+/// The number of definitions and nodes of the generated nets, and the largest net, for `gen-c --dry-run`.
+fn net_stats(hvm_book: &::hvm::ast::Book) -> String {
+  let sizes = hvm_book.defs.iter().map(|(name, net)| (name, count_nodes(net))).collect::<Vec<_>>();
+  // Reversed so that the first of the largest nets is picked.
+  let largest = match sizes.iter().rev().max_by_key(|(_, size)| *size) {
+    Some((name, size)) => format!("{name} ({size} nodes)"),
+    None => "-".to_string(),
+  };
+  format!(
+    "definitions  {}\nnodes        {}\nlargest net  {largest}\n",
+    sizes.len(),
+    sizes.iter().map(|(_, size)| size).sum::<usize>()
+  )
+}
+
+/// Checks that `hvm` accepts the generated HVM code, for `gen-hvm --validate`.
+///
+/// hvm has no command that only parses a program, so the code is given to `hvm gen-c`,
+/// which parses it and doesn't run it, and the generated C code is discarded.
+/// The errors are reported with the source definitions of the nets that they mention.
+fn validate_hvm_book(
+  hvm_book: &::hvm::ast::Book,
+  name_map: &HashMap<String, Name>,
+  hvm_path: &str,
+  temp_dir: Option<&Path>,
+) -> Result<(), Diagnostics> {
+  let path = write_temp_hvm_file(hvm_book, temp_dir)?;
+  let output = std::process::Command::new(hvm_path).arg("gen-c").arg(&path).output();
+  let _ = std::fs::remove_file(&path);
+  let output = output.map_err(|e| format!("While running hvm to validate the generated code: {e}"))?;
+  if output.status.success() {
+    return Ok(());
+  }
+
+  let err = String::from_utf8_lossy(&output.stderr);
+  let err = err.trim_end();
+  // A net is mentioned by its name with a '@' in front, not followed by more characters of a name.
+  let mentions = |net: &str| {
+    err.match_indices(&format!("@{net}")).any(|(idx, mention)| {
+      !err[idx + mention.len()..].starts_with(|c: char| c.is_ascii_alphanumeric() || "_./-".contains(c))
+    })
+  };
+  let sources = name_map.iter().filter(|(net, _)| mentions(net)).map(|(_, source)| format!("'{source}'"));
+  let sources = sources.collect::<BTreeSet<_>>().into_iter().collect::<Vec<_>>().join(", ");
+
+  let code = if sources.is_empty() {
+    "the generated code".to_string()
+  } else {
+    format!("the code generated for {sources}")
+  };
+  let mut diags = Diagnostics::default();
+  diags.add_book_error(format!("hvm rejected {code} ({}):\n{err}", output.status));
+  Err(diags)
+}
+
+/// Writes generated code to the file given with `-o`.
+fn write_output(path: &Path, out: &str) -> Result<(), String> {
+  std::fs::write(path, out).map_err(|e| format!("Failed to write output file '{}'.\n{e}", path.display()))
+}
+
+/// Writes the generated C code split into `parts` files named after `path`, plus a header.
+///
+/// For `out.c`, the runtime goes to `out.c`, the other files are `out_1.c`, `out_2.c`, ...
+/// and the header is `out.h`.
+fn write_split_c_output(path: &Path, code: &str, parts: usize) -> Result<(), String> {
+  let header_path = path.with_extension("h");
+  let header_name = header_path.file_name().unwrap_or_default().to_string_lossy();
+  let split = split_c_program(code, parts, &header_name)?;
+
+  write_output(&header_path, &split.header)?;
+  let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+  let ext = path.extension().map_or("c".into(), |ext| ext.to_string_lossy());
+  for (i, unit) in split.units.iter().enumerate() {
+    let unit_path =
+      if i == 0 { path.to_path_buf() } else { path.with_file_name(format!("{stem}_{i}.{ext}")) };
+    write_output(&unit_path, unit)?;
+  }
   Ok(())
 }
 
-fn set_warning_cfg_from_cli(mut cfg: DiagnosticsConfig, warn_opts: CliWarnOpts) -> DiagnosticsConfig {
+/// The extra arguments given to hvm with `--backend-flags`.
+#[derive(Clone, Debug)]
+struct BackendFlags(Vec<String>);
+
+/// Splits the arguments given to `--backend-flags` like a shell does.
+///
+/// Arguments are separated by whitespace, which can be kept inside of single or double quotes
+/// or escaped with a backslash, like `--backend-flags "-a 'b c' d\\ e"`.
+fn parse_backend_flags(arg: &str) -> Result<BackendFlags, String> {
+  let mut flags = vec![];
+  let mut flag: Option<String> = None;
+  let mut quote = None;
+  let mut chars = arg.chars();
+  while let Some(c) = chars.next() {
+    match (quote, c) {
+      (None, c) if c.is_whitespace() => flags.extend(flag.take()),
+      (None, '\'' | '"') => {
+        quote = Some(c);
+        flag.get_or_insert_with(String::new);
+      }
+      (Some(q), c) if c == q => quote = None,
+      // Like in a shell, backslashes are kept as they are inside of single quotes.
+      (None | Some('"'), '\\') => match chars.next() {
+        Some(c) => flag.get_or_insert_with(String::new).push(c),
+        None => return Err("expected a character after the final '\\'".to_string()),
+      },
+      (_, c) => flag.get_or_insert_with(String::new).push(c),
+    }
+  }
+  if let Some(quote) = quote {
+    return Err(format!("missing the closing {quote} of a quoted argument"));
+  }
+  flags.extend(flag);
+  Ok(BackendFlags(flags))
+}
+
+/// Parses a duration given as a number followed by one of the units `ms`, `s`, `m` or `h`.
+fn parse_duration(arg: &str) -> Result<Duration, String> {
+  let unit_start = arg.find(|c: char| !c.is_ascii_digit()).unwrap_or(arg.len());
+  let (num, unit) = arg.split_at(unit_start);
+  let num: u64 = num.parse().map_err(|_| format!("expected a number followed by a unit, found '{arg}'"))?;
+  let too_long = || format!("the duration '{arg}' is too long");
+  match unit {
+    "ms" => Ok(Duration::from_millis(num)),
+    "s" => Ok(Duration::from_secs(num)),
+    "m" => num.checked_mul(60).map(Duration::from_secs).ok_or_else(too_long),
+    "h" => num.checked_mul(60 * 60).map(Duration::from_secs).ok_or_else(too_long),
+    _ => Err(format!("unknown time unit '{unit}', expected one of 'ms', 's', 'm' or 'h'")),
+  }
+}
+
+/// Parses a number that must be at least one.
+fn parse_positive_number(arg: &str) -> Result<u64, String> {
+  match arg.parse() {
+    Ok(0) | Err(_) => Err(format!("expected a number greater than 0, found '{arg}'")),
+    Ok(runs) => Ok(runs),
+  }
+}
+
+fn parse_threads(arg: &str) -> Result<usize, String> {
+  match arg.parse::<usize>() {
+    Ok(threads) if threads.is_power_of_two() => Ok(threads),
+    _ => Err(format!("expected a power of two, found '{arg}'")),
+  }
+}
+
+/// Parses a number of bytes, optionally followed by one of the suffixes `K`, `M` or `G`.
+fn parse_memory_size(arg: &str) -> Result<usize, String> {
+  let suffix_start = arg.find(|c: char| !c.is_ascii_digit()).unwrap_or(arg.len());
+  let (num, suffix) = arg.split_at(suffix_start);
+  let num: usize = num.parse().map_err(|_| format!("expected a number of bytes, found '{arg}'"))?;
+  let scale: usize = match suffix {
+    "" => 1,
+    "K" | "k" => 1 << 10,
+    "M" | "m" => 1 << 20,
+    "G" | "g" => 1 << 30,
+    _ => return Err(format!("unknown size suffix '{suffix}', expected one of 'K', 'M' or 'G'")),
+  };
+  num.checked_mul(scale).ok_or_else(|| format!("the memory size '{arg}' is too large"))
+}
+
+/// The file read when no config file is given with `--config`.
+const CONFIG_PATH: &str = "bend.toml";
+
+/// The environment variable with optimization options applied after the ones of the config file.
+const OPTS_ENV_VAR: &str = "BEND_OPTS";
+
+/// Default options and warnings read from a config file, which the ones given in the command line override.
+///
+/// The file has an `[opts]` table, where `name = true` is the same as `-O name`, `name = false`
+/// the same as `-O no-name` and `max-net-size = N` the same as `-O max-net-size=N`, and a `[warnings]`
+/// table, where each warning is set to one of `"allow"`, `"warn"` or `"deny"`, the same as `-A`, `-W` and `-D`.
+#[derive(Debug, Default)]
+struct Config {
+  opts: Vec<ScopedOptArg>,
+  warnings: Vec<(Severity, WarningArgs)>,
+}
+
+impl Config {
+  /// Reads the given config file, or `bend.toml` in the current directory if it exists,
+  /// followed by the options of the `BEND_OPTS` environment variable.
+  fn load(path: Option<&Path>) -> Result<Config, String> {
+    let mut config = Config::load_file(path)?;
+    config.opts.extend(opts_from_env()?);
+    Ok(config)
+  }
+
+  fn load_file(path: Option<&Path>) -> Result<Config, String> {
+    let (path, code) = match path {
+      Some(path) => {
+        let code = std::fs::read_to_string(path)
+          .map_err(|e| format!("Failed to read config file '{}'.\n{e}", path.display()))?;
+        (path, code)
+      }
+      None => match std::fs::read_to_string(CONFIG_PATH) {
+        Ok(code) => (Path::new(CONFIG_PATH), code),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Config::default()),
+        Err(e) => return Err(format!("Failed to read config file '{CONFIG_PATH}'.\n{e}")),
+      },
+    };
+    Config::parse(&code).map_err(|e| format!("Invalid config file '{}'.\n{e}", path.display()))
+  }
+
+  /// Parses the subset of TOML made of tables and `key = value` entries.
+  fn parse(code: &str) -> Result<Config, String> {
+    let mut config = Config::default();
+    let mut table = None;
+    for (i, line) in code.lines().enumerate() {
+      let line_err = |msg: String| format!("Line {}: {msg}", i + 1);
+      let line = strip_comment(line).map_err(line_err)?.trim();
+      if line.is_empty() {
+        continue;
+      }
+      if let Some(name) = line.strip_prefix('[').and_then(|line| line.strip_suffix(']')) {
+        match name.trim() {
+          name @ ("opts" | "warnings") => table = Some(name),
+          name => return Err(line_err(format!("Unknown table '{name}', expected 'opts' or 'warnings'."))),
+        }
+        continue;
+      }
+      let Some((key, val)) = line.split_once('=') else {
+        return Err(line_err(format!("Expected 'key = value', found '{line}'.")));
+      };
+      let (key, val) = (key.trim(), val.trim());
+      match table {
+        Some("opts") if key == "max-net-size" => {
+          let size =
+            parse_positive_number(val).map_err(|e| line_err(format!("Invalid value for '{key}', {e}.")))?;
+          config.opts.push(ScopedOptArg { backend: None, opt: OptArg::MaxNetSize(size as usize) });
+        }
+        Some("opts") => {
+          let name = match val {
+            "true" => key.to_string(),
+            "false" => format!("no-{key}"),
+            _ => {
+              return Err(line_err(format!("Expected 'true' or 'false' for option '{key}', found '{val}'.")))
+            }
+          };
+          let opt = OptArgs::from_str(&name, false)
+            .map_err(|_| line_err(format!("Unknown optimization option '{name}'.")))?;
+          config.opts.push(ScopedOptArg { backend: None, opt: OptArg::Flag(opt) });
+        }
+        Some("warnings") => {
+          let warning =
+            WarningArgs::from_str(key, false).map_err(|_| line_err(format!("Unknown warning '{key}'.")))?;
+          let severity = match val {
+            "\"allow\"" => Severity::Allow,
+            "\"warn\"" => Severity::Warning,
+            "\"deny\"" => Severity::Error,
+            _ => {
+              let msg =
+                format!("Expected \"allow\", \"warn\" or \"deny\" for warning '{key}', found '{val}'.");
+              return Err(line_err(msg));
+            }
+          };
+          config.warnings.push((severity, warning));
+        }
+        _ => {
+          return Err(line_err(format!("The entry '{key}' must be inside an [opts] or [warnings] table.")))
+        }
+      }
+    }
+    Ok(config)
+  }
+}
+
+/// Removes the comment at the end of a config file line, if there's one.
+/// A '#' inside a quoted string is part of the string, not the start of a comment.
+fn strip_comment(line: &str) -> Result<&str, String> {
+  let mut in_str = false;
+  let mut escaped = false;
+  for (i, c) in line.char_indices() {
+    match c {
+      _ if escaped => escaped = false,
+      '\\' if in_str => escaped = true,
+      '"' => in_str = !in_str,
+      '#' if !in_str => return Ok(&line[..i]),
+      _ => {}
+    }
+  }
+  if in_str {
+    return Err(format!("Unterminated string in '{}'.", line.trim()));
+  }
+  Ok(line)
+}
+
+/// Parses the optimization options of the `BEND_OPTS` environment variable, if it's set.
+///
+/// The options are separated by spaces and written like in the command line, as in `-Ono-eta -Oc:merge`,
+/// with or without the `-O`.
+fn opts_from_env() -> Result<Vec<ScopedOptArg>, String> {
+  use clap::builder::TypedValueParser;
+  let Some(opts) = std::env::var_os(OPTS_ENV_VAR) else {
+    return Ok(vec![]);
+  };
+  let opts =
+    opts.into_string().map_err(|_| format!("The {OPTS_ENV_VAR} environment variable is not valid UTF-8."))?;
+  let cmd = Cli::command();
+  opts
+    .split_whitespace()
+    .filter(|opt| *opt != "-O")
+    .map(|opt| {
+      let opt = opt.strip_prefix("-O").unwrap_or(opt);
+      ScopedOptParser.parse_ref(&cmd, None, opt.as_ref()).map_err(|_| {
+        format!("Invalid optimization option '{opt}' in the {OPTS_ENV_VAR} environment variable. The options are the values of '-O', like 'no-eta' or 'c:merge'.")
+      })
+    })
+    .collect()
+}
+
+/// Parses a sequence of program arguments, in the same syntax as the ones given in the command line.
+fn parse_arguments(code: &str) -> Result<Vec<Term>, String> {
+  let mut parser = TermParser::new(code);
+  let mut args = vec![];
+  loop {
+    parser.skip_trivia();
+    if parser.is_eof() {
+      break;
+    }
+    args.push(parser.parse_term().map_err(|e| e.to_string())?);
+  }
+  Ok(args)
+}
+
+/// Reads a JSON file and converts its data to a program argument.
+fn read_json_argument(path: &Path) -> Result<Term, String> {
+  let code = std::fs::read_to_string(path)
+    .map_err(|e| format!("Failed to read JSON file '{}'.\n{e}", path.display()))?;
+  let value: serde_json::Value = serde_json::from_str(&code)
+    .map_err(|e| format!("Failed to parse JSON file '{}'.\n{e}", path.display()))?;
+  json_to_term(&value).map_err(|e| format!("Failed to convert JSON file '{}'.\n{e}", path.display()))
+}
+
+/// Evaluates each definition whose name starts with `test_` and contains `filter`.
+///
+/// A test passes if its result is a number other than 0 or a `True` constructor.
+/// Each test is evaluated separately, so an error or panic in one of them doesn't stop the others.
+fn run_tests(
+  book: &Book,
+  filter: Option<&str>,
+  run_opts: RunOpts,
+  compile_opts: CompileOpts,
+  diagnostics_cfg: DiagnosticsConfig,
+  print_stats: bool,
+) -> Result<(), Diagnostics> {
+  let tests = book
+    .defs
+    .values()
+    .filter(|def| !def.builtin && def.name.starts_with("test_"))
+    .filter(|def| filter.map_or(true, |filter| def.name.contains(filter)))
+    .map(|def| def.name.clone())
+    .collect::<Vec<_>>();
+
+  let total = tests.len();
+  println!("running {total} {}", if total == 1 { "test" } else { "tests" });
+  let mut failed = vec![];
+  let mut coverage = run_opts.coverage.then(Coverage::default);
+  for test in tests {
+    let res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+      eval_term_with_diagnostics(
+        book,
+        Term::r#ref(&test),
+        run_opts.clone(),
+        compile_opts.clone(),
+        diagnostics_cfg,
+      )
+    }));
+    let failure = match res {
+      Ok(Ok((term, stats, diags))) => {
+        if print_stats {
+          print!("{stats}");
+        }
+        if let (Some(coverage), Some(test_coverage)) = (&mut coverage, &stats.coverage) {
+          coverage.merge(test_coverage);
+        }
+        if is_test_success(&term) {
+          None
+        } else if run_opts.pretty {
+          let term = term.display_pretty_with_floats(0, run_opts.float_format);
+          Some(format!("{diags}Result:\n{term}"))
+        } else {
+          Some(format!("{diags}Result: {}", term.display_with_floats(run_opts.float_format)))
+        }
+      }
+      Ok(Err(diags)) => Some(diags.to_string()),
+      Err(_) => Some("The test panicked.".to_string()),
+    };
+    match failure {
+      None => println!("test {test} ... ok"),
+      Some(failure) => {
+        println!("test {test} ... FAILED");
+        failed.push((test, failure));
+      }
+    }
+  }
+
+  for (test, failure) in &failed {
+    println!("\n---- {test} ----\n{failure}");
+  }
+  let status = if failed.is_empty() { "ok" } else { "FAILED" };
+  println!("\ntest result: {status}. {} passed; {} failed", total - failed.len(), failed.len());
+  if let Some(coverage) = coverage {
+    print!("\n{coverage}");
+  }
+
+  if failed.is_empty() {
+    Ok(())
+  } else {
+    Err(format!("{} of {total} tests failed.", failed.len()).into())
+  }
+}
+
+/// Whether the result of a test counts as a success.
+fn is_test_success(term: &Term) -> bool {
+  match term {
+    Term::Num { val } => !val.is_zero(),
+    term => result_ctr(term).is_some_and(|nam| nam.rsplit_once('/').is_some_and(|(_, ctr)| ctr == "True")),
+  }
+}
+
+/// The name of the constructor without fields that a result was read back as, if any.
+fn result_ctr(term: &Term) -> Option<&str> {
+  match term {
+    // As read back with the tagged-scott encoding.
+    Term::Ref { nam } => Some(nam),
+    // As read back with the num-scott encoding: `λx (x Type/Ctr/tag)`.
+    Term::Lam { pat, bod, .. } => match (pat.as_ref(), bod.as_ref()) {
+      (Pattern::Var(Some(var)), Term::App { fun, arg, .. }) => match (fun.as_ref(), arg.as_ref()) {
+        (Term::Var { nam }, Term::Ref { nam: tag }) if nam == var => tag.strip_suffix("/tag"),
+        _ => None,
+      },
+      _ => None,
+    },
+    _ => None,
+  }
+}
+
+/// Reads lines from stdin until `:quit` or the end of the input.
+///
+/// Lines with definitions are added to the book, replacing the ones with the same name,
+/// while terms are evaluated as the entrypoint of the book.
+fn repl(
+  mut book: Book,
+  run_opts: RunOpts,
+  compile_opts: CompileOpts,
+  diagnostics_cfg: DiagnosticsConfig,
+  print_stats: bool,
+) {
+  let mut lines = std::io::stdin().lines();
+  loop {
+    print!("> ");
+    let _ = std::io::stdout().flush();
+    let Some(Ok(line)) = lines.next() else { break };
+    let line = line.trim();
+    if line.is_empty() {
+      continue;
+    }
+
+    match parse_repl_line(line, &book) {
+      Ok(ReplLine::Quit) => break,
+      Ok(ReplLine::Term(term)) => {
+        match eval_term_with_diagnostics(&book, term, run_opts.clone(), compile_opts.clone(), diagnostics_cfg)
+        {
+          Ok((term, stats, mut diags)) => {
+            diags.set_display_config(diagnostics_cfg);
+            eprint!("{diags}");
+            if run_opts.pretty {
+              println!("{}", term.display_pretty_with_floats(0, run_opts.float_format));
+            } else {
+              println!("{}", term.display_with_floats(run_opts.float_format));
+            }
+            if print_stats {
+              println!("{stats}");
+            }
+          }
+          Err(mut diags) => {
+            diags.set_display_config(diagnostics_cfg);
+            eprint!("{diags}");
+          }
+        }
+      }
+      Ok(ReplLine::Defs(new_book)) => book = new_book,
+      Err(mut diags) => {
+        diags.set_display_config(diagnostics_cfg);
+        eprint!("{diags}");
+      }
+    }
+  }
+}
+
+/// Sets the severity of the warnings given in the config file, and then of the ones given in the command line.
+fn set_warning_cfg_from_cli(
+  config: &Config,
+  mut cfg: DiagnosticsConfig,
+  warn_opts: CliWarnOpts,
+) -> DiagnosticsConfig {
   fn set(cfg: &mut DiagnosticsConfig, severity: Severity, cli_val: WarningArgs) {
     match cli_val {
       WarningArgs::All => {
@@ -384,6 +2610,15 @@ fn set_warning_cfg_from_cli(mut cfg: DiagnosticsConfig, warn_opts: CliWarnOpts)
         cfg.unused_definition = severity;
         cfg.repeated_bind = severity;
         cfg.recursion_cycle = severity;
+        cfg.shadowed_builtin = severity;
+        cfg.missing_base_case = severity;
+        cfg.ctr_arity = severity;
+        cfg.shadowed_bind = severity;
+        cfg.numeric_overflow = severity;
+        cfg.unused_bind = severity;
+        cfg.redundant_lambda = severity;
+        cfg.potential_exponential = severity;
+        cfg.match_arm_order = severity;
       }
       WarningArgs::IrrefutableMatch => cfg.irrefutable_match = severity,
       WarningArgs::RedundantMatch => cfg.redundant_match = severity,
@@ -391,9 +2626,23 @@ fn set_warning_cfg_from_cli(mut cfg: DiagnosticsConfig, warn_opts: CliWarnOpts)
       WarningArgs::UnusedDefinition => cfg.unused_definition = severity,
       WarningArgs::RepeatedBind => cfg.repeated_bind = severity,
       WarningArgs::RecursionCycle => cfg.recursion_cycle = severity,
+      WarningArgs::ShadowedBuiltin => cfg.shadowed_builtin = severity,
+      WarningArgs::MissingBaseCase => cfg.missing_base_case = severity,
+      WarningArgs::CtrArity => cfg.ctr_arity = severity,
+      WarningArgs::ShadowedBind => cfg.shadowed_bind = severity,
+      WarningArgs::NumericOverflow => cfg.numeric_overflow = severity,
+      WarningArgs::UnusedBind => cfg.unused_bind = severity,
+      WarningArgs::NamingConvention => cfg.naming_convention = severity,
+      WarningArgs::RedundantLambda => cfg.redundant_lambda = severity,
+      WarningArgs::PotentialExponential => cfg.potential_exponential = severity,
+      WarningArgs::MatchArmOrder => cfg.match_arm_order = severity,
     }
   }
 
+  for (severity, warning) in &config.warnings {
+    set(&mut cfg, *severity, warning.clone());
+  }
+
   let cmd = Cli::command();
   let matches = cmd.get_matches();
   let subcmd_name = matches.subcommand_name().expect("To have a subcommand");
@@ -408,9 +2657,13 @@ fn set_warning_cfg_from_cli(mut cfg: DiagnosticsConfig, warn_opts: CliWarnOpts)
         "allows" => set(&mut cfg, Severity::Allow, allows.next().unwrap()),
         "denies" => set(&mut cfg, Severity::Error, denies.next().unwrap()),
         "warns" => set(&mut cfg, Severity::Warning, warns.next().unwrap()),
+        "fail_on_warning" => (),
         _ => unreachable!(),
       }
     }
   }
+  if warn_opts.fail_on_warning {
+    cfg.deny_warnings();
+  }
   cfg
 }
@@ -0,0 +1,74 @@
+//! Parses the lines given to the REPL, which are either a command, a term to evaluate or new
+//! definitions for the book that the following lines can use.
+
+use crate::{
+  diagnostics::Diagnostics,
+  fun::{parser::TermParser, Book, SourceItem, Term},
+};
+use TSPL::Parser;
+
+/// What a line given to the REPL is.
+#[derive(Debug)]
+pub enum ReplLine {
+  /// The `:quit` command, which ends the REPL.
+  Quit,
+  /// A term, to be evaluated with the definitions of the book.
+  Term(Term),
+  /// The book with the definitions of the line added to it.
+  Defs(Book),
+}
+
+/// Parses a REPL line either as a command, as a term or as new definitions for the book.
+/// If both fail, returns the error of the parser that got further in the line.
+///
+/// The functions and types of the line replace the ones of the book with the same name,
+/// so that a definition can be fixed by writing it again.
+pub fn parse_repl_line(line: &str, book: &Book) -> Result<ReplLine, Diagnostics> {
+  if line.trim() == ":quit" {
+    return Ok(ReplLine::Quit);
+  }
+
+  let mut term_parser = TermParser::new(line);
+  let term_err = match term_parser.parse_term() {
+    Ok(term) => {
+      term_parser.skip_trivia();
+      if term_parser.is_eof() {
+        return Ok(ReplLine::Term(term));
+      }
+      term_parser.expected::<()>("end of input").unwrap_err()
+    }
+    Err(e) => e.to_string(),
+  };
+
+  let mut book_parser = TermParser::new(line);
+  match book_parser.parse_book(Book::default(), false) {
+    Ok(defs) => Ok(ReplLine::Defs(replace_defs(book, defs)?)),
+    Err(book_err) if *book_parser.index() > *term_parser.index() => Err(book_err.into()),
+    Err(_) => Err(term_err.into()),
+  }
+}
+
+/// Adds the definitions and types of `new` to a copy of the book, removing the user ones they replace.
+/// Built-ins can't be replaced, like in a file.
+fn replace_defs(book: &Book, new: Book) -> Result<Book, Diagnostics> {
+  let mut book = book.clone();
+  for name in new.defs.keys() {
+    if book.defs.get(name).is_some_and(|def| !def.builtin) {
+      book.defs.shift_remove(name);
+      book.allowed_warnings.remove(name);
+      book.def_spans.remove(name);
+      book.source_order.retain(|item| *item != SourceItem::Def(name.clone()));
+    }
+  }
+  for name in new.adts.keys() {
+    if book.adts.get(name).is_some_and(|adt| !adt.builtin) {
+      let adt = book.adts.shift_remove(name).unwrap();
+      book.source_order.retain(|item| *item != SourceItem::Adt(name.clone()));
+      for ctr in adt.ctrs.keys() {
+        book.ctrs.shift_remove(ctr);
+      }
+    }
+  }
+  book.merge(new)?;
+  Ok(book)
+}
@@ -1,10 +1,23 @@
 use bend::{
-  compile_book, desugar_book,
+  check_book, compile_book,
+  compile_cache::compile_book_cached,
+  desugar_book,
   diagnostics::{Diagnostics, DiagnosticsConfig, Severity},
-  fun::{load_book::do_parse_book, net_to_term::net_to_term, term_to_net::Labels, Book, Ctx, Name, Term},
-  hvm::display_hvm_book,
+  eval_term, format_book,
+  fun::{
+    load_book::do_parse_book, net_to_term::net_to_term, parser::TermParser, term_to_net::Labels, Book, Ctx,
+    Name, Term,
+  },
+  hvm::{
+    display_hvm_book,
+    interpreter::{interpret_hvm_book, InterpreterOpts},
+    split_c::split_c_program,
+    stream::TermChunk,
+  },
+  incremental::check_book_incremental,
   net::hvm_to_net::hvm_to_net,
-  run_book, AdtEncoding, CompileOpts, RunOpts,
+  repl::{parse_repl_line, ReplLine},
+  run_book, run_book_streaming, AdtEncoding, CompileOpts, PhaseInfo, RunOpts, RunStats,
 };
 use insta::assert_snapshot;
 use itertools::Itertools;
@@ -88,7 +101,7 @@ pub fn run_book_simple(
   compile_opts: CompileOpts,
   diagnostics_cfg: DiagnosticsConfig,
   args: Option<Vec<Term>>,
-) -> Result<(Term, String, Diagnostics), Diagnostics> {
+) -> Result<(Term, RunStats, Diagnostics), Diagnostics> {
   run_book(book, run_opts, compile_opts, diagnostics_cfg, args, "run").map(Option::unwrap)
 }
 
@@ -173,7 +186,7 @@ fn run_file() {
 
       let mut res = String::new();
 
-      for adt_encoding in [AdtEncoding::NumScott, AdtEncoding::Scott] {
+      for adt_encoding in [AdtEncoding::NumScott, AdtEncoding::Scott, AdtEncoding::TaggedScott] {
         let compile_opts = CompileOpts { adt_encoding, ..CompileOpts::default() };
         let (term, _, diags) =
           run_book_simple(book.clone(), run_opts.clone(), compile_opts, diagnostics_cfg, None)?;
@@ -184,6 +197,60 @@ fn run_file() {
   )
 }
 
+/// Reads the results in chunks, stopping after a few of them for the infinite ones.
+#[test]
+fn run_streaming() {
+  run_golden_test_dir(function_name!(), &|code, path| {
+    let _guard = RUN_MUTEX.lock().unwrap();
+    let book = do_parse_book(code, path, Book::builtins())?;
+    let diagnostics_cfg = DiagnosticsConfig {
+      recursion_cycle: Severity::Allow,
+      unused_definition: Severity::Allow,
+      ..DiagnosticsConfig::new(Severity::Error, true)
+    };
+
+    let mut res = String::new();
+    for adt_encoding in [AdtEncoding::NumScott, AdtEncoding::Scott, AdtEncoding::TaggedScott] {
+      let compile_opts = CompileOpts { adt_encoding, ..CompileOpts::default() };
+      let stream = run_book_streaming(book.clone(), RunOpts::default(), compile_opts, diagnostics_cfg, None)?;
+      writeln!(res, "{adt_encoding}:").unwrap();
+      for chunk in stream.take(8) {
+        match chunk? {
+          TermChunk::Element(term) => writeln!(res, "Element: {term}").unwrap(),
+          TermChunk::Rest(term) => writeln!(res, "Rest: {term}").unwrap(),
+        }
+      }
+      res.push('\n');
+    }
+    Ok(res)
+  })
+}
+
+/// Compares the interactions performed by match-heavy programs with the num-scott and tagged-scott encodings.
+///
+/// A tagged-scott match doesn't call the tag definition of the constructor, but it subtracts the
+/// first tag of the matched type, so it's only cheaper for `String`, whose first tag is 0.
+#[test]
+fn adt_encoding_interactions() {
+  let interactions = |file: &str, adt_encoding| {
+    let path = format!("{}{TESTS_PATH}adt_encoding_interactions/{file}", env!("CARGO_MANIFEST_DIR"));
+    let code = std::fs::read_to_string(&path).unwrap();
+    let mut book = do_parse_book(&code, Path::new(&path), Book::builtins()).unwrap();
+    let compile_opts = CompileOpts { adt_encoding, ..CompileOpts::default() };
+    let res = compile_book(&mut book, compile_opts, DiagnosticsConfig::default(), None).unwrap();
+    let (_, stats) = interpret_hvm_book(&res.hvm_book, &InterpreterOpts::default()).unwrap();
+    stats.interactions.unwrap()
+  };
+
+  let num_scott = interactions("tree_sum.bend", AdtEncoding::NumScott);
+  let tagged_scott = interactions("tree_sum.bend", AdtEncoding::TaggedScott);
+  assert_eq!(tagged_scott, num_scott, "matches on a user type");
+
+  let num_scott = interactions("string_len.bend", AdtEncoding::NumScott);
+  let tagged_scott = interactions("string_len.bend", AdtEncoding::TaggedScott);
+  assert!(tagged_scott < num_scott, "matches on a string: {tagged_scott} >= {num_scott}");
+}
+
 #[test]
 #[ignore = "while lazy execution is not implemented for hvm32"]
 fn run_lazy() {
@@ -213,11 +280,22 @@ fn readback_hvm() {
     let book = Book::default();
     let compat_net = hvm_to_net(&net);
     let mut diags = Diagnostics::default();
-    let term = net_to_term(&compat_net, &book, &Labels::default(), false, &mut diags);
+    let term = net_to_term(&compat_net, &book, &Labels::default(), false, None, &mut diags);
     Ok(format!("{}{}", diags, term))
   })
 }
 
+#[test]
+fn readback_depth_limit() {
+  // λa λ* λ* λ* a
+  let net = hvm::ast::CoreParser::new("(a (* (* (* a))))").parse_net().unwrap();
+  let compat_net = hvm_to_net(&net);
+  let mut diags = Diagnostics::default();
+  let term = net_to_term(&compat_net, &Book::default(), &Labels::default(), false, Some(2), &mut diags);
+  assert!(term.to_string().ends_with("<Invalid>"), "{term}");
+  assert!(diags.to_string().contains("nested more than 2 levels deep"), "{diags}");
+}
+
 #[test]
 fn simplify_matches() {
   run_golden_test_dir(function_name!(), &|code, path| {
@@ -245,7 +323,7 @@ fn simplify_matches() {
     ctx.book.make_var_names_unique();
     ctx.book.desugar_use();
     ctx.book.make_var_names_unique();
-    ctx.prune(false);
+    ctx.prune(false, &[]);
 
     Ok(ctx.book.to_string())
   })
@@ -261,11 +339,26 @@ fn parse_file() {
     ctx.book.encode_builtins();
     ctx.resolve_refs().expect("Resolve refs");
     ctx.desugar_match_defs().expect("Desugar match defs");
-    ctx.prune(false);
+    ctx.prune(false, &[]);
     Ok(book.to_string())
   })
 }
 
+#[test]
+fn parse_definitions() {
+  run_golden_test_dir(function_name!(), &|code, _| {
+    let mut parser = TermParser::new(code);
+    let mut out = String::new();
+    for def in parser.parse_definitions(Book::builtins(), false) {
+      match def {
+        Ok((_, def)) => writeln!(out, "{def}").unwrap(),
+        Err(err) => writeln!(out, "{err}").unwrap(),
+      }
+    }
+    Ok(out)
+  })
+}
+
 #[test]
 fn encode_pattern_match() {
   run_golden_test_dir(function_name!(), &|code, path| {
@@ -296,7 +389,7 @@ fn encode_pattern_match() {
       ctx.book.desugar_use();
       ctx.book.make_var_names_unique();
       ctx.book.linearize_vars();
-      ctx.prune(false);
+      ctx.prune(false, &[]);
 
       writeln!(result, "{adt_encoding}\n{}\n", ctx.book).unwrap();
     }
@@ -355,6 +448,30 @@ fn compile_entrypoint() {
   })
 }
 
+#[test]
+fn host_defs() {
+  run_golden_test_dir(function_name!(), &|code, path| {
+    let _guard = RUN_MUTEX.lock().unwrap();
+    let mut book = do_parse_book(code, path, Book::builtins())?;
+    for (name, net) in [("Host/const", "(a (* a))"), ("Host/answer", "b & @Host/const ~ (42 (* b))")] {
+      let net = hvm::ast::CoreParser::new(net).parse_net()?;
+      book.add_host_def(Name::new(name), net);
+    }
+    let diagnostics_cfg = DiagnosticsConfig::new(Severity::Error, true);
+
+    let res = compile_book(&mut book.clone(), CompileOpts::default(), diagnostics_cfg, None)?;
+    let mut out = format!("{}{}\n", res.diagnostics, display_hvm_book(&res.hvm_book));
+    let stream = run_book_streaming(book, RunOpts::default(), CompileOpts::default(), diagnostics_cfg, None)?;
+    for chunk in stream {
+      match chunk? {
+        TermChunk::Element(term) => writeln!(out, "Element: {term}").unwrap(),
+        TermChunk::Rest(term) => writeln!(out, "Result: {term}").unwrap(),
+      }
+    }
+    Ok(out)
+  })
+}
+
 #[test]
 #[ignore = "while execution with different entrypoints is not implemented for hvm32"]
 fn run_entrypoint() {
@@ -370,6 +487,17 @@ fn run_entrypoint() {
   })
 }
 
+/// Formats each program, checking that formatting the result again doesn't change it.
+#[test]
+fn format() {
+  run_golden_test_dir(function_name!(), &|code, path| {
+    let formatted = format_book(code, path)?;
+    let reformatted = format_book(&formatted, path)?;
+    assert_eq!(formatted, reformatted);
+    Ok(formatted)
+  })
+}
+
 #[test]
 fn cli() {
   run_golden_test_dir(function_name!(), &|_code, path| {
@@ -382,8 +510,11 @@ fn cli() {
     args_file.read_to_string(&mut args_buf).expect("Read args");
     let args = args_buf.lines();
 
-    let output =
-      std::process::Command::new(env!("CARGO_BIN_EXE_bend")).args(args).output().expect("Run command");
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_bend"))
+      .arg("--color=always")
+      .args(args)
+      .output()
+      .expect("Run command");
     let res =
       format!("{}{}", String::from_utf8_lossy(&output.stderr), String::from_utf8_lossy(&output.stdout));
     Ok(res)
@@ -402,6 +533,37 @@ fn mutual_recursion() {
   })
 }
 
+#[test]
+fn dedup_defs() {
+  run_golden_test_dir(function_name!(), &|code, path| {
+    let diagnostics_cfg = DiagnosticsConfig::new(Severity::Allow, true);
+    let mut book = do_parse_book(code, path, Book::builtins())?;
+    let opts = CompileOpts { dedup_defs: true, ..CompileOpts::default() };
+    let res = compile_book(&mut book, opts, diagnostics_cfg, None)?;
+    Ok(format!("{}{}", res.diagnostics, display_hvm_book(&res.hvm_book)))
+  })
+}
+
+/// Evaluates each program with and without optimizing the nets, in this process,
+/// checking that the results are the same and that no interactions are added.
+#[test]
+fn optimize_nets() {
+  run_golden_test_dir(function_name!(), &|code, path| {
+    let run = |optimize_nets| {
+      let book = do_parse_book(code, path, Book::builtins())?;
+      let compile_opts = CompileOpts { optimize_nets, ..CompileOpts::default() };
+      let run_opts = RunOpts { profile: true, ..RunOpts::default() };
+      run_book_simple(book, run_opts, compile_opts, DiagnosticsConfig::default(), None)
+    };
+    let (term, stats, diags) = run(false)?;
+    let (opt_term, opt_stats, _) = run(true)?;
+    assert_eq!(term.to_string(), opt_term.to_string());
+    let (itrs, opt_itrs) = (stats.interactions.unwrap(), opt_stats.interactions.unwrap());
+    assert!(opt_itrs <= itrs);
+    Ok(format!("{diags}Result: {term}\nInteractions: {itrs} -> {opt_itrs}"))
+  })
+}
+
 #[test]
 #[ignore = "while IO is not implemented for hvm32"]
 fn io() {
@@ -480,6 +642,391 @@ fn scott_triggers_unused() {
   })
 }
 
+#[test]
+fn check_only_changed() {
+  let versions = [
+    "Foo = λx (+ x 1)\nBar = λx (Foo (Foo x))\nUnused = λx λx x\nmain = (Bar 1)",
+    // Changes a definition used by others.
+    "Foo = λx (+ x 2)\nBar = λx (Foo (Foo x))\nUnused = λx λx x\nmain = (Bar 1)",
+    // Changes a definition that stays unused.
+    "Foo = λx (+ x 2)\nBar = λx (Foo (Foo x))\nUnused = λy λy (+ y 1)\nmain = (Bar 1)",
+    // Changes which definitions are used.
+    "Foo = λx (+ x 2)\nBar = λx (Foo (Foo x))\nUnused = λy λy (+ y 1)\nmain = (Unused (Bar 1))",
+    "Foo = λx (+ x y)\nBar = λx (Foo (Foo x))\nUnused = λy λy (+ y 1)\nmain = (Unused (Bar 1))",
+    "Foo = λx (+ x 1)\nBar = λx (Foo (Foo x))\nUnused = λx λx x\nmain = (Bar 1)",
+    "Foo = λx (+ x 1)\nBar = λx (Foo (Foo x))\nUnused = λx λx x\nmain = (Bar 1)",
+  ];
+  let cache_path = std::env::temp_dir().join(format!("bend-check-cache-{}", std::process::id()));
+  let _ = std::fs::remove_file(&cache_path);
+  let show = |res: Result<Diagnostics, Diagnostics>| match res {
+    Ok(diags) => format!("Ok: {diags}"),
+    Err(diags) => format!("Err: {diags}"),
+  };
+  for code in versions {
+    let book = do_parse_book(code, Path::new("check_only_changed.bend"), Book::builtins()).unwrap();
+    let diagnostics_cfg = DiagnosticsConfig::default();
+    let full = check_book(&mut book.clone(), diagnostics_cfg, CompileOpts::default());
+    let incremental = check_book_incremental(&book, diagnostics_cfg, CompileOpts::default(), &cache_path);
+    assert_eq!(show(incremental), show(full), "{code}");
+  }
+  let _ = std::fs::remove_file(&cache_path);
+}
+
+#[test]
+fn compile_cached() {
+  let code = "
+Unused = λx λx x
+Sum = λxs match xs { List/Nil: 0.5; List/Cons: (+ xs.head (Sum xs.tail)) }
+main = (Sum [1.25, -2.0, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16])
+";
+  let cache_dir = std::env::temp_dir().join(format!("bend-compile-cache-{}", std::process::id()));
+  let _ = std::fs::remove_dir_all(&cache_dir);
+  let book = do_parse_book(code, Path::new("compile_cached.bend"), Book::builtins()).unwrap();
+  let diagnostics_cfg = DiagnosticsConfig::default();
+  let show = |res: bend::CompileResult| format!("{}{}", res.diagnostics, display_hvm_book(&res.hvm_book));
+
+  let cold = show(compile_book(&mut book.clone(), CompileOpts::default(), diagnostics_cfg, None).unwrap());
+  // The first compilation saves the result and the second one reads it.
+  for _ in 0..2 {
+    let res =
+      compile_book_cached(&mut book.clone(), CompileOpts::default(), diagnostics_cfg, &cache_dir, None);
+    assert_eq!(show(res.unwrap()), cold);
+  }
+  assert_eq!(std::fs::read_dir(&cache_dir).unwrap().count(), 1);
+
+  // Different options don't reuse the result.
+  let opts = CompileOpts::default().set_all();
+  let cold = show(compile_book(&mut book.clone(), opts.clone(), diagnostics_cfg, None).unwrap());
+  let res = compile_book_cached(&mut book.clone(), opts.clone(), diagnostics_cfg, &cache_dir, None);
+  assert_eq!(show(res.unwrap()), cold);
+  assert_eq!(std::fs::read_dir(&cache_dir).unwrap().count(), 2);
+
+  // A cache hit is reported to the hook instead of the phases of the compilation.
+  let mut phases = vec![];
+  let mut hook = |phase: PhaseInfo| phases.push(phase.name);
+  let res = compile_book_cached(&mut book.clone(), opts, diagnostics_cfg, &cache_dir, Some(&mut hook));
+  assert_eq!(show(res.unwrap()), cold);
+  assert_eq!(phases, ["cache-hit"]);
+
+  // Failing to save the result is only a warning.
+  let file = cache_dir.join("not_a_dir");
+  std::fs::write(&file, "").unwrap();
+  let res = compile_book_cached(&mut book.clone(), CompileOpts::default(), diagnostics_cfg, &file, None);
+  assert!(res.unwrap().diagnostics.to_string().contains("Failed to write the compilation cache"));
+  let _ = std::fs::remove_dir_all(&cache_dir);
+}
+
+#[test]
+fn bend_opts_env() {
+  use std::io::Write as _;
+  let check = |env: &str, args: &[&str]| {
+    let mut child = std::process::Command::new(env!("CARGO_BIN_EXE_bend"))
+      .env("BEND_OPTS", env)
+      .arg("check")
+      .args(args)
+      .arg("-")
+      .stdin(std::process::Stdio::piped())
+      .stdout(std::process::Stdio::piped())
+      .stderr(std::process::Stdio::piped())
+      .spawn()
+      .expect("Run command");
+    child.stdin.take().unwrap().write_all("main = λa λb λc (a b c)".as_bytes()).unwrap();
+    let output = child.wait_with_output().unwrap();
+    (output.status.success(), String::from_utf8_lossy(&output.stderr).to_string())
+  };
+  assert!(check("", &[]).0);
+  // The options of the environment variable are applied.
+  assert!(!check("-Ocheck-net-size -Omax-net-size=1", &[]).0);
+  assert!(!check("check-net-size max-net-size=1", &[]).0);
+  // The ones in the command line override them.
+  assert!(check("-Ocheck-net-size -Omax-net-size=1", &["-Ono-check-net-size"]).0);
+  let (success, stderr) = check("-Ono-eta -Ofoo", &[]);
+  assert!(!success);
+  assert!(stderr.contains("Invalid optimization option 'foo' in the BEND_OPTS environment variable."));
+}
+
+#[test]
+fn compile_deterministic() {
+  // The functions generated for `bend` and `fold` capture several free variables.
+  let code = "
+type Tree = (node ~lft ~rgt) | (leaf val)
+
+main =
+  let depth = 2
+  let a = 1;
+  let b = 2;
+  let c = 3;
+  let tree = bend n = 0 {
+    when (< n depth):
+      (Tree/node (fork (+ n a)) (fork (+ n b)))
+    else:
+      (Tree/leaf c)
+  }
+  fold tree {
+    Tree/node: (+ tree.lft tree.rgt)
+    Tree/leaf: (+ tree.val (+ a (* b c)))
+  }
+";
+  let compile = || {
+    let mut book = do_parse_book(code, Path::new("compile_deterministic.bend"), Book::builtins()).unwrap();
+    let res = compile_book(&mut book, CompileOpts::default(), DiagnosticsConfig::default(), None).unwrap();
+    format!("{book}\n{}", display_hvm_book(&res.hvm_book))
+  };
+  let first = compile();
+  for _ in 0..8 {
+    assert_eq!(compile(), first);
+  }
+}
+
+#[test]
+fn compile_name_map() {
+  let code = "
+type Tree = (node ~lft ~rgt) | (leaf val)
+
+Tree/sum = λtree fold tree {
+  Tree/node: (+ tree.lft tree.rgt)
+  Tree/leaf: tree.val
+}
+
+Main = (Tree/sum bend n = 0 {
+  when (< n 2):
+    (Tree/node (fork (+ n 1)) (fork (+ n 1)))
+  else:
+    (Tree/leaf n)
+})
+";
+  let mut book = do_parse_book(code, Path::new("compile_name_map.bend"), Book::builtins()).unwrap();
+  let res = compile_book(&mut book, CompileOpts::default(), DiagnosticsConfig::default(), None).unwrap();
+  let source = |net: &str| res.name_map[net].to_string();
+
+  assert_eq!(res.name_map.keys().sorted().collect_vec(), res.hvm_book.defs.keys().sorted().collect_vec());
+  assert_eq!(source("main"), "Main");
+  assert_eq!(source("Tree/sum"), "Tree/sum");
+  // The functions generated for the `bend` and the `fold` map to the definitions that contain them.
+  let generated = res.hvm_book.defs.keys().filter(|net| net.contains("__")).collect_vec();
+  assert!(
+    generated.iter().any(|net| net.starts_with("Main__bend") && source(net) == "Main"),
+    "{generated:?}"
+  );
+  assert!(
+    generated.iter().any(|net| net.starts_with("Tree/sum__fold") && source(net) == "Tree/sum"),
+    "{generated:?}"
+  );
+}
+
+#[test]
+fn term_free_vars_and_references() {
+  let names = |names: &[&str]| names.iter().map(|nam| Name::new(*nam)).collect::<Vec<_>>();
+  let mut term = TermParser::new("λx (Foo λy λx (Bar x y z) λBaz (Baz x) w)").parse_term().unwrap();
+
+  // Before resolving the references, they are free variables of the term.
+  assert_eq!(term.free_vars().into_keys().collect::<Vec<_>>(), names(&["Bar", "Foo", "w", "z"]));
+  assert!(term.references().is_empty());
+
+  let def_names = names(&["Foo", "Bar", "Baz"]).into_iter().collect();
+  term.resolve_refs(&def_names, None, &mut HashMap::new()).unwrap();
+  assert_eq!(term.free_vars().into_keys().collect::<Vec<_>>(), names(&["w", "z"]));
+  // `Baz` is shadowed by the lambda, so it's not a reference.
+  assert_eq!(term.references(), names(&["Bar", "Foo"]).into_iter().collect());
+}
+
+#[test]
+fn rename_def() {
+  let code = "
+Sum = λn switch n {
+  0: 0
+  _: (+ n (Sum n-1))
+}
+# The lambda and the let bind variables that shadow the definition.
+Shadowed = λSum (Sum 1)
+Let = let Sum = λx x; (Sum (Sum 2))
+main = (Sum (Shadowed λx x))
+";
+  let mut book = do_parse_book(code, Path::new("rename_def.bend"), Book::default()).unwrap();
+  book.entrypoint = Some(Name::new("main"));
+  let body = |book: &Book, def: &str| book.defs[&Name::new(def)].rules[0].body.to_string();
+
+  // Fails without changing anything if the new name is taken or the old one doesn't exist.
+  let before = book.to_string();
+  assert!(book.rename_def(&Name::new("Sum"), &Name::new("Shadowed")).is_err());
+  assert!(book.rename_def(&Name::new("Missing"), &Name::new("Other")).is_err());
+  assert_eq!(book.to_string(), before);
+
+  book.rename_def(&Name::new("Sum"), &Name::new("Total")).unwrap();
+  assert_eq!(book.defs.keys().map(|nam| nam.to_string()).collect_vec(), ["Total", "Shadowed", "Let", "main"]);
+  assert!(body(&book, "Total").contains("(Total n-1)"));
+  assert_eq!(body(&book, "Shadowed"), "λSum (Sum 1)");
+  assert!(!body(&book, "Let").contains("Total"));
+  assert_eq!(body(&book, "main"), "(Total (Shadowed λx x))");
+
+  book.rename_def(&Name::new("main"), &Name::new("start")).unwrap();
+  assert_eq!(book.entrypoint, Some(Name::new("start")));
+}
+
+#[test]
+fn eval_term_entrypoints() {
+  let _guard = RUN_MUTEX.lock().unwrap();
+  let eval = |code: &str, term: &str| {
+    let book = do_parse_book(code, Path::new("eval_term.bend"), Book::builtins()).unwrap();
+    let term = TermParser::new(term).parse_term().unwrap();
+    eval_term(&book, term, RunOpts::default(), CompileOpts::default()).map(|(res, _)| res.to_string())
+  };
+
+  // The entrypoint of the book is kept as a normal definition, whichever its name.
+  assert_eq!(eval("double x = (* x 2)\nmain = 3", "(double main)").unwrap(), "6");
+  assert_eq!(eval("Main = 3", "(+ Main 1)").unwrap(), "4");
+  assert_eq!(eval("#[entrypoint]\nstart = 3", "(+ start 1)").unwrap(), "4");
+  assert_eq!(eval("main = 3\nMain = 5", "(+ main Main)").unwrap(), "8");
+
+  let err = eval("main = 3", "(+ main x)").unwrap_err();
+  assert!(err.to_string().contains("Unbound variable 'x' in the evaluated term."));
+}
+
+#[test]
+fn repl_lines() {
+  let defs = |line: &str, book: &Book| match parse_repl_line(line, book) {
+    Ok(ReplLine::Defs(book)) => book,
+    res => panic!("{line:?} should be parsed as definitions, got {res:?}"),
+  };
+  let body = |book: &Book, def: &str| book.defs[&Name::new(def)].rules[0].body.to_string();
+  let book = Book::builtins();
+
+  assert!(matches!(parse_repl_line(" :quit ", &book), Ok(ReplLine::Quit)));
+  assert!(matches!(parse_repl_line("(double (Map/get map 1))", &book), Ok(ReplLine::Term(_))));
+
+  // Writing a definition or a type again replaces it.
+  let book = defs("double x = (* x 2)", &book);
+  assert_eq!(body(&book, "double"), "(* x 2)");
+  let book = defs("double x = (+ x x)", &book);
+  assert_eq!(body(&book, "double"), "(+ x x)");
+  let book = defs("type Color = Red | Green", &book);
+  let book = defs("type Color = Red | Blue", &book);
+  assert_eq!(
+    book.adts[&Name::new("Color")].ctrs.keys().map(|ctr| ctr.to_string()).collect_vec(),
+    ["Color/Red", "Color/Blue"]
+  );
+  assert!(!book.ctrs.contains_key(&Name::new("Color/Green")));
+
+  // Built-ins can't be replaced, and neither can a constructor by a function.
+  let err = parse_repl_line("Map/empty = 0", &book).unwrap_err();
+  assert!(err.to_string().contains("Redefinition of function 'Map/empty'."));
+  let err = parse_repl_line("Color/Red = 0", &book).unwrap_err();
+  assert!(err.to_string().contains("Redefinition of function 'Color/Red'."));
+
+  // The error is the one of the parser that got further in the line.
+  let err = parse_repl_line("(+ 1 2", &book).unwrap_err();
+  assert!(err.has_errors());
+  let err = parse_repl_line("foo = (+ 1", &book).unwrap_err();
+  assert!(err.has_errors());
+}
+
+#[test]
+fn result_display_parses() {
+  // Results printed with `bend run --result-only` can be read back as program arguments.
+  let code = r#"(1, ([+2, -3, 3.5, +inf, -inf, +NaN], ("a\nb", λa λb {a b})))"#;
+  let shown = TermParser::new(code).parse_term().unwrap().to_string();
+  let reparsed = TermParser::new(&shown).parse_term().unwrap();
+  assert_eq!(reparsed.to_string(), shown);
+}
+
+#[test]
+fn adt_infos() {
+  let code = "
+type Color = Red | Green | Blue
+type Tree = (Node ~lft val ~rgt) | Leaf
+main = *
+";
+  let book = do_parse_book(code, Path::new("adt_infos.bend"), Book::builtins()).unwrap();
+  let ctrs = |encoding: AdtEncoding, name: &str| {
+    let adts = book.adt_infos(encoding);
+    let adt = adts.into_iter().find(|adt| adt.name == name).unwrap();
+    adt.ctrs.into_iter().map(|ctr| (ctr.name.to_string(), ctr.arity(), ctr.tag)).collect_vec()
+  };
+
+  // Nullary constructors have no fields.
+  let color = ctrs(AdtEncoding::NumScott, "Color");
+  assert_eq!(
+    color,
+    [("Color/Red".into(), 0, Some(0)), ("Color/Green".into(), 0, Some(1)), ("Color/Blue".into(), 0, Some(2))]
+  );
+
+  let adts = book.adt_infos(AdtEncoding::NumScott);
+  let list = adts.iter().find(|adt| adt.name == "List").unwrap();
+  assert!(list.builtin);
+  let cons = &list.ctrs[1];
+  assert_eq!(cons.name, "List/Cons");
+  assert_eq!(
+    cons.fields.iter().map(|field| (field.nam.to_string(), field.rec)).collect_vec(),
+    [("head".into(), false), ("tail".into(), true)]
+  );
+
+  let tree = adts.iter().find(|adt| adt.name == "Tree").unwrap();
+  assert!(!tree.builtin);
+  assert_eq!(tree.ctrs[0].fields.iter().map(|field| field.rec).collect_vec(), [true, false, true]);
+
+  // Scott has no tags, and tagged-scott numbers the constructors across all the datatypes.
+  assert!(ctrs(AdtEncoding::Scott, "Tree").iter().all(|(_, _, tag)| tag.is_none()));
+  let tags =
+    book.adt_infos(AdtEncoding::TaggedScott).into_iter().flat_map(|adt| adt.ctrs).map(|ctr| ctr.tag.unwrap());
+  assert!(tags.clone().all_unique());
+  assert_eq!(tags.count(), book.ctrs.len());
+}
+
+#[test]
+fn split_c() {
+  let path = format!("{}{TESTS_PATH}split_c/hvm_gen_c.c", env!("CARGO_MANIFEST_DIR"));
+  let code = std::fs::read_to_string(path).unwrap();
+  let split = split_c_program(&code, 3, "out.h").unwrap();
+  assert_eq!(split.units.len(), 3);
+
+  // Each definition is compiled in exactly one unit, and declared in the header for the others.
+  for def in ["main", "Era", "Id", "Pair"] {
+    let definition = format!("bool interact_call_{def}(Net *net, TM *tm, Port a, Port b) {{");
+    assert_eq!(split.units.iter().filter(|unit| unit.contains(&definition)).count(), 1, "{def}");
+    assert!(split.header.contains(&format!("bool interact_call_{def}(Net *net, TM *tm, Port a, Port b);")));
+  }
+  // The runtime and `main` stay in the first unit, and the static variables out of the header.
+  assert!(split.units[0].contains("int main("));
+  assert!(split.units[1..].iter().all(|unit| unit.starts_with("#include \"out.h\"")));
+  assert!(!split.header.contains("GLOBAL_SUM") && !split.header.contains("BOOK_BUF"));
+  assert!(split.header.contains("extern "));
+
+  // The units compile and link together, if there's a C compiler to check it.
+  let dir = std::env::temp_dir().join(format!("bend-split-c-{}", std::process::id()));
+  std::fs::create_dir_all(&dir).unwrap();
+  std::fs::write(dir.join("out.h"), &split.header).unwrap();
+  let units = split.units.iter().enumerate().map(|(i, unit)| {
+    let unit_path = dir.join(if i == 0 { "out.c".to_string() } else { format!("out_{i}.c") });
+    std::fs::write(&unit_path, unit).unwrap();
+    unit_path
+  });
+  let units = units.collect_vec();
+  let compiled = std::process::Command::new("cc")
+    .args(&units)
+    .args(["-w", "-o"])
+    .arg(dir.join("out"))
+    .args(["-lpthread", "-lm"])
+    .output();
+  if let Ok(compiled) = compiled {
+    assert!(compiled.status.success(), "{}", String::from_utf8_lossy(&compiled.stderr));
+  }
+  let _ = std::fs::remove_dir_all(&dir);
+
+  // A static function copied to the header can't use a static variable, since each unit has its own copy.
+  let code = "
+static int counter = 0;
+static int bump(int step) { counter += step; return counter; }
+static int twice(int counter) { return counter * 2; }
+bool interact_call_main(void) { return bump(1); }
+bool interact_call_Foo(void) { return twice(1); }
+int main() { return 0; }
+";
+  let err = split_c_program(code, 2, "out.h").unwrap_err();
+  assert!(err.contains("'bump'") && err.contains("'counter'"), "{err}");
+  let code = code.replace("counter += step; return counter;", "return step;");
+  assert!(split_c_program(&code, 2, "out.h").is_ok());
+}
+
 // TODO: also run the long string file to test the readback
 #[test]
 fn compile_long() {